@@ -27,7 +27,10 @@
 
         craneLib = crane.mkLib pkgs;
         markdownFilter = path: _type: builtins.match ".*md$" path != null;
-        markdownOrCargo = path: type: (markdownFilter path type) || (craneLib.filterCargoSources path type);
+        fixtureFilter = path: _type: builtins.match ".*/tests/fixtures(/.*)?$" path != null;
+        markdownOrCargo =
+          path: type:
+          (markdownFilter path type) || (fixtureFilter path type) || (craneLib.filterCargoSources path type);
         # Common arguments for all crane builds
         commonArgs = {
           src = pkgs.lib.cleanSourceWith {
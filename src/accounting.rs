@@ -0,0 +1,950 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::api::core::v1::Pod;
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    compat::to_utc,
+    resources::{is_binary_resource, pod_requests, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Seconds per hour
+const SECONDS_PER_HOUR: i64 = 3600;
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum AccountingError {
+    /// Samples of a series are not in chronological order
+    #[error("sample at {current} is older than the previous sample at {previous}")]
+    UnorderedSamples {
+        previous: DateTime<Utc>,
+        current: DateTime<Utc>,
+    },
+
+    /// An interval ends before it starts
+    #[error("interval ends at {end} before it starts at {start}")]
+    InvalidInterval {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+
+    /// A pod has neither a start time nor a creation timestamp
+    #[error("pod {0} has not started")]
+    NotStarted(String),
+
+    /// The integrated usage exceeds the range of a decimal
+    #[error("usage of {0} overflows")]
+    Overflow(String),
+
+    /// A quantity of the pod could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+
+    /// The price table is not valid JSON
+    #[cfg(feature = "serde")]
+    #[error("invalid JSON price table: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The price table is not valid YAML
+    #[cfg(feature = "serde")]
+    #[error("invalid YAML price table: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+// --- Units ---
+
+/// Unit of time-integrated usage. Usage is accumulated exactly in base units
+/// times seconds, e.g., core-seconds or byte-seconds, and only converted
+/// into coarser units when reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum UsageUnit {
+    /// One core for one second
+    #[cfg_attr(feature = "serde", serde(rename = "core-seconds"))]
+    CoreSeconds,
+    /// One core for one hour
+    #[cfg_attr(feature = "serde", serde(rename = "core-hours"))]
+    CoreHours,
+    /// One byte for one second
+    #[cfg_attr(feature = "serde", serde(rename = "byte-seconds"))]
+    ByteSeconds,
+    /// One gibibyte for one hour
+    #[cfg_attr(feature = "serde", serde(rename = "GiB-hours"))]
+    GibibyteHours,
+    /// One gigabyte for one hour
+    #[cfg_attr(feature = "serde", serde(rename = "GB-hours"))]
+    GigabyteHours,
+    /// One unit of a countable resource, e.g., a GPU, for one second
+    #[cfg_attr(feature = "serde", serde(rename = "unit-seconds"))]
+    UnitSeconds,
+    /// One unit of a countable resource for one hour
+    #[cfg_attr(feature = "serde", serde(rename = "unit-hours"))]
+    UnitHours,
+}
+
+impl UsageUnit {
+    /// Returns the name of the unit, e.g., `GiB-hours`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageUnit::CoreSeconds => "core-seconds",
+            UsageUnit::CoreHours => "core-hours",
+            UsageUnit::ByteSeconds => "byte-seconds",
+            UsageUnit::GibibyteHours => "GiB-hours",
+            UsageUnit::GigabyteHours => "GB-hours",
+            UsageUnit::UnitSeconds => "unit-seconds",
+            UsageUnit::UnitHours => "unit-hours",
+        }
+    }
+
+    /// Returns the unit usage of a resource is usually billed in, i.e.,
+    /// core-hours for CPU, GiB-hours for memory and storage and unit-hours
+    /// for everything else.
+    pub fn for_resource(resource: &str) -> Self {
+        if resource == "cpu" {
+            UsageUnit::CoreHours
+        } else if is_binary_resource(resource) {
+            UsageUnit::GibibyteHours
+        } else {
+            UsageUnit::UnitHours
+        }
+    }
+
+    /// Returns the number of base unit seconds in one unit.
+    pub fn base_seconds(&self) -> Decimal {
+        match self {
+            UsageUnit::CoreSeconds | UsageUnit::ByteSeconds | UsageUnit::UnitSeconds => {
+                Decimal::ONE
+            }
+            UsageUnit::CoreHours | UsageUnit::UnitHours => Decimal::from(SECONDS_PER_HOUR),
+            UsageUnit::GibibyteHours => Decimal::from(SECONDS_PER_HOUR << 30),
+            UsageUnit::GigabyteHours => Decimal::from(SECONDS_PER_HOUR * 1_000_000_000),
+        }
+    }
+
+    /// Converts usage in base unit seconds into this unit. Conversions into
+    /// hours are rounded to the precision of a decimal if they do not
+    /// terminate.
+    ///
+    /// ```rust
+    /// use kube_quantity::accounting::UsageUnit;
+    /// use rust_decimal::Decimal;
+    ///
+    /// // 512Mi for 3 hours
+    /// let usage = Decimal::from((3 * 3600_i64) << 29);
+    ///
+    /// assert_eq!(UsageUnit::GibibyteHours.convert(usage).to_string(), "1.5");
+    /// ```
+    pub fn convert(&self, base_seconds: Decimal) -> Decimal {
+        (base_seconds / self.base_seconds()).normalize()
+    }
+}
+
+// --- Integration ---
+
+/// How usage evolves between two samples.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// A sample holds until the next one, e.g., requests that change at
+    /// discrete points in time
+    #[default]
+    Step,
+    /// Usage changes linearly between samples, e.g., gauges scraped at an
+    /// interval
+    Linear,
+}
+
+/// A quantity observed at a point in time.
+#[derive(Debug, Clone)]
+pub struct TimedQuantity {
+    /// Time of the observation
+    pub timestamp: DateTime<Utc>,
+    /// Observed quantity
+    pub value: ParsedQuantity,
+}
+
+/// Integrates chronologically ordered samples over time and returns the
+/// exact usage in base unit seconds, e.g., core-seconds for CPU. The last
+/// sample only ends the series, as nothing is known about the time after
+/// it.
+///
+/// ```rust
+/// use chrono::{DateTime, TimeDelta};
+/// use kube_quantity::accounting::{integrate, Interpolation, TimedQuantity};
+///
+/// let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+/// let samples: Vec<TimedQuantity> = [(0, "500m"), (60, "1"), (120, "1")]
+///     .into_iter()
+///     .map(|(seconds, value)| TimedQuantity {
+///         timestamp: start + TimeDelta::seconds(seconds),
+///         value: value.try_into().unwrap(),
+///     })
+///     .collect();
+///
+/// assert_eq!(integrate(&samples, Interpolation::Step).unwrap().to_string(), "90");
+/// assert_eq!(integrate(&samples, Interpolation::Linear).unwrap().to_string(), "105");
+/// ```
+pub fn integrate(
+    samples: &[TimedQuantity],
+    interpolation: Interpolation,
+) -> Result<Decimal, AccountingError> {
+    let mut total = Decimal::ZERO;
+
+    for pair in samples.windows(2) {
+        let (previous, current) = (&pair[0], &pair[1]);
+        if current.timestamp < previous.timestamp {
+            return Err(AccountingError::UnorderedSamples {
+                previous: previous.timestamp,
+                current: current.timestamp,
+            });
+        }
+
+        let overflow = || AccountingError::Overflow(current.value.to_string());
+        let value = match interpolation {
+            Interpolation::Step => previous.value.checked_base_decimal().ok_or_else(overflow)?,
+            Interpolation::Linear => {
+                previous
+                    .value
+                    .checked_base_decimal()
+                    .zip(current.value.checked_base_decimal())
+                    .and_then(|(previous, current)| previous.checked_add(current))
+                    .ok_or_else(overflow)?
+                    / Decimal::TWO
+            }
+        };
+
+        total = value
+            .checked_mul(seconds(current.timestamp - previous.timestamp))
+            .and_then(|usage| total.checked_add(usage))
+            .ok_or_else(overflow)?;
+    }
+
+    Ok(total.normalize())
+}
+
+/// Returns the exact number of seconds of a duration.
+fn seconds(delta: TimeDelta) -> Decimal {
+    Decimal::from(delta.num_seconds()) + Decimal::new(i64::from(delta.subsec_nanos()), 9)
+}
+
+// --- Ledger ---
+
+/// Pod that usage is attributed to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PodRef {
+    /// Namespace of the pod
+    pub namespace: String,
+    /// Name of the pod
+    pub name: String,
+    /// Labels of the pod, used for grouping
+    pub labels: BTreeMap<String, String>,
+}
+
+impl PodRef {
+    /// Returns the reference of a pod from its metadata.
+    pub fn from_pod(pod: &Pod) -> Self {
+        Self {
+            namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+            name: pod.metadata.name.clone().unwrap_or_default(),
+            labels: pod.metadata.labels.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Resources requested by a pod during an interval.
+#[derive(Debug, Clone)]
+pub struct RequestInterval {
+    /// Pod that requested the resources
+    pub pod: PodRef,
+    /// Start of the interval
+    pub start: DateTime<Utc>,
+    /// End of the interval
+    pub end: DateTime<Utc>,
+    /// Requested resources
+    pub requests: ParsedResourceList,
+}
+
+impl RequestInterval {
+    /// Returns the requests of a pod from its start time, or its creation
+    /// timestamp if it has not been scheduled, until `end`.
+    pub fn from_pod(pod: &Pod, end: DateTime<Utc>) -> Result<Self, AccountingError> {
+        let pod_ref = PodRef::from_pod(pod);
+        let start = pod
+            .status
+            .as_ref()
+            .and_then(|status| status.start_time.as_ref())
+            .or(pod.metadata.creation_timestamp.as_ref())
+            .map(to_utc)
+            .ok_or_else(|| AccountingError::NotStarted(pod_ref.name.clone()))?;
+        let requests = pod_requests(
+            pod.spec
+                .as_ref()
+                .ok_or_else(|| AccountingError::NotStarted(pod_ref.name.clone()))?,
+        )?;
+
+        Ok(Self {
+            pod: pod_ref,
+            start,
+            end,
+            requests,
+        })
+    }
+}
+
+/// Usage of a pod for a single resource.
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    /// Pod the usage is attributed to
+    pub pod: PodRef,
+    /// Name of the resource
+    pub resource: String,
+    /// Usage in base unit seconds
+    pub usage: Decimal,
+}
+
+/// Usage in base unit seconds by resource name
+pub type UsageTotals = BTreeMap<String, Decimal>;
+
+/// Accumulates the time-integrated usage of pods for chargeback.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use chrono::{DateTime, TimeDelta};
+/// use kube_quantity::accounting::{
+///     Ledger, PodRef, Price, RequestInterval, StaticPriceTable, UsageUnit,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+/// let mut ledger = Ledger::new();
+/// ledger
+///     .record_interval(&RequestInterval {
+///         pod: PodRef {
+///             namespace: "shop".to_string(),
+///             name: "web-0".to_string(),
+///             ..Default::default()
+///         },
+///         start,
+///         end: start + TimeDelta::hours(2),
+///         requests: BTreeMap::from([
+///             ("cpu".to_string(), "2".try_into().unwrap()),
+///             ("memory".to_string(), "4Gi".try_into().unwrap()),
+///         ]),
+///     })
+///     .unwrap();
+///
+/// let totals = &ledger.by_namespace().unwrap()["shop"];
+/// assert_eq!(UsageUnit::CoreHours.convert(totals["cpu"]).to_string(), "4");
+/// assert_eq!(UsageUnit::GibibyteHours.convert(totals["memory"]).to_string(), "8");
+///
+/// let prices = StaticPriceTable {
+///     resources: BTreeMap::from([
+///         ("cpu".to_string(), Price::new(Decimal::new(3, 2), UsageUnit::CoreHours)),
+///         ("memory".to_string(), Price::new(Decimal::new(4, 3), UsageUnit::GibibyteHours)),
+///     ]),
+/// };
+/// assert_eq!(ledger.cost_by_namespace(&prices).unwrap()["shop"].to_string(), "0.152");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    /// Creates an empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded entries.
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// Integrates the samples of a resource of a pod and records the usage,
+    /// which is returned in base unit seconds.
+    pub fn record_samples(
+        &mut self,
+        pod: &PodRef,
+        resource: &str,
+        samples: &[TimedQuantity],
+        interpolation: Interpolation,
+    ) -> Result<Decimal, AccountingError> {
+        let usage = integrate(samples, interpolation)?;
+        self.record(pod, resource, usage);
+
+        Ok(usage)
+    }
+
+    /// Records the requests of a pod held for the whole interval.
+    pub fn record_interval(&mut self, interval: &RequestInterval) -> Result<(), AccountingError> {
+        if interval.end < interval.start {
+            return Err(AccountingError::InvalidInterval {
+                start: interval.start,
+                end: interval.end,
+            });
+        }
+
+        for (resource, quantity) in &interval.requests {
+            let samples = [
+                TimedQuantity {
+                    timestamp: interval.start,
+                    value: quantity.clone(),
+                },
+                TimedQuantity {
+                    timestamp: interval.end,
+                    value: quantity.clone(),
+                },
+            ];
+            let usage = integrate(&samples, Interpolation::Step)?;
+            self.record(&interval.pod, resource, usage);
+        }
+
+        Ok(())
+    }
+
+    fn record(&mut self, pod: &PodRef, resource: &str, usage: Decimal) {
+        self.entries.push(LedgerEntry {
+            pod: pod.clone(),
+            resource: resource.to_owned(),
+            usage,
+        });
+    }
+
+    /// Returns the usage totals grouped by a key of the pods.
+    pub fn group_by<K: Ord>(
+        &self,
+        key: impl Fn(&PodRef) -> K,
+    ) -> Result<BTreeMap<K, UsageTotals>, AccountingError> {
+        let mut groups: BTreeMap<K, UsageTotals> = BTreeMap::new();
+
+        for entry in &self.entries {
+            let total = groups
+                .entry(key(&entry.pod))
+                .or_default()
+                .entry(entry.resource.clone())
+                .or_default();
+            *total = total
+                .checked_add(entry.usage)
+                .ok_or_else(|| AccountingError::Overflow(entry.resource.clone()))?;
+        }
+
+        Ok(groups)
+    }
+
+    /// Returns the usage totals by namespace.
+    pub fn by_namespace(&self) -> Result<BTreeMap<String, UsageTotals>, AccountingError> {
+        self.group_by(|pod| pod.namespace.clone())
+    }
+
+    /// Returns the usage totals by the value of a label. Pods without the
+    /// label are grouped under `None`.
+    pub fn by_label(
+        &self,
+        label: &str,
+    ) -> Result<BTreeMap<Option<String>, UsageTotals>, AccountingError> {
+        self.group_by(|pod| pod.labels.get(label).cloned())
+    }
+
+    /// Returns the cost grouped by a key of the pods. Resources without a
+    /// price are free.
+    pub fn cost_by<K: Ord>(
+        &self,
+        prices: &impl PriceTable,
+        key: impl Fn(&PodRef) -> K,
+    ) -> Result<BTreeMap<K, Decimal>, AccountingError> {
+        let mut costs: BTreeMap<K, Decimal> = BTreeMap::new();
+
+        for entry in &self.entries {
+            let overflow = || AccountingError::Overflow(entry.resource.clone());
+            let cost = match prices.price(&entry.pod, &entry.resource) {
+                Some(price) => price.checked_cost(entry.usage).ok_or_else(overflow)?,
+                None => Decimal::ZERO,
+            };
+            let total = costs.entry(key(&entry.pod)).or_default();
+            *total = total.checked_add(cost).ok_or_else(overflow)?;
+        }
+
+        Ok(costs
+            .into_iter()
+            .map(|(key, cost)| (key, cost.normalize()))
+            .collect())
+    }
+
+    /// Returns the cost by namespace.
+    pub fn cost_by_namespace(
+        &self,
+        prices: &impl PriceTable,
+    ) -> Result<BTreeMap<String, Decimal>, AccountingError> {
+        self.cost_by(prices, |pod| pod.namespace.clone())
+    }
+
+    /// Returns the cost by the value of a label. Pods without the label are
+    /// grouped under `None`.
+    pub fn cost_by_label(
+        &self,
+        prices: &impl PriceTable,
+        label: &str,
+    ) -> Result<BTreeMap<Option<String>, Decimal>, AccountingError> {
+        self.cost_by(prices, |pod| pod.labels.get(label).cloned())
+    }
+}
+
+// --- Prices ---
+
+/// Price of one unit of usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Price {
+    /// Amount charged per unit
+    pub amount: Decimal,
+    /// Unit the amount is charged for
+    pub unit: UsageUnit,
+}
+
+impl Price {
+    /// Creates a price of an amount per unit.
+    pub fn new(amount: Decimal, unit: UsageUnit) -> Self {
+        Self { amount, unit }
+    }
+
+    /// Returns the cost of usage in base unit seconds. The usage is
+    /// multiplied before converting it, so that prices per hour do not
+    /// accumulate rounding errors.
+    pub fn cost(&self, usage: Decimal) -> Decimal {
+        (usage * self.amount / self.unit.base_seconds()).normalize()
+    }
+
+    /// Returns the cost of usage in base unit seconds, or `None` if it
+    /// overflows.
+    pub fn checked_cost(&self, usage: Decimal) -> Option<Decimal> {
+        usage
+            .checked_mul(self.amount)
+            .and_then(|cost| cost.checked_div(self.unit.base_seconds()))
+            .map(|cost| cost.normalize())
+    }
+}
+
+/// Source of the prices used to convert usage into cost, e.g., to charge
+/// namespaces or node pools differently.
+pub trait PriceTable {
+    /// Returns the price of a resource used by a pod, or `None` if the
+    /// resource is not charged.
+    fn price(&self, pod: &PodRef, resource: &str) -> Option<Price>;
+}
+
+/// Price table with a single price per resource. With the `serde` feature,
+/// it can be loaded from JSON or YAML.
+///
+/// ```yaml
+/// resources:
+///   cpu: { amount: "0.031611", unit: core-hours }
+///   memory: { amount: "0.004237", unit: GiB-hours }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StaticPriceTable {
+    /// Prices by resource name
+    pub resources: BTreeMap<String, Price>,
+}
+
+#[cfg(feature = "serde")]
+impl StaticPriceTable {
+    /// Parses a price table from a JSON string.
+    pub fn from_json_str(input: &str) -> Result<Self, AccountingError> {
+        Ok(serde_json::from_str(input)?)
+    }
+
+    /// Parses a price table from a YAML string.
+    pub fn from_yaml_str(input: &str) -> Result<Self, AccountingError> {
+        Ok(serde_yaml::from_str(input)?)
+    }
+}
+
+impl PriceTable for StaticPriceTable {
+    fn price(&self, _pod: &PodRef, resource: &str) -> Option<Price> {
+        self.resources.get(resource).copied()
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::core::v1::{Container, PodSpec, PodStatus, ResourceRequirements},
+        apimachinery::pkg::{api::resource::Quantity, apis::meta::v1::ObjectMeta},
+    };
+
+    use super::*;
+    use crate::{compat::from_utc, test_util::parsed_list};
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn series(points: &[(DateTime<Utc>, &str)]) -> Vec<TimedQuantity> {
+        points
+            .iter()
+            .map(|(timestamp, value)| TimedQuantity {
+                timestamp: *timestamp,
+                value: (*value).try_into().unwrap(),
+            })
+            .collect()
+    }
+
+    fn pod_ref(namespace: &str, name: &str, team: Option<&str>) -> PodRef {
+        PodRef {
+            namespace: namespace.to_owned(),
+            name: name.to_owned(),
+            labels: team
+                .map(|team| BTreeMap::from([("team".to_owned(), team.to_owned())]))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn interval(pod: PodRef, hours: i64, requests: &[(&str, &str)]) -> RequestInterval {
+        RequestInterval {
+            pod,
+            start: at(0),
+            end: at(hours * 3600),
+            requests: parsed_list(requests),
+        }
+    }
+
+    fn ledger() -> Ledger {
+        let mut ledger = Ledger::new();
+        for interval in [
+            interval(
+                pod_ref("shop", "web-0", Some("frontend")),
+                2,
+                &[("cpu", "500m"), ("memory", "1Gi")],
+            ),
+            interval(
+                pod_ref("shop", "db-0", Some("storage")),
+                1,
+                &[("cpu", "2"), ("memory", "8Gi"), ("nvidia.com/gpu", "1")],
+            ),
+            interval(pod_ref("batch", "job-x", None), 3, &[("cpu", "250m")]),
+        ] {
+            ledger.record_interval(&interval).unwrap();
+        }
+
+        ledger
+    }
+
+    fn prices() -> StaticPriceTable {
+        StaticPriceTable {
+            resources: BTreeMap::from([
+                (
+                    "cpu".to_owned(),
+                    Price::new(Decimal::new(4, 2), UsageUnit::CoreHours),
+                ),
+                (
+                    "memory".to_owned(),
+                    Price::new(Decimal::new(5, 3), UsageUnit::GibibyteHours),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_integrate_sub_second() {
+        let start = at(0);
+        let samples = series(&[
+            (start, "1Mi"),
+            (start + TimeDelta::milliseconds(1500), "3Mi"),
+            (start + TimeDelta::nanoseconds(1_500_000_001), "3Mi"),
+        ]);
+
+        assert_eq!(
+            integrate(&samples, Interpolation::Step).unwrap(),
+            Decimal::from(1_572_864) + Decimal::new(3_145_728, 9)
+        );
+        assert_eq!(
+            integrate(&samples, Interpolation::Linear).unwrap(),
+            Decimal::from(3_145_728) + Decimal::new(3_145_728, 9)
+        );
+    }
+
+    #[test]
+    fn test_integrate_is_exact() {
+        let samples: Vec<TimedQuantity> = (0..=100_000)
+            .map(|second| TimedQuantity {
+                timestamp: at(second),
+                value: "1n".try_into().unwrap(),
+            })
+            .collect();
+
+        assert_eq!(
+            integrate(&samples, Interpolation::Linear).unwrap(),
+            Decimal::new(1, 4)
+        );
+        assert_eq!(
+            integrate(&samples[..1], Interpolation::Step).unwrap(),
+            Decimal::ZERO
+        );
+        assert_eq!(integrate(&[], Interpolation::Step).unwrap(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_integrate_errors() {
+        let error =
+            integrate(&series(&[(at(60), "1"), (at(0), "1")]), Interpolation::Step).unwrap_err();
+        assert!(matches!(error, AccountingError::UnorderedSamples { .. }));
+
+        let mut ledger = Ledger::new();
+        let mut backwards = interval(pod_ref("shop", "web-0", None), 1, &[("cpu", "1")]);
+        std::mem::swap(&mut backwards.start, &mut backwards.end);
+        assert!(matches!(
+            ledger.record_interval(&backwards).unwrap_err(),
+            AccountingError::InvalidInterval { .. }
+        ));
+        assert!(ledger.entries().is_empty());
+    }
+
+    #[test]
+    fn test_record_samples() {
+        let mut ledger = Ledger::new();
+        let pod = pod_ref("shop", "web-0", None);
+
+        let usage = ledger
+            .record_samples(
+                &pod,
+                "cpu",
+                &series(&[(at(0), "100m"), (at(1800), "300m"), (at(3600), "300m")]),
+                Interpolation::Linear,
+            )
+            .unwrap();
+
+        assert_eq!(UsageUnit::CoreHours.convert(usage).to_string(), "0.25");
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(ledger.entries()[0].resource, "cpu");
+    }
+
+    #[test]
+    fn test_group_by() {
+        let ledger = ledger();
+
+        let namespaces = ledger.by_namespace().unwrap();
+        assert_eq!(namespaces["shop"]["cpu"], Decimal::from(3600 * 3));
+        assert_eq!(
+            namespaces["shop"]["memory"],
+            Decimal::from((3600_i64 * 10) << 30)
+        );
+        assert_eq!(namespaces["shop"]["nvidia.com/gpu"], Decimal::from(3600));
+        assert_eq!(namespaces["batch"]["cpu"], Decimal::from(2700));
+
+        let teams = ledger.by_label("team").unwrap();
+        assert_eq!(
+            teams.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                None,
+                Some("frontend".to_owned()),
+                Some("storage".to_owned())
+            ]
+        );
+        assert_eq!(
+            UsageUnit::GibibyteHours.convert(teams[&Some("storage".to_owned())]["memory"]),
+            Decimal::from(8)
+        );
+
+        let pods = ledger.group_by(|pod| pod.name.clone()).unwrap();
+        assert_eq!(
+            UsageUnit::CoreHours.convert(pods["web-0"]["cpu"]),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn test_cost() {
+        let ledger = ledger();
+
+        // shop: 3 core-hours, 10 GiB-hours and an unpriced GPU
+        assert_eq!(
+            ledger.cost_by_namespace(&prices()).unwrap(),
+            BTreeMap::from([
+                ("batch".to_owned(), Decimal::new(3, 2)),
+                ("shop".to_owned(), Decimal::new(17, 2)),
+            ])
+        );
+        assert_eq!(
+            ledger.cost_by_label(&prices(), "team").unwrap(),
+            BTreeMap::from([
+                (None, Decimal::new(3, 2)),
+                (Some("frontend".to_owned()), Decimal::new(5, 2)),
+                (Some("storage".to_owned()), Decimal::new(12, 2)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_custom_price_table() {
+        struct SpotDiscount(StaticPriceTable);
+
+        impl PriceTable for SpotDiscount {
+            fn price(&self, pod: &PodRef, resource: &str) -> Option<Price> {
+                let price = self.0.price(pod, resource)?;
+                match pod.namespace.as_str() {
+                    "batch" => Some(Price::new(price.amount / Decimal::TWO, price.unit)),
+                    _ => Some(price),
+                }
+            }
+        }
+
+        let costs = ledger().cost_by_namespace(&SpotDiscount(prices())).unwrap();
+        assert_eq!(costs["batch"], Decimal::new(15, 3));
+        assert_eq!(costs["shop"], Decimal::new(17, 2));
+    }
+
+    #[test]
+    fn test_totals_overflow() {
+        let mut ledger = Ledger::new();
+        let pod = pod_ref("shop", "web-0", None);
+        ledger.record(&pod, "cpu", Decimal::MAX);
+        ledger.record(&pod, "cpu", Decimal::MAX);
+
+        assert!(matches!(
+            ledger.by_namespace().unwrap_err(),
+            AccountingError::Overflow(resource) if resource == "cpu"
+        ));
+
+        let prices = StaticPriceTable {
+            resources: BTreeMap::from([(
+                "cpu".to_owned(),
+                Price::new(Decimal::MAX, UsageUnit::CoreHours),
+            )]),
+        };
+        assert_eq!(prices.resources["cpu"].checked_cost(Decimal::MAX), None);
+        assert!(matches!(
+            ledger.cost_by_namespace(&prices).unwrap_err(),
+            AccountingError::Overflow(_)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_price_table_json() {
+        let table = StaticPriceTable::from_json_str(
+            r#"{"resources": {"ephemeral-storage": {"amount": "0.0001", "unit": "GB-hours"}}}"#,
+        )
+        .unwrap();
+        let price = table.resources["ephemeral-storage"];
+
+        // 10G for 30 minutes
+        assert_eq!(
+            price.cost(Decimal::from(10_000_000_000_i64 * 1800)),
+            Decimal::new(5, 4)
+        );
+        assert!(StaticPriceTable::from_json_str(
+            r#"{"resources": {"cpu": {"amount": "1", "unit": "cores"}}}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_price_table_yaml() {
+        let table = StaticPriceTable::from_yaml_str(
+            r#"
+resources:
+  cpu: { amount: "0.04", unit: core-hours }
+  memory: { amount: "0.005", unit: GiB-hours }
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(table, prices());
+        assert!(matches!(
+            StaticPriceTable::from_yaml_str("resources: [cpu]"),
+            Err(AccountingError::Yaml(_))
+        ));
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(UsageUnit::for_resource("cpu"), UsageUnit::CoreHours);
+        assert_eq!(UsageUnit::for_resource("memory"), UsageUnit::GibibyteHours);
+        assert_eq!(
+            UsageUnit::for_resource("hugepages-2Mi"),
+            UsageUnit::GibibyteHours
+        );
+        assert_eq!(UsageUnit::for_resource("pods"), UsageUnit::UnitHours);
+
+        let usage = Decimal::from(3600_i64 << 30);
+        assert_eq!(UsageUnit::ByteSeconds.convert(usage), usage);
+        assert_eq!(UsageUnit::GibibyteHours.convert(usage), Decimal::ONE);
+        assert_eq!(
+            UsageUnit::GigabyteHours.convert(usage),
+            Decimal::new(1_073_741_824, 9)
+        );
+        assert_eq!(UsageUnit::GibibyteHours.as_str(), "GiB-hours");
+    }
+
+    #[test]
+    fn test_from_pod() {
+        let pod = Pod {
+            metadata: ObjectMeta {
+                namespace: Some("shop".to_owned()),
+                name: Some("web-0".to_owned()),
+                creation_timestamp: Some(from_utc(at(-60))),
+                labels: Some(BTreeMap::from([("team".to_owned(), "frontend".to_owned())])),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_owned(),
+                    resources: Some(ResourceRequirements {
+                        requests: Some(BTreeMap::from([(
+                            "cpu".to_owned(),
+                            Quantity("1500m".to_owned()),
+                        )])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            status: Some(PodStatus {
+                start_time: Some(from_utc(at(0))),
+                ..Default::default()
+            }),
+        };
+
+        let interval = RequestInterval::from_pod(&pod, at(7200)).unwrap();
+        assert_eq!(interval.pod, pod_ref("shop", "web-0", Some("frontend")));
+        assert_eq!(interval.start, at(0));
+
+        let mut ledger = Ledger::new();
+        ledger.record_interval(&interval).unwrap();
+        assert_eq!(
+            UsageUnit::CoreHours.convert(ledger.by_namespace().unwrap()["shop"]["cpu"]),
+            Decimal::from(3)
+        );
+
+        let pending = Pod {
+            status: None,
+            ..pod.clone()
+        };
+        assert_eq!(
+            RequestInterval::from_pod(&pending, at(7200)).unwrap().start,
+            at(-60)
+        );
+
+        let unknown = Pod {
+            metadata: ObjectMeta::default(),
+            status: None,
+            ..pod
+        };
+        assert!(matches!(
+            RequestInterval::from_pod(&unknown, at(7200)).unwrap_err(),
+            AccountingError::NotStarted(_)
+        ));
+    }
+}
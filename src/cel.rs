@@ -0,0 +1,361 @@
+use std::cmp::Ordering;
+
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use thiserror::Error;
+
+use crate::{format::Format, ParseQuantityError, ParsedQuantity};
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum CelError {
+    /// The string does not match the quantity grammar
+    #[error("quantities must match the regular expression '^([+-]?[0-9.]+)([eEinumkKMGTP]*[-+]?[0-9]*)$'")]
+    FormatWrong(String),
+
+    /// The string could not be parsed as a quantity
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+
+    /// The quantity is not an integer or does not fit into an `int`
+    #[error("cannot convert value to integer")]
+    NotInteger,
+
+    /// The result of an arithmetic operation cannot be represented
+    #[error("quantity arithmetic overflow")]
+    Overflow,
+}
+
+// --- CEL quantity library ---
+//
+// The functions mirror the Kubernetes CEL quantity library, which is
+// available in ValidatingAdmissionPolicy and CRD validation rules. Each
+// function maps to one overload and can be registered with a CEL interpreter
+// as is, e.g., `quantity(string)` to [`quantity`] and `<Quantity>.add(int)` to
+// [`add_int`].
+
+/// `quantity(string) -> Quantity` parses a quantity. The digits are kept as
+/// written, e.g., `1.0` is not shortened to `1`, as [`as_integer`] depends on
+/// them.
+///
+/// ```rust
+/// use kube_quantity::cel;
+///
+/// assert_eq!(cel::quantity("1.5Gi").unwrap().to_string(), "1.5Gi");
+/// assert!(cel::quantity("Gi").is_err());
+/// ```
+pub fn quantity(value: &str) -> Result<ParsedQuantity, CelError> {
+    // Unlike the parser of this crate, upstream requires at least one digit
+    let number = value.trim_start_matches(['+', '-']);
+    if !number.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        || !number.contains(|c: char| c.is_ascii_digit())
+    {
+        return Err(CelError::FormatWrong(value.to_owned()));
+    }
+
+    let parsed: ParsedQuantity = value.try_into()?;
+    Ok(ParsedQuantity {
+        value: literal_value(value).unwrap_or(parsed.value),
+        ..parsed
+    })
+}
+
+/// `isQuantity(string) -> bool` returns whether the string is a valid
+/// quantity.
+pub fn is_quantity(value: &str) -> bool {
+    quantity(value).is_ok()
+}
+
+/// `<Quantity>.sign() -> int` returns `-1`, `0` or `1`.
+pub fn sign(quantity: &ParsedQuantity) -> i64 {
+    match quantity.to_base_decimal().cmp(&Decimal::ZERO) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// `<Quantity>.isInteger() -> bool` returns whether [`as_integer`]
+/// succeeds.
+pub fn is_integer(quantity: &ParsedQuantity) -> bool {
+    as_integer(quantity).is_ok()
+}
+
+/// `<Quantity>.asInteger() -> int` returns the value as an integer like
+/// `Quantity.AsInt64()`.
+///
+/// Upstream only converts quantities stored as a 64-bit integer with a
+/// non-negative decimal exponent, which depends on how they are written:
+///
+/// - Decimal quantities need at most 18 digits, and the suffix must cover
+///   the digits after the decimal point, e.g., `1.5k` converts, while `1.0`
+///   and `1000m` do not.
+/// - Binary quantities must be whole numbers whose digits and suffix leave
+///   some precision, e.g., `1Gi` converts, while `0.5Ki` and `1Pi` do not.
+///
+/// Quantities written in exponent notation are judged by their digits in
+/// plain notation, e.g., `1e3` like `1000`.
+///
+/// ```rust
+/// use kube_quantity::cel;
+///
+/// assert_eq!(cel::as_integer(&cel::quantity("50k").unwrap()).unwrap(), 50000);
+/// assert!(cel::as_integer(&cel::quantity("50m").unwrap()).is_err());
+/// assert!(cel::as_integer(&cel::quantity("1000m").unwrap()).is_err());
+/// assert!(cel::as_integer(&cel::quantity("9999999999999999999").unwrap()).is_err());
+/// ```
+pub fn as_integer(quantity: &ParsedQuantity) -> Result<i64, CelError> {
+    let value = quantity.value;
+    let mantissa = value.mantissa().to_i64().ok_or(CelError::NotInteger)?;
+    let digits = mantissa.unsigned_abs().checked_ilog10().unwrap_or_default() as i32 + 1;
+    let exponent: i32 = (&quantity.scale).into();
+
+    let multiplier = match quantity.format() {
+        Format::BinarySI => {
+            let bits = 10 * exponent;
+            if value.scale() > 0 || bits < 0 || 15 - digits - bits * 3 / 10 - 1 < 0 {
+                return Err(CelError::NotInteger);
+            }
+            1i64.checked_shl(bits as u32)
+        }
+        Format::DecimalSI => {
+            let scale = 3 * exponent - value.scale() as i32;
+            if digits > 18 || scale < 0 {
+                return Err(CelError::NotInteger);
+            }
+            10i64.checked_pow(scale as u32)
+        }
+    };
+
+    multiplier
+        .and_then(|multiplier| mantissa.checked_mul(multiplier))
+        .ok_or(CelError::NotInteger)
+}
+
+/// `<Quantity>.asApproximateFloat() -> double` returns the closest float to
+/// the value, which may lose precision.
+pub fn as_approximate_float(quantity: &ParsedQuantity) -> f64 {
+    quantity.to_base_decimal().to_f64().unwrap_or_default()
+}
+
+/// `<Quantity>.add(Quantity) -> Quantity` returns the exact sum. The result
+/// keeps the suffix format of the receiver, unless the receiver is zero.
+///
+/// ```rust
+/// use kube_quantity::cel;
+///
+/// let sum = cel::add(&cel::quantity("50k").unwrap(), &cel::quantity("100k").unwrap()).unwrap();
+/// assert_eq!(cel::compare_to(&sum, &cel::quantity("150k").unwrap()), 0);
+/// ```
+pub fn add(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> Result<ParsedQuantity, CelError> {
+    let value = checked_value(lhs)?
+        .checked_add(checked_value(rhs)?)
+        .ok_or(CelError::Overflow)?;
+
+    Ok(ParsedQuantity::from_base_decimal(
+        value,
+        result_format(lhs, rhs),
+    ))
+}
+
+/// `<Quantity>.add(int) -> Quantity` returns the exact sum.
+pub fn add_int(lhs: &ParsedQuantity, rhs: i64) -> Result<ParsedQuantity, CelError> {
+    add(lhs, &int_quantity(rhs))
+}
+
+/// `<Quantity>.sub(Quantity) -> Quantity` returns the exact difference. The
+/// result keeps the suffix format of the receiver, unless the receiver is
+/// zero.
+pub fn sub(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> Result<ParsedQuantity, CelError> {
+    let value = checked_value(lhs)?
+        .checked_sub(checked_value(rhs)?)
+        .ok_or(CelError::Overflow)?;
+
+    Ok(ParsedQuantity::from_base_decimal(
+        value,
+        result_format(lhs, rhs),
+    ))
+}
+
+/// `<Quantity>.sub(int) -> Quantity` returns the exact difference.
+pub fn sub_int(lhs: &ParsedQuantity, rhs: i64) -> Result<ParsedQuantity, CelError> {
+    sub(lhs, &int_quantity(rhs))
+}
+
+/// `<Quantity>.isLessThan(Quantity) -> bool`
+pub fn is_less_than(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> bool {
+    compare_to(lhs, rhs) < 0
+}
+
+/// `<Quantity>.isGreaterThan(Quantity) -> bool`
+pub fn is_greater_than(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> bool {
+    compare_to(lhs, rhs) > 0
+}
+
+/// `<Quantity>.compareTo(Quantity) -> int` returns `-1`, `0` or `1` by
+/// comparing the exact values.
+pub fn compare_to(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> i64 {
+    match lhs.to_base_decimal().cmp(&rhs.to_base_decimal()) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// Returns the exact value in base units, failing instead of saturating.
+fn checked_value(quantity: &ParsedQuantity) -> Result<Decimal, CelError> {
+    quantity.checked_base_decimal().ok_or(CelError::Overflow)
+}
+
+/// Mirrors `Quantity.Add`, which takes the format of the argument if the
+/// receiver is zero.
+fn result_format(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> Format {
+    if lhs.value.is_zero() {
+        rhs.format().clone()
+    } else {
+        lhs.format().clone()
+    }
+}
+
+/// Returns the number of a quantity string with the digits as written.
+fn literal_value(value: &str) -> Option<Decimal> {
+    let number_end = value
+        .find(|c: char| !matches!(c, '+' | '-' | '.' | '0'..='9'))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(number_end);
+    let number = number.strip_prefix('+').unwrap_or(number);
+
+    // `e` and `E` followed by digits are an exponent rather than a suffix
+    let exponent = suffix
+        .strip_prefix(['e', 'E'])
+        .map(|exponent| exponent.trim_start_matches(['+', '-']))
+        .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_digit()));
+
+    if exponent {
+        Decimal::from_scientific(&format!("{number}{suffix}")).ok()
+    } else {
+        number.parse().ok()
+    }
+}
+
+fn int_quantity(value: i64) -> ParsedQuantity {
+    ParsedQuantity::from_base_decimal(Decimal::from(value), Format::DecimalSI)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(value: &str) -> ParsedQuantity {
+        quantity(value).unwrap()
+    }
+
+    #[test]
+    fn test_quantity_and_is_quantity() {
+        for valid in ["0", "1", "-1.5", "+200m", "1.5Gi", ".5", "1e3", "20Ki"] {
+            assert!(is_quantity(valid), "{valid}");
+        }
+        for invalid in ["", "Gi", "m", "+", "-Ki", "1.2.3", "10 Gi", "1Gb", "abc"] {
+            assert!(!is_quantity(invalid), "{invalid}");
+        }
+
+        assert!(matches!(quantity("Gi"), Err(CelError::FormatWrong(_))));
+    }
+
+    #[test]
+    fn test_integer_conversion() {
+        assert_eq!(as_integer(&q("50k")).unwrap(), 50_000);
+        assert_eq!(as_integer(&q("1Ki")).unwrap(), 1024);
+        assert_eq!(as_integer(&q("1.5k")).unwrap(), 1500);
+        assert_eq!(as_integer(&q("1e3")).unwrap(), 1000);
+        assert_eq!(as_integer(&q("-8")).unwrap(), -8);
+        assert_eq!(as_integer(&q("1Ti")).unwrap(), 1 << 40);
+        assert!(is_integer(&q("1Gi")));
+        assert!(is_integer(&q("0")));
+        assert!(!is_integer(&q("50m")));
+        assert!(!is_integer(&q("1.5")));
+
+        // Whole values are not integers if their digits or suffix rule out the
+        // 64-bit integer representation upstream
+        for fractional in ["1000m", "1.0", "0.5Ki", "1.0Ki", "1000e-3", "1Pi"] {
+            assert!(!is_integer(&q(fractional)), "{fractional}");
+        }
+
+        assert_eq!(
+            as_integer(&q("-999999999999999999")).unwrap(),
+            -999_999_999_999_999_999
+        );
+        assert!(matches!(
+            as_integer(&q("-9223372036854775808")),
+            Err(CelError::NotInteger)
+        ));
+        assert!(matches!(
+            as_integer(&q("9223372036854775808")),
+            Err(CelError::NotInteger)
+        ));
+        assert!(matches!(
+            as_integer(&q("10000P")),
+            Err(CelError::NotInteger)
+        ));
+        assert!(matches!(
+            as_integer(&q("10000Pi")),
+            Err(CelError::NotInteger)
+        ));
+        assert_eq!(
+            as_integer(&q("8192Pi")).unwrap_err().to_string(),
+            "cannot convert value to integer"
+        );
+
+        assert_eq!(as_approximate_float(&q("1.5Gi")), 1_610_612_736.0);
+        assert_eq!(as_approximate_float(&q("250m")), 0.25);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(
+            compare_to(&add(&q("50k"), &q("100k")).unwrap(), &q("150k")),
+            0
+        );
+        assert_eq!(compare_to(&add_int(&q("50k"), 20).unwrap(), &q("50020")), 0);
+        assert_eq!(
+            compare_to(&sub(&q("50k"), &q("100k")).unwrap(), &q("-50k")),
+            0
+        );
+        assert_eq!(compare_to(&sub_int(&q("1"), 2).unwrap(), &q("-1")), 0);
+        assert_eq!(
+            compare_to(&add(&q("100m"), &q("0.2")).unwrap(), &q("300m")),
+            0
+        );
+
+        // The zero receiver takes the format of the argument
+        assert_eq!(
+            add(&q("0"), &q("1Ki")).unwrap().to_canonical_string(),
+            "1Ki"
+        );
+        assert_eq!(
+            add(&q("1Ki"), &q("1k")).unwrap().to_canonical_string(),
+            "2024"
+        );
+
+        assert!(matches!(
+            add(&q("1000000000000000P"), &q("1")),
+            Err(CelError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_comparison_and_sign() {
+        assert!(is_less_than(&q("50M"), &q("50Mi")));
+        assert!(is_greater_than(&q("1"), &q("999m")));
+        assert!(!is_less_than(&q("1Gi"), &q("1024Mi")));
+        assert_eq!(compare_to(&q("1Gi"), &q("1024Mi")), 0);
+        assert_eq!(compare_to(&q("1"), &q("2")), -1);
+        assert_eq!(compare_to(&q("2"), &q("1")), 1);
+
+        assert_eq!(sign(&q("-1m")), -1);
+        assert_eq!(sign(&q("0Gi")), 0);
+        assert_eq!(sign(&q("5Ki")), 1);
+    }
+}
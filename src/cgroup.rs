@@ -0,0 +1,601 @@
+use std::{fmt::Display, str::FromStr};
+
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::{format::Format, Limit, ParsedQuantity};
+
+/// Minimum number of CPU shares the kubelet assigns
+pub const MIN_SHARES: u64 = 2;
+/// Maximum number of CPU shares the kubelet assigns
+pub const MAX_SHARES: u64 = 262_144;
+/// Number of CPU shares of a single core
+pub const SHARES_PER_CPU: u64 = 1024;
+/// Default CFS period in microseconds
+pub const QUOTA_PERIOD: u64 = 100_000;
+/// Minimum CFS quota in microseconds
+pub const MIN_QUOTA_PERIOD: u64 = 1000;
+/// Minimum cgroup v2 `cpu.weight`
+pub const MIN_WEIGHT: u64 = 1;
+/// Maximum cgroup v2 `cpu.weight`
+pub const MAX_WEIGHT: u64 = 10_000;
+/// Sentinel used by cgroup v2 files for the absence of a limit
+pub const MAX: &str = "max";
+
+/// Value cgroup v1 reports for `memory.limit_in_bytes` without a limit, i.e.,
+/// `i64::MAX` rounded down to 4KiB pages
+pub const MEMORY_UNLIMITED_IN_BYTES: u64 = 9_223_372_036_854_771_712;
+
+const MILLI_CPU_TO_CPU: u64 = 1000;
+
+// --- Errors ---
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CgroupFileError {
+    /// The content does not match the format of the cgroup file
+    #[error("invalid cgroup file content: {0:?}")]
+    InvalidFormat(String),
+
+    /// A CFS period of zero was given
+    #[error("CFS period must be greater than zero")]
+    ZeroPeriod,
+}
+
+// --- Requests ---
+
+/// Returns the CPU shares the kubelet assigns for a CPU request, i.e.,
+/// `MilliCPUToShares`. Requests are rounded up to millicores and the result
+/// is clamped between [`MIN_SHARES`] and [`MAX_SHARES`].
+///
+/// ```rust
+/// use kube_quantity::{cgroup::cpu_shares, ParsedQuantity};
+///
+/// let request: ParsedQuantity = "250m".try_into().unwrap();
+/// assert_eq!(cpu_shares(&request), 256);
+///
+/// let request: ParsedQuantity = "1m".try_into().unwrap();
+/// assert_eq!(cpu_shares(&request), 2);
+/// ```
+pub fn cpu_shares(request: &ParsedQuantity) -> u64 {
+    milli_cpu_to_shares(milli_cpu(request))
+}
+
+/// Returns the CPU shares for a number of millicores, i.e., `MilliCPUToShares`.
+pub fn milli_cpu_to_shares(milli_cpu: u64) -> u64 {
+    (milli_cpu.saturating_mul(SHARES_PER_CPU) / MILLI_CPU_TO_CPU).clamp(MIN_SHARES, MAX_SHARES)
+}
+
+/// Returns the CPU request the given number of shares was derived from,
+/// rounded up to millicores. Shares below [`MIN_SHARES`] map to zero.
+///
+/// ```rust
+/// use kube_quantity::cgroup::cpu_request_from_shares;
+///
+/// assert_eq!(cpu_request_from_shares(256).to_string(), "250m");
+/// assert_eq!(cpu_request_from_shares(2).to_string(), "2m");
+/// ```
+pub fn cpu_request_from_shares(shares: u64) -> ParsedQuantity {
+    ParsedQuantity::from_milli_decimal(Decimal::from(shares_to_milli_cpu(shares)))
+}
+
+/// Returns the millicores for a number of CPU shares, rounded up.
+pub fn shares_to_milli_cpu(shares: u64) -> u64 {
+    if shares < MIN_SHARES {
+        return 0;
+    }
+
+    shares
+        .saturating_mul(MILLI_CPU_TO_CPU)
+        .div_ceil(SHARES_PER_CPU)
+}
+
+// --- Weights ---
+
+/// Converts cgroup v1 CPU shares to a cgroup v2 `cpu.weight`, mapping
+/// `[2, 262144]` linearly onto `[1, 10000]`. Zero shares map to a zero
+/// weight, which means unset.
+///
+/// ```rust
+/// use kube_quantity::cgroup::shares_to_weight;
+///
+/// assert_eq!(shares_to_weight(2), 1);
+/// assert_eq!(shares_to_weight(1024), 39);
+/// assert_eq!(shares_to_weight(262_144), 10_000);
+/// ```
+pub fn shares_to_weight(shares: u64) -> u64 {
+    if shares == 0 {
+        return 0;
+    }
+
+    let shares = shares.clamp(MIN_SHARES, MAX_SHARES);
+    MIN_WEIGHT + ((shares - MIN_SHARES) * (MAX_WEIGHT - MIN_WEIGHT)) / (MAX_SHARES - MIN_SHARES)
+}
+
+/// Converts a cgroup v2 `cpu.weight` back to the smallest number of CPU
+/// shares mapping to it, so that converting the result again yields the
+/// same weight. A zero weight maps to zero shares.
+///
+/// ```rust
+/// use kube_quantity::cgroup::{shares_to_weight, weight_to_shares};
+///
+/// assert_eq!(weight_to_shares(39), 999);
+/// assert_eq!(shares_to_weight(weight_to_shares(39)), 39);
+/// ```
+pub fn weight_to_shares(weight: u64) -> u64 {
+    if weight == 0 {
+        return 0;
+    }
+
+    let weight = weight.clamp(MIN_WEIGHT, MAX_WEIGHT);
+    MIN_SHARES
+        + ((weight - MIN_WEIGHT) * (MAX_SHARES - MIN_SHARES)).div_ceil(MAX_WEIGHT - MIN_WEIGHT)
+}
+
+// --- Limits ---
+
+/// Returns the CFS quota in microseconds for a CPU limit and a CFS period,
+/// i.e., `MilliCPUToQuota`. Quotas are at least [`MIN_QUOTA_PERIOD`], and a
+/// missing or zero limit yields `None`, which is written as [`MAX`] in
+/// `cpu.max` or `-1` in `cpu.cfs_quota_us`.
+///
+/// ```rust
+/// use kube_quantity::{cgroup::{cpu_quota, QUOTA_PERIOD}, ParsedQuantity};
+///
+/// let limit: ParsedQuantity = "1500m".try_into().unwrap();
+/// assert_eq!(cpu_quota(Some(&limit), QUOTA_PERIOD), Some(150_000));
+///
+/// let limit: ParsedQuantity = "5m".try_into().unwrap();
+/// assert_eq!(cpu_quota(Some(&limit), QUOTA_PERIOD), Some(1000));
+///
+/// assert_eq!(cpu_quota(None, QUOTA_PERIOD), None);
+/// ```
+pub fn cpu_quota(limit: Option<&ParsedQuantity>, period: u64) -> Option<u64> {
+    milli_cpu_to_quota(limit.map(milli_cpu).unwrap_or_default(), period)
+}
+
+/// Returns the CFS quota for a number of millicores, i.e., `MilliCPUToQuota`.
+pub fn milli_cpu_to_quota(milli_cpu: u64, period: u64) -> Option<u64> {
+    if milli_cpu == 0 {
+        return None;
+    }
+
+    Some((milli_cpu.saturating_mul(period) / MILLI_CPU_TO_CPU).max(MIN_QUOTA_PERIOD))
+}
+
+/// Returns the CPU limit a CFS quota and period correspond to, rounded down
+/// to millicores, with `None` standing for [`MAX`].
+///
+/// ```rust
+/// use kube_quantity::cgroup::{cpu_limit_from_quota, QUOTA_PERIOD};
+///
+/// let limit = cpu_limit_from_quota(Some(150_000), QUOTA_PERIOD).unwrap();
+/// assert_eq!(limit.to_string(), "1500m");
+///
+/// assert_eq!(cpu_limit_from_quota(None, QUOTA_PERIOD), None);
+/// ```
+pub fn cpu_limit_from_quota(quota: Option<u64>, period: u64) -> Option<ParsedQuantity> {
+    quota
+        .map(|quota| quota_to_milli_cpu(quota, period))
+        .map(|milli_cpu| ParsedQuantity::from_milli_decimal(Decimal::from(milli_cpu)))
+}
+
+/// Returns the millicores for a CFS quota and period, rounded down.
+pub fn quota_to_milli_cpu(quota: u64, period: u64) -> u64 {
+    if period == 0 {
+        return 0;
+    }
+
+    quota.saturating_mul(MILLI_CPU_TO_CPU) / period
+}
+
+/// Returns the millicores of a CPU quantity, with negative values being zero.
+fn milli_cpu(quantity: &ParsedQuantity) -> u64 {
+    quantity.to_milli_i64().max(0).unsigned_abs()
+}
+
+// --- Files ---
+
+/// Content of the cgroup v2 `cpu.max` file, e.g., `max 100000` or
+/// `50000 100000`.
+///
+/// ```rust
+/// use kube_quantity::cgroup::{CpuMax, QUOTA_PERIOD};
+/// use kube_quantity::{Limit, ParsedQuantity};
+///
+/// let cpu_max: CpuMax = "50000 100000\n".parse().unwrap();
+/// assert_eq!(cpu_max.limit().to_string(), "500m");
+///
+/// let limit: ParsedQuantity = "1500m".try_into().unwrap();
+/// let cpu_max = CpuMax::from_limit(&Limit::Limited(limit), QUOTA_PERIOD);
+/// assert_eq!(cpu_max.to_string(), "150000 100000");
+///
+/// let cpu_max = CpuMax::from_limit(&Limit::Unlimited, QUOTA_PERIOD);
+/// assert_eq!(cpu_max.to_string(), "max 100000");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuMax {
+    /// CFS quota in microseconds, with `None` standing for `max`
+    pub quota: Option<u64>,
+    /// CFS period in microseconds
+    pub period: u64,
+}
+
+impl CpuMax {
+    /// Returns the content the kubelet writes for a CPU limit and period.
+    pub fn from_limit(limit: &Limit, period: u64) -> Self {
+        Self {
+            quota: cpu_quota(limit.as_limited(), period),
+            period,
+        }
+    }
+
+    /// Returns the CPU limit, rounded down to millicores like the kubelet's
+    /// `QuotaToMilliCPU`.
+    pub fn limit(&self) -> Limit {
+        cpu_limit(self.quota, self.period)
+    }
+}
+
+impl Default for CpuMax {
+    fn default() -> Self {
+        Self {
+            quota: None,
+            period: QUOTA_PERIOD,
+        }
+    }
+}
+
+impl FromStr for CpuMax {
+    type Err = CgroupFileError;
+
+    /// Parses the content of `cpu.max`, where the period is optional.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let quota = fields.next().ok_or_else(|| invalid_format(s))?;
+        let period = fields
+            .next()
+            .map_or(Ok(QUOTA_PERIOD), |period| parse_number(period, s))?;
+
+        if fields.next().is_some() {
+            return Err(invalid_format(s));
+        }
+        if period == 0 {
+            return Err(CgroupFileError::ZeroPeriod);
+        }
+
+        let quota = match quota {
+            MAX => None,
+            quota => Some(parse_number(quota, s)?),
+        };
+
+        Ok(Self { quota, period })
+    }
+}
+
+impl Display for CpuMax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.quota {
+            Some(quota) => write!(f, "{quota} {}", self.period),
+            None => write!(f, "{MAX} {}", self.period),
+        }
+    }
+}
+
+/// Parses the content of the cgroup v1 `cpu.cfs_quota_us` file into a CPU
+/// limit, given the content of `cpu.cfs_period_us`. A quota of `-1` is
+/// unlimited, and limits are rounded down to millicores like
+/// [`cpu_limit_from_quota`].
+///
+/// ```rust
+/// use kube_quantity::cgroup::parse_cfs_quota_us;
+///
+/// assert_eq!(parse_cfs_quota_us("25000\n", "100000\n").unwrap().to_string(), "250m");
+/// assert!(parse_cfs_quota_us("-1", "100000").unwrap().is_unlimited());
+/// ```
+pub fn parse_cfs_quota_us(quota: &str, period: &str) -> Result<Limit, CgroupFileError> {
+    let period = parse_number(period.trim(), period)?;
+    if period == 0 {
+        return Err(CgroupFileError::ZeroPeriod);
+    }
+
+    let quota = match quota.trim() {
+        "-1" => None,
+        value => Some(parse_number(value, quota)?),
+    };
+
+    Ok(cpu_limit(quota, period))
+}
+
+/// Returns the content the kubelet writes to the cgroup v1 `cpu.cfs_quota_us`
+/// file for a CPU limit and period.
+///
+/// ```rust
+/// use kube_quantity::cgroup::{format_cfs_quota_us, QUOTA_PERIOD};
+/// use kube_quantity::{Limit, ParsedQuantity};
+///
+/// let limit: ParsedQuantity = "250m".try_into().unwrap();
+/// assert_eq!(format_cfs_quota_us(&Limit::Limited(limit), QUOTA_PERIOD), "25000");
+/// assert_eq!(format_cfs_quota_us(&Limit::Unlimited, QUOTA_PERIOD), "-1");
+/// ```
+pub fn format_cfs_quota_us(limit: &Limit, period: u64) -> String {
+    match cpu_quota(limit.as_limited(), period) {
+        Some(quota) => quota.to_string(),
+        None => "-1".to_owned(),
+    }
+}
+
+/// Parses the content of the cgroup v2 `memory.max` or `memory.high` file,
+/// which is either `max` or a number of bytes.
+///
+/// ```rust
+/// use kube_quantity::cgroup::parse_memory_max;
+///
+/// assert_eq!(parse_memory_max("536870912\n").unwrap().to_string(), "512Mi");
+/// assert!(parse_memory_max("max").unwrap().is_unlimited());
+/// ```
+pub fn parse_memory_max(content: &str) -> Result<Limit, CgroupFileError> {
+    match content.trim() {
+        MAX => Ok(Limit::Unlimited),
+        value => parse_number(value, content).map(memory_limit),
+    }
+}
+
+/// Returns the content of the cgroup v2 `memory.max` or `memory.high` file
+/// for a memory limit, rounding fractional bytes up.
+///
+/// ```rust
+/// use kube_quantity::cgroup::format_memory_max;
+/// use kube_quantity::{Limit, ParsedQuantity};
+///
+/// let limit: ParsedQuantity = "512Mi".try_into().unwrap();
+/// assert_eq!(format_memory_max(&Limit::Limited(limit)), "536870912");
+/// assert_eq!(format_memory_max(&Limit::Unlimited), "max");
+/// ```
+pub fn format_memory_max(limit: &Limit) -> String {
+    match limit {
+        Limit::Unlimited => MAX.to_owned(),
+        Limit::Limited(limit) => memory_bytes(limit).to_string(),
+    }
+}
+
+/// Parses the content of the cgroup v1 `memory.limit_in_bytes` file. Both `-1`
+/// and values of at least [`MEMORY_UNLIMITED_IN_BYTES`] are unlimited.
+///
+/// ```rust
+/// use kube_quantity::cgroup::parse_memory_limit_in_bytes;
+///
+/// assert_eq!(parse_memory_limit_in_bytes("1073741824").unwrap().to_string(), "1Gi");
+/// assert!(parse_memory_limit_in_bytes("9223372036854771712\n").unwrap().is_unlimited());
+/// ```
+pub fn parse_memory_limit_in_bytes(content: &str) -> Result<Limit, CgroupFileError> {
+    match content.trim() {
+        "-1" => Ok(Limit::Unlimited),
+        value => parse_number(value, content).map(|bytes| {
+            if bytes >= MEMORY_UNLIMITED_IN_BYTES {
+                Limit::Unlimited
+            } else {
+                memory_limit(bytes)
+            }
+        }),
+    }
+}
+
+/// Returns the content written to the cgroup v1 `memory.limit_in_bytes` file
+/// for a memory limit, using `-1` to remove the limit.
+pub fn format_memory_limit_in_bytes(limit: &Limit) -> String {
+    match limit {
+        Limit::Unlimited => "-1".to_owned(),
+        Limit::Limited(limit) => memory_bytes(limit).to_string(),
+    }
+}
+
+fn cpu_limit(quota: Option<u64>, period: u64) -> Limit {
+    cpu_limit_from_quota(quota, period).map_or(Limit::Unlimited, Limit::Limited)
+}
+
+fn memory_limit(bytes: u64) -> Limit {
+    Limit::Limited(ParsedQuantity::from_base_decimal_suffixed(
+        Decimal::from(bytes),
+        Format::BinarySI,
+    ))
+}
+
+fn memory_bytes(limit: &ParsedQuantity) -> u64 {
+    let bytes = limit.to_base_decimal().ceil();
+    bytes.max(Decimal::ZERO).try_into().unwrap_or(u64::MAX)
+}
+
+fn parse_number(value: &str, content: &str) -> Result<u64, CgroupFileError> {
+    value.parse().map_err(|_| invalid_format(content))
+}
+
+fn invalid_format(content: &str) -> CgroupFileError {
+    CgroupFileError::InvalidFormat(content.to_owned())
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::fixture;
+
+    fn quantity(value: &str) -> ParsedQuantity {
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_cpu_shares() {
+        assert_eq!(cpu_shares(&quantity("0")), MIN_SHARES);
+        assert_eq!(cpu_shares(&quantity("1m")), MIN_SHARES);
+        assert_eq!(cpu_shares(&quantity("100m")), 102);
+        assert_eq!(cpu_shares(&quantity("1")), 1024);
+        assert_eq!(cpu_shares(&quantity("0.0001")), MIN_SHARES);
+        assert_eq!(cpu_shares(&quantity("1k")), MAX_SHARES);
+    }
+
+    #[test]
+    fn test_shares_round_trip() {
+        for milli_cpu in [2, 10, 100, 250, 333, 1000, 4500] {
+            let shares = milli_cpu_to_shares(milli_cpu);
+            assert_eq!(
+                milli_cpu_to_shares(shares_to_milli_cpu(shares)),
+                shares,
+                "{milli_cpu}m"
+            );
+        }
+
+        assert_eq!(shares_to_milli_cpu(1), 0);
+        assert_eq!(shares_to_milli_cpu(102), 100);
+    }
+
+    #[test]
+    fn test_cpu_quota() {
+        assert_eq!(
+            cpu_quota(Some(&quantity("100m")), QUOTA_PERIOD),
+            Some(10_000)
+        );
+        assert_eq!(cpu_quota(Some(&quantity("2")), 50_000), Some(100_000));
+        assert_eq!(
+            cpu_quota(Some(&quantity("1m")), QUOTA_PERIOD),
+            Some(MIN_QUOTA_PERIOD)
+        );
+        assert_eq!(cpu_quota(Some(&quantity("0")), QUOTA_PERIOD), None);
+
+        assert_eq!(quota_to_milli_cpu(10_000, QUOTA_PERIOD), 100);
+        assert_eq!(quota_to_milli_cpu(10_099, QUOTA_PERIOD), 100);
+        assert_eq!(quota_to_milli_cpu(10_000, 0), 0);
+    }
+
+    #[test]
+    fn test_weight_conversion() {
+        assert_eq!(shares_to_weight(0), 0);
+        assert_eq!(shares_to_weight(1), MIN_WEIGHT);
+        assert_eq!(shares_to_weight(102), 4);
+        assert_eq!(shares_to_weight(1_000_000), MAX_WEIGHT);
+
+        assert_eq!(weight_to_shares(0), 0);
+        assert_eq!(weight_to_shares(MIN_WEIGHT), MIN_SHARES);
+        assert_eq!(weight_to_shares(MAX_WEIGHT), MAX_SHARES);
+
+        for weight in MIN_WEIGHT..=MAX_WEIGHT {
+            let shares = weight_to_shares(weight);
+            assert_eq!(shares_to_weight(shares), weight);
+            assert_eq!(
+                shares_to_weight(shares - 1).max(MIN_WEIGHT),
+                weight.max(2) - 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_cpu_max() {
+        let cpu_max: CpuMax = "max 100000".parse().unwrap();
+        assert_eq!(cpu_max, CpuMax::default());
+        assert!(cpu_max.limit().is_unlimited());
+
+        // Limits are rounded down to millicores instead of repeating decimals
+        let cpu_max: CpuMax = "33333 100000".parse().unwrap();
+        assert_eq!(cpu_max.limit(), Limit::Limited(quantity("333m")));
+        let cpu_max: CpuMax = "10000 30000".parse().unwrap();
+        assert_eq!(cpu_max.limit().to_string(), "333m");
+        assert_eq!(
+            parse_cfs_quota_us("20000", "30000").unwrap().to_string(),
+            "666m"
+        );
+
+        let cpu_max: CpuMax = "20000".parse().unwrap();
+        assert_eq!(cpu_max.to_string(), "20000 100000");
+
+        assert_eq!(
+            "50000 0".parse::<CpuMax>(),
+            Err(CgroupFileError::ZeroPeriod)
+        );
+        for content in ["", "max max", "1 2 3", "-1 100000"] {
+            assert_eq!(
+                content.parse::<CpuMax>(),
+                Err(CgroupFileError::InvalidFormat(content.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_files() {
+        let limit = Limit::Limited(quantity("1.5Gi"));
+        assert_eq!(
+            parse_memory_max(&format_memory_max(&limit)),
+            Ok(limit.clone())
+        );
+        assert_eq!(
+            parse_memory_limit_in_bytes(&format_memory_limit_in_bytes(&limit)),
+            Ok(limit)
+        );
+
+        let unlimited = Limit::Unlimited;
+        assert_eq!(format_memory_limit_in_bytes(&unlimited), "-1");
+        assert_eq!(parse_memory_limit_in_bytes("-1"), Ok(unlimited));
+        assert_eq!(format_memory_max(&Limit::Limited(quantity("0.5"))), "1");
+        assert!(parse_memory_max("1G").is_err());
+    }
+
+    #[test]
+    fn test_memory_files_keep_exact_bytes() {
+        // Above 2^53, where f64 can no longer represent every integer
+        let exact = |bytes: u64| {
+            Limit::Limited(ParsedQuantity::from_base_decimal(
+                Decimal::from(bytes),
+                Format::BinarySI,
+            ))
+        };
+
+        let limit = parse_memory_max("9007199254740993").unwrap();
+        assert_eq!(limit, exact(9_007_199_254_740_993));
+        assert_eq!(format_memory_max(&limit), "9007199254740993");
+
+        // Just below the cgroup v1 unlimited sentinel
+        let limit = parse_memory_limit_in_bytes("9223372036854770001").unwrap();
+        assert_eq!(limit, exact(9_223_372_036_854_770_001));
+        assert_eq!(format_memory_limit_in_bytes(&limit), "9223372036854770001");
+
+        let limit = parse_memory_limit_in_bytes("9223372036854770688").unwrap();
+        assert_eq!(limit.to_string(), "9007199254740987Ki");
+    }
+
+    #[test]
+    fn test_cgroup_v2_fixtures_match_limits() {
+        let cpu_max: CpuMax = fixture("cgroup/v2/cpu.max").parse().unwrap();
+        assert_eq!(cpu_max.limit(), Limit::from(Some(&quantity("1500m"))));
+        assert_eq!(
+            cpu_max,
+            CpuMax::from_limit(&Limit::Limited(quantity("1.5")), QUOTA_PERIOD)
+        );
+
+        assert_eq!(
+            parse_memory_max(&fixture("cgroup/v2/memory.max")),
+            Ok(Limit::from(Some(&quantity("1Gi"))))
+        );
+        assert_eq!(
+            parse_memory_max(&fixture("cgroup/v2/memory.high")),
+            Ok(Limit::Unlimited)
+        );
+    }
+
+    #[test]
+    fn test_cgroup_v1_fixtures_match_limits() {
+        assert_eq!(
+            parse_cfs_quota_us(
+                &fixture("cgroup/v1/cpu.cfs_quota_us"),
+                &fixture("cgroup/v1/cpu.cfs_period_us")
+            ),
+            Ok(Limit::Unlimited)
+        );
+        assert_eq!(
+            parse_memory_limit_in_bytes(&fixture("cgroup/v1/memory.limit_in_bytes")),
+            Ok(Limit::Unlimited)
+        );
+        assert_eq!(
+            parse_cfs_quota_us("150000", &fixture("cgroup/v1/cpu.cfs_period_us")),
+            Ok(Limit::Limited(quantity("1500m")))
+        );
+    }
+}
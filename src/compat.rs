@@ -0,0 +1,72 @@
+// Differences between the supported k8s-openapi versions. Up to 0.25,
+// `Time` wraps a chrono `DateTime<Utc>` and specs are optional. Since 0.26,
+// `Time` wraps a jiff `Timestamp` and some specs are required.
+
+use chrono::{DateTime, Utc};
+use k8s_openapi::{
+    api::{autoscaling::v2::HorizontalPodAutoscalerSpec, batch::v1::CronJobSpec},
+    apimachinery::pkg::apis::meta::v1::Time,
+};
+
+/// Returns the instant of a `Time`. Both wrapped types format as RFC 3339
+/// with up to nanosecond precision, which chrono parses exactly.
+pub(crate) fn to_utc(time: &Time) -> DateTime<Utc> {
+    time.0
+        .to_string()
+        .parse()
+        .expect("timestamps format as RFC 3339")
+}
+
+/// Returns a `Time` of an instant.
+#[cfg(test)]
+pub(crate) fn from_utc(time: DateTime<Utc>) -> Time {
+    Time(
+        time.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            .parse()
+            .unwrap(),
+    )
+}
+
+/// Borrows a field that is optional in some k8s-openapi versions and required
+/// in others.
+pub(crate) trait AsOptional<T> {
+    fn as_optional(&self) -> Option<&T>;
+}
+
+impl<T> AsOptional<T> for Option<T> {
+    fn as_optional(&self) -> Option<&T> {
+        self.as_ref()
+    }
+}
+
+impl AsOptional<CronJobSpec> for CronJobSpec {
+    fn as_optional(&self) -> Option<&CronJobSpec> {
+        Some(self)
+    }
+}
+
+impl AsOptional<HorizontalPodAutoscalerSpec> for HorizontalPodAutoscalerSpec {
+    fn as_optional(&self) -> Option<&HorizontalPodAutoscalerSpec> {
+        Some(self)
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_utc() {
+        for input in [
+            "2023-11-14T22:13:20Z",
+            "2023-11-14T22:13:20.5Z",
+            "1970-01-01T00:00:00.000000001Z",
+        ] {
+            let expected: DateTime<Utc> = input.parse().unwrap();
+            assert_eq!(to_utc(&Time(input.parse().unwrap())), expected);
+            assert_eq!(to_utc(&from_utc(expected)), expected);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{Container, PodSpec, ResourceFieldSelector};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::{
+    resources::{container_limits, container_requests, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Resources whose missing limits are replaced by the node allocatable
+const ALLOCATABLE_DEFAULTED: [&str; 3] = ["cpu", "memory", "ephemeral-storage"];
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum DownwardApiError {
+    /// The resource cannot be exposed through the Downward API
+    #[error("unsupported container resource: {0}")]
+    UnsupportedResource(String),
+
+    /// The divisor is not greater than zero in the unit of the resource
+    #[error("divisor must be greater than zero, got {0}")]
+    InvalidDivisor(String),
+
+    /// A resource field reference names a container the pod does not have
+    #[error("unknown container {0:?}")]
+    UnknownContainer(String),
+
+    /// A quantity of the container, the divisor or the node could not be
+    /// parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Resource field references ---
+
+/// Returns the value the kubelet exposes for a `resourceFieldRef` of a
+/// container, i.e., the resource divided by the divisor and rounded up.
+///
+/// CPU is divided in millicores, all other resources in whole units. Missing
+/// or zero CPU, memory and ephemeral storage limits are replaced by the node
+/// allocatable, like the kubelet does before exposing them.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, ResourceFieldSelector, ResourceRequirements};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::{downward_api::resource_field_value, ParsedQuantity};
+///
+/// let container = Container {
+///     name: "app".to_string(),
+///     resources: Some(ResourceRequirements {
+///         requests: Some(BTreeMap::from([("cpu".to_string(), Quantity("250m".to_string()))])),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// let allocatable = BTreeMap::from([
+///     ("cpu".to_string(), ParsedQuantity::try_from("3920m").unwrap()),
+///     ("memory".to_string(), ParsedQuantity::try_from("15Gi").unwrap()),
+/// ]);
+/// let selector = |resource: &str, divisor: Option<&str>| ResourceFieldSelector {
+///     resource: resource.to_string(),
+///     divisor: divisor.map(|divisor| Quantity(divisor.to_string())),
+///     ..Default::default()
+/// };
+///
+/// let value = |resource, divisor| {
+///     resource_field_value(&selector(resource, divisor), &container, &allocatable).unwrap()
+/// };
+///
+/// assert_eq!(value("requests.cpu", None), "1");
+/// assert_eq!(value("requests.cpu", Some("1m")), "250");
+/// assert_eq!(value("limits.cpu", None), "4");
+/// assert_eq!(value("limits.memory", Some("1Mi")), "15360");
+/// ```
+pub fn resource_field_value(
+    selector: &ResourceFieldSelector,
+    container: &Container,
+    node_allocatable: &ParsedResourceList,
+) -> Result<String, DownwardApiError> {
+    let divisor = match &selector.divisor {
+        Some(divisor) => ParsedQuantity::try_from(divisor)?,
+        None => ParsedQuantity::default(),
+    };
+    let divisor = if divisor.to_base_decimal().is_zero() {
+        Decimal::ONE.into()
+    } else {
+        divisor
+    };
+
+    let (list, resource) = if let Some(resource) = selector.resource.strip_prefix("requests.") {
+        (container_requests(container)?, resource)
+    } else if let Some(resource) = selector.resource.strip_prefix("limits.") {
+        let mut limits = container_limits(container)?;
+        for name in ALLOCATABLE_DEFAULTED {
+            if let Some(allocatable) = node_allocatable.get(name) {
+                if limits
+                    .get(name)
+                    .is_none_or(|limit| limit.to_base_decimal().is_zero())
+                {
+                    limits.insert(name.to_owned(), allocatable.clone());
+                }
+            }
+        }
+        (limits, resource)
+    } else {
+        return Err(DownwardApiError::UnsupportedResource(
+            selector.resource.clone(),
+        ));
+    };
+
+    let quantity = list.get(resource).cloned().unwrap_or_default();
+    let (value, divisor_value) = match resource {
+        "cpu" => (
+            Decimal::from(quantity.to_milli_i64()),
+            Decimal::from(divisor.to_milli_i64()),
+        ),
+        "memory" | "ephemeral-storage" => (value(&quantity), value(&divisor)),
+        _ if resource.starts_with("hugepages-") => (value(&quantity), value(&divisor)),
+        _ => {
+            return Err(DownwardApiError::UnsupportedResource(
+                selector.resource.clone(),
+            ))
+        }
+    };
+
+    if divisor_value <= Decimal::ZERO {
+        return Err(DownwardApiError::InvalidDivisor(
+            divisor.to_canonical_string(),
+        ));
+    }
+
+    Ok((value / divisor_value).ceil().normalize().to_string())
+}
+
+/// Returns the values of all environment variables of a container that
+/// reference container resources, by variable name.
+///
+/// A `containerName` in the reference selects the container of the pod,
+/// including init containers, whose resources are exposed. A missing or empty
+/// name refers to the container itself.
+pub fn container_resource_env(
+    container: &Container,
+    pod: &PodSpec,
+    node_allocatable: &ParsedResourceList,
+) -> Result<BTreeMap<String, String>, DownwardApiError> {
+    container
+        .env
+        .iter()
+        .flatten()
+        .filter_map(|env| {
+            let selector = env.value_from.as_ref()?.resource_field_ref.as_ref()?;
+            let source = match selector.container_name.as_deref() {
+                None | Some("") => Some(container),
+                Some(name) if name == container.name => Some(container),
+                Some(name) => pod
+                    .containers
+                    .iter()
+                    .chain(pod.init_containers.iter().flatten())
+                    .find(|other| other.name == name),
+            };
+            let Some(source) = source else {
+                return Some(Err(DownwardApiError::UnknownContainer(
+                    selector.container_name.clone().unwrap_or_default(),
+                )));
+            };
+
+            Some(
+                resource_field_value(selector, source, node_allocatable)
+                    .map(|value| (env.name.clone(), value)),
+            )
+        })
+        .collect()
+}
+
+/// Returns the value in whole units, rounded up like `Quantity.Value()`.
+fn value(quantity: &ParsedQuantity) -> Decimal {
+    quantity.to_base_decimal().ceil()
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::core::v1::{EnvVar, EnvVarSource},
+        apimachinery::pkg::api::resource::Quantity,
+    };
+
+    use super::*;
+    use crate::test_util::container;
+
+    fn allocatable() -> ParsedResourceList {
+        BTreeMap::from([
+            ("cpu".to_owned(), "3920m".try_into().unwrap()),
+            ("memory".to_owned(), "15Gi".try_into().unwrap()),
+            ("ephemeral-storage".to_owned(), "100G".try_into().unwrap()),
+        ])
+    }
+
+    fn selector(resource: &str, divisor: Option<&str>) -> ResourceFieldSelector {
+        ResourceFieldSelector {
+            resource: resource.to_owned(),
+            divisor: divisor.map(|divisor| Quantity(divisor.to_owned())),
+            ..Default::default()
+        }
+    }
+
+    fn value(container: &Container, resource: &str, divisor: Option<&str>) -> String {
+        resource_field_value(&selector(resource, divisor), container, &allocatable()).unwrap()
+    }
+
+    #[test]
+    fn test_divisor_rounding() {
+        let container = container(
+            "app",
+            &[("cpu", "1250m"), ("memory", "100M")],
+            &[("cpu", "2"), ("memory", "1Gi"), ("hugepages-2Mi", "5Mi")],
+        );
+
+        assert_eq!(value(&container, "requests.cpu", None), "2");
+        assert_eq!(value(&container, "requests.cpu", Some("0")), "2");
+        assert_eq!(value(&container, "requests.cpu", Some("1m")), "1250");
+        assert_eq!(value(&container, "requests.cpu", Some("100m")), "13");
+        assert_eq!(value(&container, "requests.memory", None), "100000000");
+        assert_eq!(value(&container, "requests.memory", Some("1Mi")), "96");
+        assert_eq!(value(&container, "limits.memory", Some("1M")), "1074");
+        assert_eq!(value(&container, "limits.memory", Some("1Gi")), "1");
+        assert_eq!(value(&container, "limits.hugepages-2Mi", Some("2Mi")), "3");
+        // Requests default to limits
+        assert_eq!(
+            value(&container, "requests.hugepages-2Mi", Some("1Mi")),
+            "5"
+        );
+    }
+
+    #[test]
+    fn test_allocatable_fallback() {
+        let container = container("app", &[("cpu", "100m")], &[("cpu", "0")]);
+
+        assert_eq!(value(&container, "limits.cpu", None), "4");
+        assert_eq!(value(&container, "limits.cpu", Some("1m")), "3920");
+        assert_eq!(value(&container, "limits.memory", Some("1Gi")), "15");
+        assert_eq!(
+            value(&container, "limits.ephemeral-storage", Some("1G")),
+            "100"
+        );
+        // Requests and hugepages are not defaulted
+        assert_eq!(value(&container, "requests.memory", None), "0");
+        assert_eq!(value(&container, "limits.hugepages-1Gi", None), "0");
+    }
+
+    #[test]
+    fn test_errors() {
+        let container = container("app", &[], &[]);
+        let result = |resource: &str, divisor: Option<&str>| {
+            resource_field_value(&selector(resource, divisor), &container, &allocatable())
+        };
+
+        assert!(matches!(
+            result("limits.nvidia.com/gpu", None),
+            Err(DownwardApiError::UnsupportedResource(_))
+        ));
+        assert!(matches!(
+            result("cpu", None),
+            Err(DownwardApiError::UnsupportedResource(_))
+        ));
+        assert!(matches!(
+            result("limits.memory", Some("-1Mi")),
+            Err(DownwardApiError::InvalidDivisor(_))
+        ));
+        assert!(matches!(
+            result("limits.cpu", Some("-1m")),
+            Err(DownwardApiError::InvalidDivisor(_))
+        ));
+        assert!(matches!(
+            result("limits.cpu", Some("1.2.3")),
+            Err(DownwardApiError::InvalidQuantity(_))
+        ));
+    }
+
+    #[test]
+    fn test_container_resource_env() {
+        let mut app = container("app", &[("memory", "64Mi")], &[("memory", "128Mi")]);
+        let sidecar = container("sidecar", &[("memory", "16Mi")], &[("memory", "32Mi")]);
+        let init = container("init", &[("memory", "8Mi")], &[]);
+
+        let env = |name: &str, resource: &str, container_name: Option<&str>| EnvVar {
+            name: name.to_owned(),
+            value_from: Some(EnvVarSource {
+                resource_field_ref: Some(ResourceFieldSelector {
+                    container_name: container_name.map(ToOwned::to_owned),
+                    divisor: Some(Quantity("1Mi".to_owned())),
+                    resource: resource.to_owned(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        app.env = Some(vec![
+            EnvVar {
+                name: "PLAIN".to_owned(),
+                value: Some("value".to_owned()),
+                ..Default::default()
+            },
+            env("MEMORY_REQUEST", "requests.memory", None),
+            env("MEMORY_LIMIT", "limits.memory", Some("app")),
+            env("EMPTY_NAME", "requests.memory", Some("")),
+            env("SIDECAR_LIMIT", "limits.memory", Some("sidecar")),
+            env("INIT_REQUEST", "requests.memory", Some("init")),
+        ]);
+        let pod = PodSpec {
+            containers: vec![app.clone(), sidecar],
+            init_containers: Some(vec![init]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            container_resource_env(&app, &pod, &allocatable()).unwrap(),
+            BTreeMap::from([
+                ("EMPTY_NAME".to_owned(), "64".to_owned()),
+                ("INIT_REQUEST".to_owned(), "8".to_owned()),
+                ("MEMORY_LIMIT".to_owned(), "128".to_owned()),
+                ("MEMORY_REQUEST".to_owned(), "64".to_owned()),
+                ("SIDECAR_LIMIT".to_owned(), "32".to_owned()),
+            ])
+        );
+
+        app.env = Some(vec![env("MISSING", "limits.memory", Some("missing"))]);
+        assert!(matches!(
+            container_resource_env(&app, &pod, &allocatable()),
+            Err(DownwardApiError::UnknownContainer(name)) if name == "missing"
+        ));
+    }
+}
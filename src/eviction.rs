@@ -0,0 +1,466 @@
+use std::{cmp::Ordering, collections::BTreeMap, time::Duration};
+
+use k8s_openapi::api::core::v1::PodSpec;
+use rust_decimal::Decimal;
+
+use crate::{
+    kubelet::{EvictionSignal, EvictionThreshold, ThresholdOperator},
+    resources::{pod_requests, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Usage of the memory of a pod, i.e., its working set
+pub const USAGE_MEMORY: &str = "memory";
+/// Usage of the ephemeral storage of a pod, i.e., its logs, writable layers
+/// and local volumes
+pub const USAGE_EPHEMERAL_STORAGE: &str = "ephemeral-storage";
+/// Number of inodes a pod consumes
+pub const USAGE_INODES: &str = "inodes";
+/// Number of processes a pod runs
+pub const USAGE_PIDS: &str = "pids";
+
+// --- Simulation ---
+
+/// Observed value of an eviction signal on a node.
+#[derive(Debug, Clone)]
+pub struct SignalObservation {
+    /// Amount of the resource available
+    pub available: ParsedQuantity,
+    /// Total amount of the resource, which percentage thresholds refer to
+    pub capacity: ParsedQuantity,
+    /// How long the signal has been below its soft thresholds
+    pub pressure_duration: Duration,
+}
+
+impl SignalObservation {
+    /// Creates an observation that has just dropped below its thresholds.
+    pub fn new(available: ParsedQuantity, capacity: ParsedQuantity) -> Self {
+        Self {
+            available,
+            capacity,
+            pressure_duration: Duration::ZERO,
+        }
+    }
+}
+
+/// Observed signals of a node.
+pub type NodeStats = BTreeMap<EvictionSignal, SignalObservation>;
+
+/// A pod running on the node and its current usage.
+#[derive(Debug, Clone)]
+pub struct PodStats {
+    /// Name of the pod
+    pub name: String,
+    /// Spec of the pod, providing its requests and priority
+    pub spec: PodSpec,
+    /// Usage by name, see [`USAGE_MEMORY`], [`USAGE_EPHEMERAL_STORAGE`],
+    /// [`USAGE_INODES`] and [`USAGE_PIDS`]
+    pub usage: ParsedResourceList,
+}
+
+/// A threshold that is met by its observed signal.
+#[derive(Debug, Clone)]
+pub struct FiredThreshold {
+    /// The threshold
+    pub threshold: EvictionThreshold,
+    /// Value of the threshold resolved against the capacity
+    pub quantity: ParsedQuantity,
+    /// Observed available amount
+    pub available: ParsedQuantity,
+}
+
+/// Outcome of a node-pressure eviction cycle.
+#[derive(Debug, Clone, Default)]
+pub struct EvictionSimulation {
+    /// Thresholds that are met, in the order the kubelet acts on them
+    pub fired: Vec<FiredThreshold>,
+    /// Names of the pods in the order they would be evicted to relieve the
+    /// first fired threshold
+    pub eviction_order: Vec<String>,
+}
+
+impl EvictionSimulation {
+    /// Returns the signals of all fired thresholds without duplicates.
+    pub fn fired_signals(&self) -> Vec<EvictionSignal> {
+        let mut signals: Vec<EvictionSignal> = Vec::new();
+        for fired in &self.fired {
+            if !signals.contains(&fired.threshold.signal) {
+                signals.push(fired.threshold.signal);
+            }
+        }
+        signals
+    }
+}
+
+/// Simulates a node-pressure eviction cycle of the kubelet.
+///
+/// A threshold is met when the available amount of its signal drops below the
+/// threshold, with soft thresholds firing once the pressure lasted for their
+/// grace period. Memory thresholds are acted on first. Pods are then ranked
+/// for the first fired threshold:
+///
+/// - memory and disk pressure rank pods whose usage exceeds their requests
+///   first, then by lower priority, then by larger usage above requests
+/// - PID pressure ranks pods by lower priority, then by more processes
+///
+/// The kubelet evicts a single pod per cycle and re-evaluates afterwards.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::PodSpec;
+/// use kube_quantity::eviction::{simulate_eviction, PodStats, SignalObservation};
+/// use kube_quantity::kubelet::{EvictionFlags, EvictionSignal};
+///
+/// let thresholds = EvictionFlags {
+///     eviction_hard: "memory.available<500Mi".to_string(),
+///     ..Default::default()
+/// }
+/// .parse()
+/// .unwrap();
+///
+/// let stats = BTreeMap::from([(
+///     EvictionSignal::MemoryAvailable,
+///     SignalObservation::new("400Mi".try_into().unwrap(), "8Gi".try_into().unwrap()),
+/// )]);
+/// let pod = |name: &str, usage: &str| PodStats {
+///     name: name.to_string(),
+///     spec: PodSpec::default(),
+///     usage: BTreeMap::from([("memory".to_string(), usage.try_into().unwrap())]),
+/// };
+///
+/// let simulation = simulate_eviction(&thresholds, &stats, &[pod("small", "1Gi"), pod("large", "3Gi")]).unwrap();
+///
+/// assert_eq!(simulation.fired_signals(), vec![EvictionSignal::MemoryAvailable]);
+/// assert_eq!(simulation.eviction_order, vec!["large", "small"]);
+/// ```
+pub fn simulate_eviction(
+    thresholds: &[EvictionThreshold],
+    stats: &NodeStats,
+    pods: &[PodStats],
+) -> Result<EvictionSimulation, ParseQuantityError> {
+    let mut fired: Vec<FiredThreshold> = thresholds
+        .iter()
+        .filter_map(|threshold| {
+            let observation = stats.get(&threshold.signal)?;
+            let quantity = threshold.value.resolve(&observation.capacity);
+
+            let is_met = match threshold.operator {
+                ThresholdOperator::LessThan => {
+                    observation.available.to_base_decimal() < quantity.to_base_decimal()
+                }
+            };
+            let is_due = observation.pressure_duration >= threshold.grace_period;
+
+            (is_met && is_due).then(|| FiredThreshold {
+                threshold: threshold.clone(),
+                quantity,
+                available: observation.available.clone(),
+            })
+        })
+        .collect();
+
+    fired.sort_by_key(|fired| eviction_priority(fired.threshold.signal));
+
+    let eviction_order = match fired.first() {
+        Some(fired) => rank_pods(fired.threshold.signal, pods)?,
+        None => Vec::new(),
+    };
+
+    Ok(EvictionSimulation {
+        fired,
+        eviction_order,
+    })
+}
+
+/// Memory signals are relieved before all others.
+fn eviction_priority(signal: EvictionSignal) -> u8 {
+    match signal {
+        EvictionSignal::MemoryAvailable | EvictionSignal::AllocatableMemoryAvailable => 0,
+        _ => 1,
+    }
+}
+
+/// Returns the usage resource and the request it is compared against for a
+/// signal, if any.
+fn ranked_resource(signal: EvictionSignal) -> (&'static str, Option<&'static str>) {
+    match signal {
+        EvictionSignal::MemoryAvailable | EvictionSignal::AllocatableMemoryAvailable => {
+            (USAGE_MEMORY, Some("memory"))
+        }
+        EvictionSignal::NodeFsAvailable
+        | EvictionSignal::ImageFsAvailable
+        | EvictionSignal::ContainerFsAvailable => {
+            (USAGE_EPHEMERAL_STORAGE, Some("ephemeral-storage"))
+        }
+        EvictionSignal::NodeFsInodesFree
+        | EvictionSignal::ImageFsInodesFree
+        | EvictionSignal::ContainerFsInodesFree => (USAGE_INODES, None),
+        EvictionSignal::PidAvailable => (USAGE_PIDS, None),
+    }
+}
+
+struct RankedPod<'a> {
+    name: &'a str,
+    priority: i32,
+    usage: Decimal,
+    request: Decimal,
+}
+
+fn rank_pods(signal: EvictionSignal, pods: &[PodStats]) -> Result<Vec<String>, ParseQuantityError> {
+    let (usage_name, request_name) = ranked_resource(signal);
+
+    let mut ranked = pods
+        .iter()
+        .map(|pod| {
+            let request = match request_name {
+                Some(request_name) => pod_requests_without_overhead(&pod.spec)?
+                    .get(request_name)
+                    .map(ParsedQuantity::to_base_decimal)
+                    .unwrap_or_default(),
+                None => Decimal::ZERO,
+            };
+
+            Ok(RankedPod {
+                name: &pod.name,
+                priority: pod.spec.priority.unwrap_or_default(),
+                usage: pod
+                    .usage
+                    .get(usage_name)
+                    .map(ParsedQuantity::to_base_decimal)
+                    .unwrap_or_default(),
+                request,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseQuantityError>>()?;
+
+    if signal == EvictionSignal::PidAvailable {
+        ranked.sort_by(|lhs, rhs| {
+            lhs.priority
+                .cmp(&rhs.priority)
+                .then_with(|| rhs.usage.cmp(&lhs.usage))
+        });
+    } else {
+        ranked.sort_by(|lhs, rhs| {
+            exceeds_requests(rhs)
+                .cmp(&exceeds_requests(lhs))
+                .then_with(|| lhs.priority.cmp(&rhs.priority))
+                .then_with(|| above_requests(rhs, lhs))
+        });
+    }
+
+    Ok(ranked.into_iter().map(|pod| pod.name.to_owned()).collect())
+}
+
+fn exceeds_requests(pod: &RankedPod) -> bool {
+    pod.usage > pod.request
+}
+
+fn above_requests(lhs: &RankedPod, rhs: &RankedPod) -> Ordering {
+    (lhs.usage - lhs.request).cmp(&(rhs.usage - rhs.request))
+}
+
+/// Returns the requests of the containers of a pod, which the kubelet ranks
+/// pods by, excluding the pod overhead.
+fn pod_requests_without_overhead(spec: &PodSpec) -> Result<ParsedResourceList, ParseQuantityError> {
+    if spec.overhead.is_none() {
+        return pod_requests(spec);
+    }
+
+    pod_requests(&PodSpec {
+        overhead: None,
+        ..spec.clone()
+    })
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::core::v1::{Container, ResourceRequirements},
+        apimachinery::pkg::api::resource::Quantity,
+    };
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{kubelet::EvictionFlags, resources::parse_resource_list, test_util::fixture};
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Scenario {
+        flags: ScenarioFlags,
+        signals: BTreeMap<String, ScenarioSignal>,
+        pods: Vec<ScenarioPod>,
+        expected: ScenarioExpectation,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioFlags {
+        #[serde(default)]
+        eviction_hard: String,
+        #[serde(default)]
+        eviction_soft: String,
+        #[serde(default)]
+        eviction_soft_grace_period: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioSignal {
+        available: Quantity,
+        capacity: Quantity,
+        #[serde(default)]
+        pressure_seconds: u64,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioPod {
+        name: String,
+        #[serde(default)]
+        priority: Option<i32>,
+        #[serde(default)]
+        requests: BTreeMap<String, Quantity>,
+        usage: BTreeMap<String, Quantity>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioExpectation {
+        fired: Vec<String>,
+        eviction_order: Vec<String>,
+    }
+
+    fn run_scenario(name: &str) {
+        let scenario: Scenario =
+            serde_yaml::from_str(&fixture(&format!("eviction/{name}"))).unwrap();
+
+        let thresholds = EvictionFlags {
+            eviction_hard: scenario.flags.eviction_hard,
+            eviction_soft: scenario.flags.eviction_soft,
+            eviction_soft_grace_period: scenario.flags.eviction_soft_grace_period,
+            ..Default::default()
+        }
+        .parse()
+        .unwrap();
+
+        let stats: NodeStats = scenario
+            .signals
+            .into_iter()
+            .map(|(signal, observation)| {
+                (
+                    signal.parse().unwrap(),
+                    SignalObservation {
+                        available: observation.available.try_into().unwrap(),
+                        capacity: observation.capacity.try_into().unwrap(),
+                        pressure_duration: Duration::from_secs(observation.pressure_seconds),
+                    },
+                )
+            })
+            .collect();
+
+        let pods: Vec<PodStats> = scenario
+            .pods
+            .into_iter()
+            .map(|pod| PodStats {
+                name: pod.name,
+                spec: PodSpec {
+                    priority: pod.priority,
+                    containers: vec![Container {
+                        resources: Some(ResourceRequirements {
+                            requests: Some(pod.requests),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                usage: parse_resource_list(&pod.usage).unwrap(),
+            })
+            .collect();
+
+        let simulation = simulate_eviction(&thresholds, &stats, &pods).unwrap();
+        let fired: Vec<&str> = simulation
+            .fired_signals()
+            .iter()
+            .map(EvictionSignal::as_str)
+            .collect();
+
+        assert_eq!(fired, scenario.expected.fired, "{name}");
+        assert_eq!(
+            simulation.eviction_order, scenario.expected.eviction_order,
+            "{name}"
+        );
+    }
+
+    #[test]
+    fn test_memory_pressure_scenario() {
+        run_scenario("memory-pressure.yaml");
+    }
+
+    #[test]
+    fn test_disk_pressure_scenario() {
+        run_scenario("disk-pressure.yaml");
+    }
+
+    #[test]
+    fn test_soft_threshold_grace_period() {
+        let thresholds = EvictionFlags {
+            eviction_soft: "memory.available<1Gi".to_owned(),
+            eviction_soft_grace_period: "memory.available=1m".to_owned(),
+            ..Default::default()
+        }
+        .parse()
+        .unwrap();
+        let observation = |seconds: u64| {
+            BTreeMap::from([(
+                EvictionSignal::MemoryAvailable,
+                SignalObservation {
+                    available: "512Mi".try_into().unwrap(),
+                    capacity: "8Gi".try_into().unwrap(),
+                    pressure_duration: Duration::from_secs(seconds),
+                },
+            )])
+        };
+
+        let simulation = simulate_eviction(&thresholds, &observation(30), &[]).unwrap();
+        assert!(simulation.fired.is_empty());
+
+        let simulation = simulate_eviction(&thresholds, &observation(60), &[]).unwrap();
+        assert_eq!(simulation.fired[0].quantity.to_canonical_string(), "1Gi");
+    }
+
+    #[test]
+    fn test_pid_pressure_ranking() {
+        let thresholds = EvictionFlags {
+            eviction_hard: "pid.available<10%".to_owned(),
+            ..Default::default()
+        }
+        .parse()
+        .unwrap();
+        let stats = BTreeMap::from([(
+            EvictionSignal::PidAvailable,
+            SignalObservation::new("100".try_into().unwrap(), "4096".try_into().unwrap()),
+        )]);
+        let pod = |name: &str, priority: i32, pids: &str| PodStats {
+            name: name.to_owned(),
+            spec: PodSpec {
+                priority: Some(priority),
+                ..Default::default()
+            },
+            usage: BTreeMap::from([(USAGE_PIDS.to_owned(), pids.try_into().unwrap())]),
+        };
+
+        let simulation = simulate_eviction(
+            &thresholds,
+            &stats,
+            &[pod("a", 100, "2000"), pod("b", 0, "10"), pod("c", 0, "500")],
+        )
+        .unwrap();
+
+        assert_eq!(simulation.fired[0].quantity.to_string(), "409");
+        assert_eq!(simulation.eviction_order, vec!["c", "b", "a"]);
+    }
+}
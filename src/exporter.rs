@@ -0,0 +1,634 @@
+use std::fmt::Write;
+
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::{
+    format::Format,
+    resources::{is_binary_resource, ParsedResourceList},
+    ParsedQuantity,
+};
+
+/// Unit label of CPU resources
+pub const UNIT_CORE: &str = "core";
+/// Unit label of memory and storage resources
+pub const UNIT_BYTE: &str = "byte";
+/// Unit label of countable resources, e.g., `pods` or extended resources
+pub const UNIT_INTEGER: &str = "integer";
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum ExporterError {
+    /// A line is not a valid sample
+    #[error("line {line}: {message}")]
+    InvalidLine { line: usize, message: String },
+
+    /// The value of a sample is not a finite number
+    #[error("line {line}: invalid sample value {value:?}")]
+    InvalidValue { line: usize, value: String },
+}
+
+// --- Metric families ---
+
+/// Text format of the exposition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextFormat {
+    /// Prometheus text format 0.0.4
+    #[default]
+    Prometheus,
+    /// OpenMetrics text format 1.0.0, terminated by `# EOF`
+    OpenMetrics,
+}
+
+/// Type of a metric family.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetricType {
+    /// A value that can go up and down
+    #[default]
+    Gauge,
+    /// A monotonically increasing value
+    Counter,
+}
+
+impl MetricType {
+    /// Returns the name of the type as used in `# TYPE` lines.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Gauge => "gauge",
+            MetricType::Counter => "counter",
+        }
+    }
+}
+
+/// A sample of a metric family with its labels in exposition order.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// Label names and values
+    pub labels: Vec<(String, String)>,
+    /// Value, which is exposed in base units
+    pub value: ParsedQuantity,
+}
+
+/// Metric family with its metadata and samples.
+#[derive(Debug, Clone)]
+pub struct MetricFamily {
+    /// Name of the family, e.g., `kube_pod_container_resource_requests`
+    pub name: String,
+    /// Help text
+    pub help: String,
+    /// Type of the family
+    pub metric_type: MetricType,
+    /// Samples of the family
+    pub samples: Vec<Sample>,
+}
+
+impl MetricFamily {
+    /// Creates a family without samples.
+    pub fn new(name: impl Into<String>, help: impl Into<String>, metric_type: MetricType) -> Self {
+        Self {
+            name: name.into(),
+            help: help.into(),
+            metric_type,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Adds a sample with the given labels.
+    pub fn push(&mut self, labels: &[(&str, &str)], value: ParsedQuantity) {
+        self.samples.push(Sample {
+            labels: labels
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            value,
+        });
+    }
+
+    /// Adds one sample per resource of the list, with the `resource` and
+    /// `unit` labels appended like kube-state-metrics does.
+    pub fn push_resources(&mut self, labels: &[(&str, &str)], list: &ParsedResourceList) {
+        for (resource, quantity) in list {
+            let resource_label = resource_label(resource);
+            let mut labels = labels.to_vec();
+            labels.push(("resource", &resource_label));
+            labels.push(("unit", unit_for_resource(resource)));
+            self.push(&labels, quantity.clone());
+        }
+    }
+}
+
+/// Renders the families in the text format, with each value as the exact
+/// decimal in base units, i.e., cores and bytes.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use kube_quantity::exporter::{encode, MetricFamily, MetricType, TextFormat};
+///
+/// let mut family = MetricFamily::new(
+///     "kube_pod_container_resource_requests",
+///     "The number of requested resource by a container.",
+///     MetricType::Gauge,
+/// );
+/// family.push_resources(
+///     &[("namespace", "default"), ("pod", "web"), ("container", "app")],
+///     &BTreeMap::from([
+///         ("cpu".to_string(), "250m".try_into().unwrap()),
+///         ("memory".to_string(), "1Gi".try_into().unwrap()),
+///     ]),
+/// );
+///
+/// assert_eq!(
+///     encode(&[family], TextFormat::Prometheus),
+///     concat!(
+///         "# HELP kube_pod_container_resource_requests The number of requested resource by a container.\n",
+///         "# TYPE kube_pod_container_resource_requests gauge\n",
+///         "kube_pod_container_resource_requests{namespace=\"default\",pod=\"web\",container=\"app\",resource=\"cpu\",unit=\"core\"} 0.25\n",
+///         "kube_pod_container_resource_requests{namespace=\"default\",pod=\"web\",container=\"app\",resource=\"memory\",unit=\"byte\"} 1073741824\n",
+///     ),
+/// );
+/// ```
+pub fn encode(families: &[MetricFamily], format: TextFormat) -> String {
+    let mut text = String::new();
+
+    for family in families {
+        // OpenMetrics names counter families without the suffix of their
+        // samples
+        let (family_name, sample_name) = match (format, family.metric_type) {
+            (TextFormat::OpenMetrics, MetricType::Counter) => {
+                let name = family
+                    .name
+                    .strip_suffix("_total")
+                    .unwrap_or(&family.name)
+                    .to_owned();
+                let sample_name = format!("{name}_total");
+                (name, sample_name)
+            }
+            _ => (family.name.clone(), family.name.clone()),
+        };
+
+        let _ = writeln!(text, "# HELP {family_name} {}", escape_help(&family.help));
+        let _ = writeln!(text, "# TYPE {family_name} {}", family.metric_type.as_str());
+
+        for sample in &family.samples {
+            text.push_str(&sample_name);
+            if !sample.labels.is_empty() {
+                let labels: Vec<String> = sample
+                    .labels
+                    .iter()
+                    .map(|(name, value)| format!("{name}=\"{}\"", escape_label_value(value)))
+                    .collect();
+                let _ = write!(text, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(text, " {}", format_value(&sample.value));
+        }
+    }
+
+    if format == TextFormat::OpenMetrics {
+        text.push_str("# EOF\n");
+    }
+
+    text
+}
+
+// --- Parsing ---
+
+type Labels = Vec<(String, String)>;
+
+/// Sample parsed from the text format.
+#[derive(Debug, Clone)]
+pub struct ParsedSample {
+    /// Name of the sample, including suffixes such as `_total`
+    pub name: String,
+    /// Label names and values in exposition order
+    pub labels: Vec<(String, String)>,
+    /// Value in base units, using binary suffixes for bytes
+    pub value: ParsedQuantity,
+    /// Timestamp in milliseconds, if any
+    pub timestamp: Option<i64>,
+}
+
+impl ParsedSample {
+    /// Returns the value of a label.
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(label, _)| label == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses the samples of the Prometheus or OpenMetrics text format back into
+/// quantities. Comments and metadata are skipped. Values with the `byte`
+/// unit label use binary suffixes in their canonical form.
+///
+/// ```rust
+/// use kube_quantity::exporter::parse_text;
+///
+/// let samples = parse_text(
+///     "kube_node_status_allocatable{node=\"a\",resource=\"memory\",unit=\"byte\"} 16106127360\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(samples[0].label("resource"), Some("memory"));
+/// assert_eq!(samples[0].value.to_canonical_string(), "15Gi");
+/// ```
+pub fn parse_text(input: &str) -> Result<Vec<ParsedSample>, ExporterError> {
+    let mut samples = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |message: &str| ExporterError::InvalidLine {
+            line: line_number,
+            message: message.to_owned(),
+        };
+
+        let name_end = line
+            .find(|c: char| c == '{' || c.is_whitespace())
+            .ok_or_else(|| invalid("missing sample value"))?;
+        let name = &line[..name_end];
+        if !is_valid_name(name, true) {
+            return Err(invalid("invalid metric name"));
+        }
+
+        let mut rest = &line[name_end..];
+        let mut labels = Vec::new();
+        if let Some(label_text) = rest.strip_prefix('{') {
+            let (parsed, remainder) =
+                parse_labels(label_text).map_err(|message| invalid(&message))?;
+            labels = parsed;
+            rest = remainder;
+        }
+
+        let mut fields = rest.split_whitespace();
+        let value = fields
+            .next()
+            .ok_or_else(|| invalid("missing sample value"))?;
+        let timestamp = fields
+            .next()
+            .map(|timestamp| {
+                timestamp
+                    .parse::<i64>()
+                    .map_err(|_| invalid("invalid timestamp"))
+            })
+            .transpose()?;
+        if fields.next().is_some() {
+            return Err(invalid("unexpected trailing content"));
+        }
+
+        let decimal = Decimal::from_str_exact(value)
+            .or_else(|_| Decimal::from_scientific(value))
+            .map_err(|_| ExporterError::InvalidValue {
+                line: line_number,
+                value: value.to_owned(),
+            })?;
+
+        let unit = labels
+            .iter()
+            .find(|(label, _)| label == "unit")
+            .map(|(_, unit)| unit.as_str());
+        let format = if unit == Some(UNIT_BYTE) {
+            Format::BinarySI
+        } else {
+            Format::DecimalSI
+        };
+
+        samples.push(ParsedSample {
+            name: name.to_owned(),
+            labels,
+            value: ParsedQuantity::from_base_decimal(decimal, format),
+            timestamp,
+        });
+    }
+
+    Ok(samples)
+}
+
+/// Parses the labels after the opening brace and returns the text after
+/// the closing brace.
+fn parse_labels(text: &str) -> Result<(Labels, &str), String> {
+    let mut labels = Vec::new();
+    let mut rest = text.trim_start();
+
+    loop {
+        if let Some(remainder) = rest.strip_prefix('}') {
+            return Ok((labels, remainder));
+        }
+
+        let name_end = rest.find('=').ok_or("missing label value")?;
+        let name = rest[..name_end].trim();
+        if !is_valid_name(name, false) {
+            return Err(format!("invalid label name {name:?}"));
+        }
+
+        rest = rest[name_end + 1..]
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or("label value must be quoted")?;
+
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((index, '"')) => break index,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, '\\')) => value.push('\\'),
+                    Some((_, '"')) => value.push('"'),
+                    _ => return Err("invalid escape sequence".to_owned()),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("unterminated label value".to_owned()),
+            }
+        };
+        labels.push((name.to_owned(), value));
+
+        rest = rest[end + 1..].trim_start();
+        if let Some(remainder) = rest.strip_prefix(',') {
+            rest = remainder.trim_start();
+        } else if !rest.starts_with('}') {
+            return Err("expected ',' or '}' after label".to_owned());
+        }
+    }
+}
+
+// --- Labels ---
+
+/// Returns the `unit` label kube-state-metrics uses for a resource.
+///
+/// ```rust
+/// use kube_quantity::exporter::unit_for_resource;
+///
+/// assert_eq!(unit_for_resource("cpu"), "core");
+/// assert_eq!(unit_for_resource("hugepages-2Mi"), "byte");
+/// assert_eq!(unit_for_resource("nvidia.com/gpu"), "integer");
+/// ```
+pub fn unit_for_resource(resource: &str) -> &'static str {
+    if resource == "cpu" {
+        UNIT_CORE
+    } else if is_binary_resource(resource) {
+        UNIT_BYTE
+    } else {
+        UNIT_INTEGER
+    }
+}
+
+/// Returns the resource name as a label value, where characters that are
+/// invalid in label names are replaced like kube-state-metrics does, e.g.,
+/// `nvidia.com/gpu` becomes `nvidia_com_gpu`.
+pub fn resource_label(resource: &str) -> String {
+    resource
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Returns the value in base units as an exact decimal.
+fn format_value(quantity: &ParsedQuantity) -> String {
+    quantity.to_base_decimal().normalize().to_string()
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Returns whether the name is a valid metric name, or label name if colons
+/// are not allowed.
+fn is_valid_name(name: &str, allow_colon: bool) -> bool {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || (allow_colon && c == ':');
+
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit()) && name.chars().all(valid)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::test_util::{fixture, parsed_list};
+
+    fn families() -> Vec<MetricFamily> {
+        let mut requests = MetricFamily::new(
+            "kube_pod_container_resource_requests",
+            "The number of requested resource by a container.",
+            MetricType::Gauge,
+        );
+        requests.push_resources(
+            &[
+                ("namespace", "default"),
+                ("pod", "web-0"),
+                ("container", "app"),
+            ],
+            &parsed_list(&[
+                ("cpu", "1500m"),
+                ("memory", "1536Mi"),
+                ("nvidia.com/gpu", "1"),
+            ]),
+        );
+        requests.push_resources(
+            &[
+                ("namespace", "default"),
+                ("pod", "web-0"),
+                ("container", "proxy"),
+            ],
+            &parsed_list(&[("cpu", "1n"), ("ephemeral-storage", "1G")]),
+        );
+
+        let mut allocatable = MetricFamily::new(
+            "kube_node_status_allocatable",
+            "The allocatable for different resources of a node that are available for scheduling.",
+            MetricType::Gauge,
+        );
+        allocatable.push_resources(
+            &[("node", "worker-1")],
+            &parsed_list(&[
+                ("cpu", "7910m"),
+                ("hugepages-2Mi", "512Mi"),
+                ("memory", "15Gi"),
+                ("pods", "110"),
+            ]),
+        );
+
+        let mut cpu_seconds = MetricFamily::new(
+            "container_cpu_usage_seconds_total",
+            "Cumulative cpu time consumed in seconds.",
+            MetricType::Counter,
+        );
+        cpu_seconds.push(
+            &[
+                ("namespace", "default"),
+                ("pod", "web-0"),
+                ("container", "app"),
+            ],
+            "1002500m".try_into().unwrap(),
+        );
+
+        vec![requests, allocatable, cpu_seconds]
+    }
+
+    #[test]
+    fn test_encode_prometheus() {
+        assert_eq!(
+            encode(&families(), TextFormat::Prometheus),
+            fixture("exporter/resources.prom")
+        );
+    }
+
+    #[test]
+    fn test_encode_openmetrics() {
+        let text = encode(&families(), TextFormat::OpenMetrics);
+
+        assert!(text.contains("# TYPE container_cpu_usage_seconds counter\n"));
+        assert!(text.contains("\ncontainer_cpu_usage_seconds_total{"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [TextFormat::Prometheus, TextFormat::OpenMetrics] {
+            let expected: Vec<Sample> = families()
+                .into_iter()
+                .flat_map(|family| family.samples)
+                .collect();
+            let parsed = parse_text(&encode(&families(), format)).unwrap();
+
+            assert_eq!(parsed.len(), expected.len());
+            for (parsed, expected) in parsed.iter().zip(expected) {
+                assert_eq!(parsed.labels, expected.labels);
+                assert_eq!(
+                    parsed.value.to_base_decimal(),
+                    expected.value.to_base_decimal()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let samples = parse_text(&fixture("exporter/resources.prom")).unwrap();
+
+        let format = |name: &str, resource: &str| {
+            samples
+                .iter()
+                .find(|sample| sample.name == name && sample.label("resource") == Some(resource))
+                .map(|sample| sample.value.to_canonical_string())
+                .unwrap()
+        };
+        assert_eq!(
+            format("kube_pod_container_resource_requests", "cpu"),
+            "1500m"
+        );
+        assert_eq!(
+            format("kube_pod_container_resource_requests", "memory"),
+            "1536Mi"
+        );
+        assert_eq!(
+            format("kube_pod_container_resource_requests", "nvidia_com_gpu"),
+            "1"
+        );
+        assert_eq!(
+            format("kube_node_status_allocatable", "hugepages_2Mi"),
+            "512Mi"
+        );
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let samples = parse_text(concat!(
+            "# HELP node_memory_MemTotal_bytes Memory information field MemTotal_bytes.\n",
+            "node_memory_MemTotal_bytes{unit=\"byte\"} 1.7179869184e+10\n",
+            "process_start_time_seconds 1700000000.25 1700000001000\n",
+            "up{ job = \"node\" , } 1\n",
+        ))
+        .unwrap();
+
+        assert_eq!(samples[0].value.to_canonical_string(), "16Gi");
+        assert_eq!(
+            samples[1].value.to_base_decimal().to_string(),
+            "1700000000.25"
+        );
+        assert_eq!(samples[1].timestamp, Some(1700000001000));
+        assert_eq!(samples[2].label("job"), Some("node"));
+    }
+
+    #[test]
+    fn test_label_escaping() {
+        let mut family = MetricFamily::new("test", "Line one\nline \\two", MetricType::Gauge);
+        family.push(&[("path", "C:\\dir \"a\"\nb")], "1".try_into().unwrap());
+
+        let text = encode(&[family], TextFormat::Prometheus);
+        assert!(text.contains("# HELP test Line one\\nline \\\\two\n"));
+        assert!(text.contains("test{path=\"C:\\\\dir \\\"a\\\"\\nb\"} 1\n"));
+
+        let samples = parse_text(&text).unwrap();
+        assert_eq!(samples[0].label("path"), Some("C:\\dir \"a\"\nb"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, message) in [
+            ("up", "line 1: missing sample value"),
+            ("1up 1", "line 1: invalid metric name"),
+            ("up{job=node} 1", "line 1: label value must be quoted"),
+            ("up{job=\"node} 1", "line 1: unterminated label value"),
+            ("up{job=\"a\\t\"} 1", "line 1: invalid escape sequence"),
+            (
+                "up{job=\"a\" x=\"b\"} 1",
+                "line 1: expected ',' or '}' after label",
+            ),
+            ("up 1 now", "line 1: invalid timestamp"),
+            ("\nup NaN", "line 2: invalid sample value \"NaN\""),
+            ("up +Inf", "line 1: invalid sample value \"+Inf\""),
+        ] {
+            assert_eq!(
+                parse_text(input).unwrap_err().to_string(),
+                message,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unit_for_resource() {
+        let units: BTreeMap<&str, &str> = [
+            "cpu",
+            "memory",
+            "ephemeral-storage",
+            "storage",
+            "hugepages-1Gi",
+            "pods",
+            "nvidia.com/gpu",
+        ]
+        .into_iter()
+        .map(|resource| (resource, unit_for_resource(resource)))
+        .collect();
+
+        assert_eq!(
+            units,
+            BTreeMap::from([
+                ("cpu", UNIT_CORE),
+                ("ephemeral-storage", UNIT_BYTE),
+                ("hugepages-1Gi", UNIT_BYTE),
+                ("memory", UNIT_BYTE),
+                ("nvidia.com/gpu", UNIT_INTEGER),
+                ("pods", UNIT_INTEGER),
+                ("storage", UNIT_BYTE),
+            ])
+        );
+    }
+}
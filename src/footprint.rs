@@ -0,0 +1,617 @@
+use k8s_openapi::{
+    api::{
+        apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+        batch::v1::{CronJob, Job, JobSpec},
+        core::v1::{PodSpec, PodTemplateSpec},
+    },
+    apimachinery::pkg::util::intstr::IntOrString,
+};
+use thiserror::Error;
+
+use crate::{
+    compat::AsOptional,
+    resources::{pod_limits, pod_requests, ParsedResourceList},
+    ParseQuantityError,
+};
+
+/// Default `maxSurge` of Deployments using the rolling update strategy
+const DEFAULT_DEPLOYMENT_MAX_SURGE: &str = "25%";
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum FootprintError {
+    /// The controller has no pod template
+    #[error("{0} has no pod template")]
+    MissingPodTemplate(String),
+
+    /// A `maxSurge` value is neither an integer nor a percentage
+    #[error("invalid value for IntOrString: {0}")]
+    InvalidIntOrPercent(String),
+
+    /// A quantity of the pod template could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Controllers ---
+
+/// A controller owning a pod template.
+#[derive(Debug, Clone)]
+pub enum Controller {
+    /// Runs `spec.replicas` pods, surging by `maxSurge` during rollouts
+    Deployment(Box<Deployment>),
+    /// Runs `spec.replicas` pods, replacing them without surge
+    StatefulSet(Box<StatefulSet>),
+    /// Runs a pod per node, surging by `maxSurge` during rollouts
+    DaemonSet(Box<DaemonSet>),
+    /// Runs `spec.replicas` pods
+    ReplicaSet(Box<ReplicaSet>),
+    /// Runs up to `spec.parallelism` pods, bounded by `spec.completions`
+    Job(Box<Job>),
+    /// Runs the pods of its job template, once or concurrently depending on
+    /// its `concurrencyPolicy`
+    CronJob(Box<CronJob>),
+}
+
+impl From<Deployment> for Controller {
+    fn from(value: Deployment) -> Self {
+        Controller::Deployment(Box::new(value))
+    }
+}
+
+impl From<StatefulSet> for Controller {
+    fn from(value: StatefulSet) -> Self {
+        Controller::StatefulSet(Box::new(value))
+    }
+}
+
+impl From<DaemonSet> for Controller {
+    fn from(value: DaemonSet) -> Self {
+        Controller::DaemonSet(Box::new(value))
+    }
+}
+
+impl From<ReplicaSet> for Controller {
+    fn from(value: ReplicaSet) -> Self {
+        Controller::ReplicaSet(Box::new(value))
+    }
+}
+
+impl From<Job> for Controller {
+    fn from(value: Job) -> Self {
+        Controller::Job(Box::new(value))
+    }
+}
+
+impl From<CronJob> for Controller {
+    fn from(value: CronJob) -> Self {
+        Controller::CronJob(Box::new(value))
+    }
+}
+
+// --- Footprint ---
+
+/// Options for computing the footprint of a controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FootprintOptions {
+    /// Number of nodes a DaemonSet runs on
+    pub node_count: u32,
+    /// Number of jobs a CronJob with the `Allow` concurrency policy is
+    /// expected to run at the same time
+    pub concurrent_jobs: u32,
+    /// Whether to compute the peak footprint during rolling updates
+    pub include_surge: bool,
+}
+
+impl Default for FootprintOptions {
+    fn default() -> Self {
+        Self {
+            node_count: 1,
+            concurrent_jobs: 1,
+            include_surge: false,
+        }
+    }
+}
+
+/// Resources of a number of pods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceTotals {
+    /// Number of pods
+    pub pods: u32,
+    /// Sum of the effective requests of all pods
+    pub requests: ParsedResourceList,
+    /// Sum of the effective limits of all pods
+    pub limits: ParsedResourceList,
+}
+
+/// Resources a controller occupies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footprint {
+    /// Effective requests of a single pod
+    pub pod_requests: ParsedResourceList,
+    /// Effective limits of a single pod
+    pub pod_limits: ParsedResourceList,
+    /// Resources of all pods in the steady state
+    pub steady: ResourceTotals,
+    /// Resources of all pods at the peak of a rolling update, if requested
+    pub peak: Option<ResourceTotals>,
+}
+
+/// Returns the resources a controller occupies, i.e., the effective pod
+/// requests and limits multiplied by the number of pods the controller runs.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::apps::v1::{Deployment, DeploymentSpec};
+/// use k8s_openapi::api::core::v1::{Container, PodSpec, PodTemplateSpec, ResourceRequirements};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::footprint::{workload_footprint, FootprintOptions};
+///
+/// let deployment = Deployment {
+///     spec: Some(DeploymentSpec {
+///         replicas: Some(4),
+///         template: PodTemplateSpec {
+///             spec: Some(PodSpec {
+///                 containers: vec![Container {
+///                     resources: Some(ResourceRequirements {
+///                         requests: Some(BTreeMap::from([
+///                             ("memory".to_string(), Quantity("256Mi".to_string())),
+///                         ])),
+///                         ..Default::default()
+///                     }),
+///                     ..Default::default()
+///                 }],
+///                 ..Default::default()
+///             }),
+///             ..Default::default()
+///         },
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+///
+/// let options = FootprintOptions { include_surge: true, ..Default::default() };
+/// let footprint = workload_footprint(&deployment.into(), &options).unwrap();
+///
+/// assert_eq!(footprint.steady.requests["memory"].to_string(), "1024Mi");
+///
+/// // The default maxSurge of 25% adds a pod during rollouts
+/// let peak = footprint.peak.unwrap();
+/// assert_eq!(peak.pods, 5);
+/// assert_eq!(peak.requests["memory"].to_string(), "1280Mi");
+/// ```
+pub fn workload_footprint(
+    controller: &Controller,
+    options: &FootprintOptions,
+) -> Result<Footprint, FootprintError> {
+    let (name, template, pods, surge) = match controller {
+        Controller::Deployment(deployment) => {
+            let name = object_name("Deployment", deployment.metadata.name.as_deref());
+            let spec = deployment
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+            let replicas = count(spec.replicas.unwrap_or(1));
+
+            let strategy = spec.strategy.as_ref();
+            let surge = if strategy.and_then(|strategy| strategy.type_.as_deref())
+                == Some("Recreate")
+            {
+                0
+            } else {
+                let max_surge = strategy
+                    .and_then(|strategy| strategy.rolling_update.as_ref())
+                    .and_then(|rolling_update| rolling_update.max_surge.clone())
+                    .unwrap_or_else(|| IntOrString::String(DEFAULT_DEPLOYMENT_MAX_SURGE.into()));
+                scaled_value(&max_surge, replicas)?
+            };
+
+            (name, Some(&spec.template), replicas, surge)
+        }
+        Controller::StatefulSet(stateful_set) => {
+            let name = object_name("StatefulSet", stateful_set.metadata.name.as_deref());
+            let spec = stateful_set
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+
+            (
+                name,
+                Some(&spec.template),
+                count(spec.replicas.unwrap_or(1)),
+                0,
+            )
+        }
+        Controller::DaemonSet(daemon_set) => {
+            let name = object_name("DaemonSet", daemon_set.metadata.name.as_deref());
+            let spec = daemon_set
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+
+            let max_surge = spec
+                .update_strategy
+                .as_ref()
+                .filter(|strategy| strategy.type_.as_deref() != Some("OnDelete"))
+                .and_then(|strategy| strategy.rolling_update.as_ref())
+                .and_then(|rolling_update| rolling_update.max_surge.as_ref());
+            let surge = max_surge
+                .map(|max_surge| scaled_value(max_surge, options.node_count))
+                .transpose()?
+                .unwrap_or_default();
+
+            (name, Some(&spec.template), options.node_count, surge)
+        }
+        Controller::ReplicaSet(replica_set) => {
+            let name = object_name("ReplicaSet", replica_set.metadata.name.as_deref());
+            let spec = replica_set
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+
+            (
+                name,
+                spec.template.as_ref(),
+                count(spec.replicas.unwrap_or(1)),
+                0,
+            )
+        }
+        Controller::Job(job) => {
+            let name = object_name("Job", job.metadata.name.as_deref());
+            let spec = job
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+
+            (name, Some(&spec.template), active_pods(spec), 0)
+        }
+        Controller::CronJob(cron_job) => {
+            let name = object_name("CronJob", cron_job.metadata.name.as_deref());
+            let spec = cron_job
+                .spec
+                .as_optional()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+            let job = spec
+                .job_template
+                .spec
+                .as_ref()
+                .ok_or_else(|| FootprintError::MissingPodTemplate(name.clone()))?;
+
+            // Only the Allow policy lets runs overlap
+            let jobs = match spec.concurrency_policy.as_deref() {
+                _ if spec.suspend == Some(true) => 0,
+                Some("Forbid" | "Replace") => 1,
+                _ => options.concurrent_jobs,
+            };
+
+            (
+                name,
+                Some(&job.template),
+                active_pods(job).saturating_mul(jobs),
+                0,
+            )
+        }
+    };
+
+    let spec = template
+        .and_then(|template: &PodTemplateSpec| template.spec.as_ref())
+        .ok_or(FootprintError::MissingPodTemplate(name))?;
+
+    pod_footprint(
+        spec,
+        pods,
+        options.include_surge.then_some(pods.saturating_add(surge)),
+    )
+}
+
+/// Returns the footprint of the given number of pods of a pod spec, with an
+/// optional number of pods at the peak.
+pub fn pod_footprint(
+    spec: &PodSpec,
+    pods: u32,
+    peak_pods: Option<u32>,
+) -> Result<Footprint, FootprintError> {
+    let pod_requests = pod_requests(spec)?;
+    let pod_limits = pod_limits(spec)?;
+
+    let totals = |pods: u32| ResourceTotals {
+        pods,
+        requests: multiply(&pod_requests, pods),
+        limits: multiply(&pod_limits, pods),
+    };
+
+    Ok(Footprint {
+        steady: totals(pods),
+        peak: peak_pods.map(totals),
+        pod_requests,
+        pod_limits,
+    })
+}
+
+fn multiply(list: &ParsedResourceList, factor: u32) -> ParsedResourceList {
+    list.iter()
+        .map(|(name, quantity)| (name.clone(), quantity.clone() * factor))
+        .collect()
+}
+
+/// Returns the number of pods a job runs at the same time.
+fn active_pods(spec: &JobSpec) -> u32 {
+    if spec.suspend == Some(true) {
+        return 0;
+    }
+
+    let parallelism = count(spec.parallelism.unwrap_or(1));
+    match spec.completions {
+        Some(completions) => parallelism.min(count(completions)),
+        None => parallelism,
+    }
+}
+
+/// Resolves an integer or percentage of a total, rounding percentages up.
+fn scaled_value(value: &IntOrString, total: u32) -> Result<u32, FootprintError> {
+    match value {
+        IntOrString::Int(value) => Ok(count(*value)),
+        IntOrString::String(value) => {
+            let percent: u64 = value
+                .strip_suffix('%')
+                .and_then(|percent| percent.parse().ok())
+                .ok_or_else(|| FootprintError::InvalidIntOrPercent(value.clone()))?;
+
+            Ok(percent
+                .saturating_mul(u64::from(total))
+                .div_ceil(100)
+                .try_into()
+                .unwrap_or(u32::MAX))
+        }
+    }
+}
+
+fn count(value: i32) -> u32 {
+    value.max(0).unsigned_abs()
+}
+
+fn object_name(kind: &str, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{kind} {name}"),
+        None => kind.to_owned(),
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::{
+        api::{
+            apps::v1::{
+                DaemonSetSpec, DaemonSetUpdateStrategy, DeploymentSpec, DeploymentStrategy,
+                RollingUpdateDaemonSet, RollingUpdateDeployment, StatefulSetSpec,
+            },
+            batch::v1::{CronJobSpec, JobTemplateSpec},
+            core::v1::{Container, ResourceRequirements},
+        },
+        apimachinery::pkg::api::resource::Quantity,
+    };
+
+    use super::*;
+
+    fn template() -> PodTemplateSpec {
+        let list = |cpu: &str, memory: &str| {
+            Some(BTreeMap::from([
+                ("cpu".to_owned(), Quantity(cpu.to_owned())),
+                ("memory".to_owned(), Quantity(memory.to_owned())),
+            ]))
+        };
+
+        PodTemplateSpec {
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_owned(),
+                    resources: Some(ResourceRequirements {
+                        requests: list("250m", "512Mi"),
+                        limits: list("1", "1Gi"),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn job_spec(parallelism: Option<i32>, completions: Option<i32>) -> JobSpec {
+        JobSpec {
+            parallelism,
+            completions,
+            template: template(),
+            ..Default::default()
+        }
+    }
+
+    fn with_surge() -> FootprintOptions {
+        FootprintOptions {
+            include_surge: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deployment_max_surge() {
+        let deployment = |max_surge: IntOrString| Deployment {
+            spec: Some(DeploymentSpec {
+                replicas: Some(10),
+                strategy: Some(DeploymentStrategy {
+                    rolling_update: Some(RollingUpdateDeployment {
+                        max_surge: Some(max_surge),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                template: template(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let footprint =
+            workload_footprint(&deployment(IntOrString::Int(2)).into(), &with_surge()).unwrap();
+        assert_eq!(footprint.steady.requests["cpu"].to_string(), "2500m");
+        assert_eq!(footprint.steady.limits["memory"].to_string(), "10Gi");
+        assert_eq!(footprint.peak.unwrap().pods, 12);
+
+        let footprint = workload_footprint(
+            &deployment(IntOrString::String("15%".to_owned())).into(),
+            &with_surge(),
+        )
+        .unwrap();
+        assert_eq!(footprint.peak.unwrap().requests["cpu"].to_string(), "3000m");
+
+        // Huge percentages saturate instead of overflowing
+        let footprint = workload_footprint(
+            &deployment(IntOrString::String(format!("{}%", u64::MAX))).into(),
+            &with_surge(),
+        )
+        .unwrap();
+        assert_eq!(footprint.peak.unwrap().pods, u32::MAX);
+
+        let result = workload_footprint(
+            &deployment(IntOrString::String("15".to_owned())).into(),
+            &with_surge(),
+        );
+        assert!(matches!(
+            result,
+            Err(FootprintError::InvalidIntOrPercent(_))
+        ));
+    }
+
+    #[test]
+    fn test_recreate_deployment_has_no_surge() {
+        let deployment = Deployment {
+            spec: Some(DeploymentSpec {
+                strategy: Some(DeploymentStrategy {
+                    type_: Some("Recreate".to_owned()),
+                    ..Default::default()
+                }),
+                template: template(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let footprint = workload_footprint(&deployment.into(), &with_surge()).unwrap();
+
+        assert_eq!(footprint.steady.pods, 1);
+        assert_eq!(footprint.peak.unwrap().pods, 1);
+    }
+
+    #[test]
+    fn test_stateful_set_and_daemon_set() {
+        let stateful_set = StatefulSet {
+            spec: Some(StatefulSetSpec {
+                replicas: Some(3),
+                template: template(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let daemon_set = DaemonSet {
+            spec: Some(DaemonSetSpec {
+                template: template(),
+                update_strategy: Some(DaemonSetUpdateStrategy {
+                    rolling_update: Some(RollingUpdateDaemonSet {
+                        max_surge: Some(IntOrString::String("10%".to_owned())),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let options = FootprintOptions {
+            node_count: 12,
+            ..with_surge()
+        };
+
+        let footprint = workload_footprint(&stateful_set.into(), &options).unwrap();
+        assert_eq!(footprint.steady.requests["memory"].to_string(), "1536Mi");
+        assert_eq!(footprint.peak.unwrap().pods, 3);
+
+        let footprint = workload_footprint(&daemon_set.into(), &options).unwrap();
+        assert_eq!(footprint.steady.pods, 12);
+        assert_eq!(footprint.steady.limits["cpu"].to_string(), "12");
+        assert_eq!(footprint.peak.unwrap().pods, 14);
+    }
+
+    #[test]
+    fn test_job_parallelism() {
+        let footprint = |spec: JobSpec| {
+            let job = Job {
+                spec: Some(spec),
+                ..Default::default()
+            };
+            workload_footprint(&job.into(), &FootprintOptions::default()).unwrap()
+        };
+
+        assert_eq!(footprint(job_spec(None, None)).steady.pods, 1);
+        assert_eq!(footprint(job_spec(Some(5), None)).steady.pods, 5);
+        assert_eq!(footprint(job_spec(Some(5), Some(2))).steady.pods, 2);
+        assert_eq!(
+            footprint(JobSpec {
+                suspend: Some(true),
+                ..job_spec(Some(5), None)
+            })
+            .steady
+            .requests["cpu"]
+                .to_string(),
+            "0m"
+        );
+    }
+
+    #[test]
+    // The spec is optional before k8s-openapi 0.26
+    #[allow(clippy::useless_conversion)]
+    fn test_cron_job_concurrency_policy() {
+        let cron_job = |policy: &str| {
+            Controller::from(CronJob {
+                spec: CronJobSpec {
+                    concurrency_policy: Some(policy.to_owned()),
+                    job_template: JobTemplateSpec {
+                        spec: Some(job_spec(Some(2), None)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+                .into(),
+                ..Default::default()
+            })
+        };
+        let options = FootprintOptions {
+            concurrent_jobs: 3,
+            ..Default::default()
+        };
+
+        let footprint = workload_footprint(&cron_job("Allow"), &options).unwrap();
+        assert_eq!(footprint.steady.pods, 6);
+
+        let footprint = workload_footprint(&cron_job("Forbid"), &options).unwrap();
+        assert_eq!(footprint.steady.pods, 2);
+        assert_eq!(footprint.peak, None);
+    }
+
+    #[test]
+    fn test_missing_pod_template() {
+        let replica_set = ReplicaSet::default();
+        let result = workload_footprint(&replica_set.into(), &FootprintOptions::default());
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "ReplicaSet has no pod template"
+        );
+    }
+}
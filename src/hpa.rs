@@ -0,0 +1,1696 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::{
+    api::{
+        autoscaling::v2::{HPAScalingPolicy, HPAScalingRules, HorizontalPodAutoscaler, MetricSpec},
+        core::v1::Pod,
+    },
+    apimachinery::pkg::api::resource::Quantity,
+};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::{
+    compat::{to_utc, AsOptional},
+    resources::{container_requests, quantity_for, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Default tolerance within which the usage ratio does not trigger scaling
+pub const DEFAULT_TOLERANCE: f64 = 0.1;
+/// Default period after start in which CPU samples of pods may be skipped
+pub const DEFAULT_CPU_INITIALIZATION_PERIOD: Duration = Duration::from_secs(5 * 60);
+/// Default period after start in which pods that never became ready are
+/// treated as unready
+pub const DEFAULT_INITIAL_READINESS_DELAY: Duration = Duration::from_secs(30);
+/// Default window of past recommendations considered before scaling down
+pub const DEFAULT_DOWNSCALE_STABILIZATION_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// Default target CPU utilization of autoscalers without metrics
+pub const DEFAULT_CPU_UTILIZATION: i32 = 80;
+
+const CPU: &str = "cpu";
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum HpaError {
+    /// The autoscaler has no spec
+    #[error("horizontal pod autoscaler has no spec")]
+    MissingSpec,
+
+    /// No pods are targeted by the autoscaler
+    #[error("no pods returned by selector while calculating replica count")]
+    NoPods,
+
+    /// No usable metrics are available for the targeted pods
+    #[error("did not receive metrics for targeted pods (pods might be unready)")]
+    NoMetrics,
+
+    /// No metrics are available for the metric
+    #[error("no metrics returned for {0}")]
+    MissingMetric(String),
+
+    /// The metrics do not belong to any targeted pod
+    #[error("no metrics returned matched known pods")]
+    UnknownPods,
+
+    /// A container of a targeted pod does not request the resource
+    #[error("missing request for {resource} in container {container} of Pod {pod}")]
+    MissingRequest {
+        resource: String,
+        container: String,
+        pod: String,
+    },
+
+    /// The metric spec is not supported or incomplete
+    #[error("invalid metric source: {0}")]
+    InvalidMetricSource(String),
+
+    /// Metrics could not be evaluated and scaling is not safe
+    #[error("invalid metrics ({invalid} invalid out of {total}), first error is: {first}")]
+    InvalidMetrics {
+        invalid: usize,
+        total: usize,
+        first: Box<HpaError>,
+    },
+
+    /// A quantity could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Inputs ---
+
+/// Options of the horizontal pod autoscaler controller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpaOptions {
+    /// Tolerance within which the usage ratio does not trigger scaling,
+    /// i.e., `--horizontal-pod-autoscaler-tolerance`
+    pub tolerance: f64,
+    /// `--horizontal-pod-autoscaler-cpu-initialization-period`
+    pub cpu_initialization_period: Duration,
+    /// `--horizontal-pod-autoscaler-initial-readiness-delay`
+    pub initial_readiness_delay: Duration,
+    /// `--horizontal-pod-autoscaler-downscale-stabilization`
+    pub downscale_stabilization_window: Duration,
+}
+
+impl Default for HpaOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_TOLERANCE,
+            cpu_initialization_period: DEFAULT_CPU_INITIALIZATION_PERIOD,
+            initial_readiness_delay: DEFAULT_INITIAL_READINESS_DELAY,
+            downscale_stabilization_window: DEFAULT_DOWNSCALE_STABILIZATION_WINDOW,
+        }
+    }
+}
+
+/// Resource usage of a pod as reported by the resource metrics API.
+#[derive(Debug, Clone)]
+pub struct PodUsage {
+    /// Time the usage was sampled at
+    pub timestamp: DateTime<Utc>,
+    /// Window the usage was averaged over
+    pub window: Duration,
+    /// Usage by container name
+    pub containers: BTreeMap<String, ParsedResourceList>,
+}
+
+/// Metrics observed for the targets of an autoscaler.
+#[derive(Debug, Clone, Default)]
+pub struct ObservedMetrics {
+    /// Resource usage by pod name
+    pub resources: BTreeMap<String, PodUsage>,
+    /// Custom per-pod metrics by metric name and pod name
+    pub pods: BTreeMap<String, BTreeMap<String, ParsedQuantity>>,
+    /// Object metrics by metric name
+    pub objects: BTreeMap<String, ParsedQuantity>,
+    /// External metrics by metric name, summed over all series
+    pub external: BTreeMap<String, Vec<ParsedQuantity>>,
+}
+
+// --- Outputs ---
+
+/// Replica count proposed for a single metric.
+#[derive(Debug, Clone)]
+pub struct MetricRecommendation {
+    /// Name of the metric, e.g., `cpu` or `http_requests`
+    pub metric: String,
+    /// Proposed replica count
+    pub replicas: i32,
+    /// Current average utilization in percent of the requests, for
+    /// utilization targets
+    pub utilization: Option<i32>,
+    /// Current value of the metric, averaged per pod for average targets
+    pub value: ParsedQuantity,
+}
+
+/// Result of one reconciliation of an autoscaler.
+#[derive(Debug, Clone)]
+pub struct ScaleRecommendation {
+    /// Replica count before scaling
+    pub current_replicas: i32,
+    /// Proposals of all metrics that could be evaluated
+    pub metrics: Vec<MetricRecommendation>,
+    /// Largest proposal of all metrics, before stabilization and scaling
+    /// policies
+    pub metric_replicas: Option<i32>,
+    /// Replica count after stabilization and scaling policies
+    pub desired_replicas: i32,
+}
+
+impl ScaleRecommendation {
+    /// Returns whether the target is scaled.
+    pub fn rescale(&self) -> bool {
+        self.desired_replicas != self.current_replicas
+    }
+}
+
+// --- Autoscaler ---
+
+/// Replica calculation of the horizontal pod autoscaler controller for a
+/// single autoscaler, including the history of recommendations and scale
+/// events used for stabilization and scaling policies.
+///
+/// The controller rounds ratios of milli-values computed in `float64`, which
+/// is mirrored to reproduce its replica counts exactly.
+///
+/// ```rust
+/// use std::{collections::BTreeMap, time::Duration};
+///
+/// use k8s_openapi::api::autoscaling::v2::{
+///     HorizontalPodAutoscaler, HorizontalPodAutoscalerSpec, MetricSpec, MetricTarget,
+///     ResourceMetricSource,
+/// };
+/// use k8s_openapi::api::core::v1::{Container, Pod, PodSpec, PodStatus};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+/// use chrono::DateTime;
+/// use kube_quantity::hpa::{Autoscaler, HpaOptions, ObservedMetrics, PodUsage};
+///
+/// let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+/// let hpa = HorizontalPodAutoscaler {
+///     spec: HorizontalPodAutoscalerSpec {
+///         max_replicas: 10,
+///         metrics: Some(vec![MetricSpec {
+///             type_: "Resource".to_string(),
+///             resource: Some(ResourceMetricSource {
+///                 name: "memory".to_string(),
+///                 target: MetricTarget {
+///                     type_: "AverageValue".to_string(),
+///                     average_value: Some(Quantity("512Mi".to_string())),
+///                     ..Default::default()
+///                 },
+///             }),
+///             ..Default::default()
+///         }]),
+///         ..Default::default()
+///     }.into(),
+///     ..Default::default()
+/// };
+/// let pod = |name: &str| Pod {
+///     metadata: ObjectMeta { name: Some(name.to_string()), ..Default::default() },
+///     spec: Some(PodSpec { containers: vec![Container::default()], ..Default::default() }),
+///     status: Some(PodStatus { phase: Some("Running".to_string()), ..Default::default() }),
+/// };
+/// let usage = |memory: &str| PodUsage {
+///     timestamp: now,
+///     window: Duration::from_secs(30),
+///     containers: BTreeMap::from([(
+///         "app".to_string(),
+///         BTreeMap::from([("memory".to_string(), memory.try_into().unwrap())]),
+///     )]),
+/// };
+/// let metrics = ObservedMetrics {
+///     resources: BTreeMap::from([("a".to_string(), usage("800Mi")), ("b".to_string(), usage("700Mi"))]),
+///     ..Default::default()
+/// };
+///
+/// let mut autoscaler = Autoscaler::new(HpaOptions::default());
+/// let result = autoscaler.reconcile(&hpa, 2, &[pod("a"), pod("b")], &metrics, now).unwrap();
+///
+/// // An average of 750Mi is 1.46 times the target
+/// assert_eq!(result.metrics[0].value.to_canonical_string(), "750Mi");
+/// assert_eq!(result.desired_replicas, 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Autoscaler {
+    options: HpaOptions,
+    recommendations: Vec<(DateTime<Utc>, i32)>,
+    scale_up_events: Vec<(DateTime<Utc>, i32)>,
+    scale_down_events: Vec<(DateTime<Utc>, i32)>,
+}
+
+impl Autoscaler {
+    /// Creates an autoscaler without history.
+    pub fn new(options: HpaOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Computes the desired replica count of the scale target at `now` and
+    /// records it, like one sync of the controller. The target is assumed to
+    /// be scaled to the desired replica count.
+    pub fn reconcile(
+        &mut self,
+        hpa: &HorizontalPodAutoscaler,
+        current_replicas: i32,
+        pods: &[Pod],
+        metrics: &ObservedMetrics,
+        now: DateTime<Utc>,
+    ) -> Result<ScaleRecommendation, HpaError> {
+        let spec = hpa.spec.as_optional().ok_or(HpaError::MissingSpec)?;
+        let min_replicas = spec.min_replicas.unwrap_or(1);
+
+        let mut recommendation = ScaleRecommendation {
+            current_replicas,
+            metrics: Vec::new(),
+            metric_replicas: None,
+            desired_replicas: current_replicas,
+        };
+
+        if current_replicas == 0 && min_replicas != 0 {
+            // Autoscaling is disabled for the target
+            recommendation.desired_replicas = 0;
+            return Ok(recommendation);
+        } else if current_replicas > spec.max_replicas {
+            recommendation.desired_replicas = spec.max_replicas;
+        } else if current_replicas < min_replicas {
+            recommendation.desired_replicas = min_replicas;
+        } else {
+            let default_metrics = [default_metric()];
+            let metric_specs = match spec.metrics.as_deref() {
+                Some(metric_specs) if !metric_specs.is_empty() => metric_specs,
+                _ => &default_metrics,
+            };
+
+            let mut metric_replicas = 0;
+            let mut invalid = Vec::new();
+            for metric_spec in metric_specs {
+                let context = MetricContext {
+                    options: &self.options,
+                    current_replicas,
+                    pods,
+                    metrics,
+                    now,
+                };
+                match context.replicas_for_metric(metric_spec) {
+                    Ok(proposal) => {
+                        if metric_replicas == 0 || proposal.replicas > metric_replicas {
+                            metric_replicas = proposal.replicas;
+                        }
+                        recommendation.metrics.push(proposal);
+                    }
+                    Err(err) => invalid.push(err),
+                }
+            }
+
+            // Metrics that cannot be evaluated must not cause a scale down
+            if !invalid.is_empty()
+                && (invalid.len() >= metric_specs.len() || metric_replicas < current_replicas)
+            {
+                return Err(HpaError::InvalidMetrics {
+                    invalid: invalid.len(),
+                    total: metric_specs.len(),
+                    first: Box::new(invalid.swap_remove(0)),
+                });
+            }
+
+            recommendation.metric_replicas = Some(metric_replicas);
+            let desired = metric_replicas.max(0);
+            recommendation.desired_replicas = match &spec.behavior {
+                None => self.normalize(
+                    now,
+                    current_replicas,
+                    desired,
+                    min_replicas,
+                    spec.max_replicas,
+                ),
+                Some(behavior) => {
+                    let scale_up = scaling_rules(behavior.scale_up.as_ref(), true, &self.options);
+                    let scale_down =
+                        scaling_rules(behavior.scale_down.as_ref(), false, &self.options);
+                    let desired = self.normalize_with_behaviors(
+                        now,
+                        current_replicas,
+                        desired,
+                        min_replicas,
+                        spec.max_replicas,
+                        &scale_up,
+                        &scale_down,
+                    );
+                    self.record_scale_event(now, current_replicas, desired, &scale_up, &scale_down);
+                    desired
+                }
+            };
+        }
+
+        Ok(recommendation)
+    }
+
+    /// Mirrors `normalizeDesiredReplicas` of autoscalers without behaviors.
+    fn normalize(
+        &mut self,
+        now: DateTime<Utc>,
+        current_replicas: i32,
+        desired_replicas: i32,
+        min_replicas: i32,
+        max_replicas: i32,
+    ) -> i32 {
+        let cutoff = now - time_delta(self.options.downscale_stabilization_window);
+
+        let mut stabilized = desired_replicas;
+        let mut old_sample = None;
+        for (index, (timestamp, recommendation)) in self.recommendations.iter().enumerate() {
+            if *timestamp < cutoff {
+                old_sample = Some(index);
+            } else {
+                stabilized = stabilized.max(*recommendation);
+            }
+        }
+        self.record_recommendation(old_sample, now, desired_replicas);
+
+        // Guards against bogus usage reports causing a rapid scale up
+        let scale_up_limit = (2.0 * f64::from(current_replicas)).max(4.0) as i32;
+        let max_allowed = max_replicas.min(scale_up_limit);
+
+        if stabilized < min_replicas {
+            min_replicas
+        } else if stabilized > max_allowed {
+            max_allowed
+        } else {
+            stabilized
+        }
+    }
+
+    /// Mirrors `normalizeDesiredReplicasWithBehaviors`.
+    #[allow(clippy::too_many_arguments)]
+    fn normalize_with_behaviors(
+        &mut self,
+        now: DateTime<Utc>,
+        current_replicas: i32,
+        desired_replicas: i32,
+        min_replicas: i32,
+        max_replicas: i32,
+        scale_up: &ScalingRules,
+        scale_down: &ScalingRules,
+    ) -> i32 {
+        // Stabilization
+        let up_cutoff = now - TimeDelta::seconds(scale_up.stabilization_window_seconds.into());
+        let down_cutoff = now - TimeDelta::seconds(scale_down.stabilization_window_seconds.into());
+
+        let mut up_recommendation = desired_replicas;
+        let mut down_recommendation = desired_replicas;
+        let mut old_sample = None;
+        for (index, (timestamp, recommendation)) in self.recommendations.iter().enumerate() {
+            if *timestamp > up_cutoff {
+                up_recommendation = up_recommendation.min(*recommendation);
+            }
+            if *timestamp > down_cutoff {
+                down_recommendation = down_recommendation.max(*recommendation);
+            }
+            if *timestamp < up_cutoff && *timestamp < down_cutoff {
+                old_sample = Some(index);
+            }
+        }
+        self.record_recommendation(old_sample, now, desired_replicas);
+
+        let desired_replicas = current_replicas
+            .max(up_recommendation)
+            .min(down_recommendation);
+
+        // Scaling policies
+        if desired_replicas > current_replicas {
+            let scale_up_limit = self
+                .scale_limit(now, current_replicas, scale_up, true)
+                .max(current_replicas);
+            desired_replicas.min(max_replicas.min(scale_up_limit))
+        } else if desired_replicas < current_replicas {
+            let scale_down_limit = self
+                .scale_limit(now, current_replicas, scale_down, false)
+                .min(current_replicas);
+            desired_replicas.max(min_replicas.max(scale_down_limit))
+        } else {
+            desired_replicas
+        }
+    }
+
+    /// Returns the replica count the policies allow scaling to, i.e.,
+    /// `calculateScaleUpLimitWithScalingRules` and
+    /// `calculateScaleDownLimitWithBehaviors`.
+    fn scale_limit(
+        &self,
+        now: DateTime<Utc>,
+        current_replicas: i32,
+        rules: &ScalingRules,
+        scale_up: bool,
+    ) -> i32 {
+        let select_min = match rules.select_policy.as_str() {
+            "Disabled" => return current_replicas,
+            "Min" => scale_up,
+            _ => !scale_up,
+        };
+
+        let mut result = if select_min { i32::MAX } else { i32::MIN };
+        for policy in &rules.policies {
+            let cutoff = now - TimeDelta::seconds(policy.period_seconds.into());
+            let added = replicas_changed_since(&self.scale_up_events, cutoff);
+            let deleted = replicas_changed_since(&self.scale_down_events, cutoff);
+            let period_start_replicas = current_replicas - added + deleted;
+
+            let proposed = match (policy.type_.as_str(), scale_up) {
+                ("Pods", true) => period_start_replicas + policy.value,
+                ("Pods", false) => period_start_replicas - policy.value,
+                // Rounded up, as the change might never add a replica otherwise
+                (_, true) => (f64::from(period_start_replicas)
+                    * (1.0 + f64::from(policy.value) / 100.0))
+                    .ceil() as i32,
+                (_, false) => {
+                    (f64::from(period_start_replicas) * (1.0 - f64::from(policy.value) / 100.0))
+                        as i32
+                }
+            };
+
+            result = if select_min {
+                result.min(proposed)
+            } else {
+                result.max(proposed)
+            };
+        }
+
+        result
+    }
+
+    fn record_recommendation(
+        &mut self,
+        old_sample: Option<usize>,
+        now: DateTime<Utc>,
+        recommendation: i32,
+    ) {
+        match old_sample {
+            Some(index) => self.recommendations[index] = (now, recommendation),
+            None => self.recommendations.push((now, recommendation)),
+        }
+    }
+
+    /// Records a scale event and forgets those outside of every policy
+    /// period.
+    fn record_scale_event(
+        &mut self,
+        now: DateTime<Utc>,
+        current_replicas: i32,
+        desired_replicas: i32,
+        scale_up: &ScalingRules,
+        scale_down: &ScalingRules,
+    ) {
+        let (events, rules) = match desired_replicas.cmp(&current_replicas) {
+            std::cmp::Ordering::Greater => (&mut self.scale_up_events, scale_up),
+            std::cmp::Ordering::Less => (&mut self.scale_down_events, scale_down),
+            std::cmp::Ordering::Equal => return,
+        };
+
+        let longest_period = rules
+            .policies
+            .iter()
+            .map(|policy| policy.period_seconds)
+            .max()
+            .unwrap_or_default();
+        let cutoff = now - TimeDelta::seconds(longest_period.into());
+        events.retain(|(timestamp, _)| *timestamp > cutoff);
+        events.push((now, (desired_replicas - current_replicas).abs()));
+    }
+}
+
+/// Scaling rules with the defaults of the API server applied.
+struct ScalingRules {
+    stabilization_window_seconds: i32,
+    select_policy: String,
+    policies: Vec<HPAScalingPolicy>,
+}
+
+fn scaling_rules(
+    rules: Option<&HPAScalingRules>,
+    scale_up: bool,
+    options: &HpaOptions,
+) -> ScalingRules {
+    let policy = |type_: &str, value| HPAScalingPolicy {
+        type_: type_.to_owned(),
+        value,
+        period_seconds: 15,
+    };
+    let (default_window, default_policies) = if scale_up {
+        (0, vec![policy("Pods", 4), policy("Percent", 100)])
+    } else {
+        (
+            i32::try_from(options.downscale_stabilization_window.as_secs()).unwrap_or(i32::MAX),
+            vec![policy("Percent", 100)],
+        )
+    };
+
+    ScalingRules {
+        stabilization_window_seconds: rules
+            .and_then(|rules| rules.stabilization_window_seconds)
+            .unwrap_or(default_window),
+        select_policy: rules
+            .and_then(|rules| rules.select_policy.clone())
+            .unwrap_or_else(|| "Max".to_owned()),
+        policies: rules
+            .and_then(|rules| rules.policies.clone())
+            .unwrap_or(default_policies),
+    }
+}
+
+fn replicas_changed_since(events: &[(DateTime<Utc>, i32)], cutoff: DateTime<Utc>) -> i32 {
+    events
+        .iter()
+        .filter(|(timestamp, _)| *timestamp > cutoff)
+        .map(|(_, change)| change)
+        .sum()
+}
+
+fn default_metric() -> MetricSpec {
+    use k8s_openapi::api::autoscaling::v2::{MetricTarget, ResourceMetricSource};
+
+    MetricSpec {
+        type_: "Resource".to_owned(),
+        resource: Some(ResourceMetricSource {
+            name: CPU.to_owned(),
+            target: MetricTarget {
+                type_: "Utilization".to_owned(),
+                average_utilization: Some(DEFAULT_CPU_UTILIZATION),
+                ..Default::default()
+            },
+        }),
+        ..Default::default()
+    }
+}
+
+// --- Replica calculation ---
+
+/// Target of a metric that is not measured per pod.
+enum TotalTarget<'a> {
+    /// The total value across all pods
+    Value(&'a Quantity),
+    /// The total value divided by the replica count
+    AverageValue(&'a Quantity),
+}
+
+/// Value of a metric for a pod, in milli-units.
+#[derive(Debug, Clone)]
+struct PodMetric {
+    timestamp: DateTime<Utc>,
+    window: Duration,
+    value: i64,
+}
+
+type PodMetrics = BTreeMap<String, PodMetric>;
+
+/// Pods grouped by how their metrics are treated.
+#[derive(Debug, Default)]
+struct PodGroups {
+    ready: usize,
+    unready: BTreeSet<String>,
+    missing: BTreeSet<String>,
+    ignored: BTreeSet<String>,
+}
+
+struct MetricContext<'a> {
+    options: &'a HpaOptions,
+    current_replicas: i32,
+    pods: &'a [Pod],
+    metrics: &'a ObservedMetrics,
+    now: DateTime<Utc>,
+}
+
+impl MetricContext<'_> {
+    /// Mirrors `computeReplicasForMetric`.
+    fn replicas_for_metric(&self, spec: &MetricSpec) -> Result<MetricRecommendation, HpaError> {
+        match spec.type_.as_str() {
+            "Resource" | "ContainerResource" => {
+                let (resource, container, target) = match (&spec.resource, &spec.container_resource)
+                {
+                    (Some(source), _) if spec.type_ == "Resource" => {
+                        (&source.name, None, &source.target)
+                    }
+                    (_, Some(source)) if spec.type_ == "ContainerResource" => (
+                        &source.name,
+                        Some(source.container.as_str()),
+                        &source.target,
+                    ),
+                    _ => return Err(missing_source(&spec.type_)),
+                };
+                let metrics = self.resource_metrics(resource, container)?;
+
+                if let Some(average_value) = &target.average_value {
+                    let (replicas, usage) =
+                        self.plain_metric_replicas(metrics, milli_value(average_value)?, resource)?;
+                    return Ok(MetricRecommendation {
+                        metric: resource.clone(),
+                        replicas,
+                        utilization: None,
+                        value: from_milli(resource, usage),
+                    });
+                }
+
+                let target_utilization = target.average_utilization.ok_or_else(|| {
+                    HpaError::InvalidMetricSource(format!(
+                        "neither an average utilization target nor an average value target \
+                         was set for resource {resource}"
+                    ))
+                })?;
+                let (replicas, utilization, usage) =
+                    self.resource_replicas(metrics, target_utilization, resource, container)?;
+
+                Ok(MetricRecommendation {
+                    metric: resource.clone(),
+                    replicas,
+                    utilization: Some(utilization),
+                    value: from_milli(resource, usage),
+                })
+            }
+            "Pods" => {
+                let source = spec.pods.as_ref().ok_or_else(|| missing_source("Pods"))?;
+                let name = &source.metric.name;
+                let values = self
+                    .metrics
+                    .pods
+                    .get(name)
+                    .filter(|values| !values.is_empty())
+                    .ok_or_else(|| HpaError::MissingMetric(name.clone()))?;
+                let metrics = values
+                    .iter()
+                    .map(|(pod, value)| {
+                        let metric = PodMetric {
+                            timestamp: self.now,
+                            window: Duration::ZERO,
+                            value: value.to_milli_i64(),
+                        };
+                        (pod.clone(), metric)
+                    })
+                    .collect();
+                let target = source.target.average_value.as_ref().ok_or_else(|| {
+                    HpaError::InvalidMetricSource("no average value target was set".to_owned())
+                })?;
+
+                let (replicas, usage) =
+                    self.plain_metric_replicas(metrics, milli_value(target)?, "")?;
+                Ok(MetricRecommendation {
+                    metric: name.clone(),
+                    replicas,
+                    utilization: None,
+                    value: from_milli(name, usage),
+                })
+            }
+            "Object" => {
+                let source = spec
+                    .object
+                    .as_ref()
+                    .ok_or_else(|| missing_source("Object"))?;
+                let name = &source.metric.name;
+                let usage = self
+                    .metrics
+                    .objects
+                    .get(name)
+                    .ok_or_else(|| HpaError::MissingMetric(name.clone()))?
+                    .to_milli_i64();
+
+                let target = match source.target.type_.as_str() {
+                    "Value" => source.target.value.as_ref().map(TotalTarget::Value),
+                    "AverageValue" => source
+                        .target
+                        .average_value
+                        .as_ref()
+                        .map(TotalTarget::AverageValue),
+                    _ => None,
+                };
+                self.total_metric_replicas(name, usage, target, "object")
+            }
+            "External" => {
+                let source = spec
+                    .external
+                    .as_ref()
+                    .ok_or_else(|| missing_source("External"))?;
+                let name = &source.metric.name;
+                let usage = self
+                    .metrics
+                    .external
+                    .get(name)
+                    .filter(|values| !values.is_empty())
+                    .ok_or_else(|| HpaError::MissingMetric(name.clone()))?
+                    .iter()
+                    .map(ParsedQuantity::to_milli_i64)
+                    .fold(0_i64, i64::saturating_add);
+
+                // Per pod targets take precedence for external metrics
+                let target = source
+                    .target
+                    .average_value
+                    .as_ref()
+                    .map(TotalTarget::AverageValue)
+                    .or(source.target.value.as_ref().map(TotalTarget::Value));
+                self.total_metric_replicas(name, usage, target, "external")
+            }
+            type_ => Err(HpaError::InvalidMetricSource(format!(
+                "unknown metric source type {type_:?}"
+            ))),
+        }
+    }
+
+    /// Mirrors `GetObjectMetricReplicas`, `GetObjectPerPodMetricReplicas`,
+    /// `GetExternalMetricReplicas` and `GetExternalPerPodMetricReplicas`.
+    fn total_metric_replicas(
+        &self,
+        name: &str,
+        usage: i64,
+        target: Option<TotalTarget>,
+        kind: &str,
+    ) -> Result<MetricRecommendation, HpaError> {
+        let (replicas, usage) = match target {
+            Some(TotalTarget::Value(target)) => {
+                let usage_ratio = usage as f64 / milli_value(target)? as f64;
+                (self.usage_ratio_replicas(usage_ratio)?, usage)
+            }
+            Some(TotalTarget::AverageValue(target)) => {
+                let target = milli_value(target)? as f64;
+                let usage_ratio = usage as f64 / (target * f64::from(self.current_replicas));
+
+                let mut replicas = self.current_replicas;
+                if (1.0 - usage_ratio).abs() > self.options.tolerance {
+                    replicas = (usage as f64 / target).ceil() as i32;
+                }
+                (
+                    replicas,
+                    (usage as f64 / f64::from(self.current_replicas)).ceil() as i64,
+                )
+            }
+            None => {
+                return Err(HpaError::InvalidMetricSource(format!(
+                    "neither a value target nor an average value target was set \
+                     for {kind} metric {name}"
+                )))
+            }
+        };
+
+        Ok(MetricRecommendation {
+            metric: name.to_owned(),
+            replicas,
+            utilization: None,
+            value: from_milli(name, usage),
+        })
+    }
+
+    /// Mirrors `getUsageRatioReplicaCount`.
+    fn usage_ratio_replicas(&self, usage_ratio: f64) -> Result<i32, HpaError> {
+        if self.current_replicas == 0 {
+            // Scale to zero or n pods depending on the usage ratio
+            return Ok(usage_ratio.ceil() as i32);
+        }
+        if (1.0 - usage_ratio).abs() <= self.options.tolerance {
+            return Ok(self.current_replicas);
+        }
+
+        if self.pods.is_empty() {
+            return Err(HpaError::NoPods);
+        }
+        let ready = self
+            .pods
+            .iter()
+            .filter(|pod| {
+                phase(pod) == "Running"
+                    && ready_condition(pod).is_some_and(|(status, _)| status == "True")
+            })
+            .count();
+
+        Ok((usage_ratio * ready as f64).ceil() as i32)
+    }
+
+    /// Mirrors `GetResourceReplicas`.
+    fn resource_replicas(
+        &self,
+        mut metrics: PodMetrics,
+        target_utilization: i32,
+        resource: &str,
+        container: Option<&str>,
+    ) -> Result<(i32, i32, i64), HpaError> {
+        if self.pods.is_empty() {
+            return Err(HpaError::NoPods);
+        }
+
+        let groups = self.group_pods(&metrics, resource);
+        metrics.retain(|pod, _| !groups.ignored.contains(pod) && !groups.unready.contains(pod));
+        if metrics.is_empty() {
+            return Err(HpaError::NoMetrics);
+        }
+
+        let requests = self.pod_requests(resource, container)?;
+        let (usage_ratio, utilization, raw_usage) =
+            utilization_ratio(&metrics, &requests, target_utilization)?;
+
+        let scale_up_with_unready = !groups.unready.is_empty() && usage_ratio > 1.0;
+        if !scale_up_with_unready && groups.missing.is_empty() {
+            if (1.0 - usage_ratio).abs() <= self.options.tolerance {
+                return Ok((self.current_replicas, utilization, raw_usage));
+            }
+
+            return Ok((
+                (usage_ratio * groups.ready as f64).ceil() as i32,
+                utilization,
+                raw_usage,
+            ));
+        }
+
+        if !groups.missing.is_empty() {
+            // On a scale down, missing pods are assumed to use their request
+            // or the target, whichever is higher. On a scale up, they are
+            // assumed to use nothing. At a ratio of exactly one, they are
+            // left out, which keeps the current replica count.
+            let fallback_utilization = i64::from(target_utilization.max(100));
+            for pod in &groups.missing {
+                let value = if usage_ratio < 1.0 {
+                    requests.get(pod).copied().unwrap_or_default() * fallback_utilization / 100
+                } else if usage_ratio > 1.0 {
+                    0
+                } else {
+                    continue;
+                };
+                metrics.insert(pod.clone(), self.fallback_metric(value));
+            }
+        }
+        if scale_up_with_unready {
+            for pod in &groups.unready {
+                metrics.insert(pod.clone(), self.fallback_metric(0));
+            }
+        }
+
+        let (new_usage_ratio, _, _) = utilization_ratio(&metrics, &requests, target_utilization)?;
+        let replicas = self.resolve_replicas(usage_ratio, new_usage_ratio, metrics.len());
+
+        Ok((replicas, utilization, raw_usage))
+    }
+
+    /// Mirrors `calcPlainMetricReplicas`, returning the replica count and the
+    /// average usage in milli-units.
+    fn plain_metric_replicas(
+        &self,
+        mut metrics: PodMetrics,
+        target_usage: i64,
+        resource: &str,
+    ) -> Result<(i32, i64), HpaError> {
+        if self.pods.is_empty() {
+            return Err(HpaError::NoPods);
+        }
+
+        let groups = self.group_pods(&metrics, resource);
+        metrics.retain(|pod, _| !groups.ignored.contains(pod) && !groups.unready.contains(pod));
+        if metrics.is_empty() {
+            return Err(HpaError::NoMetrics);
+        }
+
+        let (usage_ratio, usage) = metric_usage_ratio(&metrics, target_usage);
+
+        let scale_up_with_unready = !groups.unready.is_empty() && usage_ratio > 1.0;
+        if !scale_up_with_unready && groups.missing.is_empty() {
+            if (1.0 - usage_ratio).abs() <= self.options.tolerance {
+                return Ok((self.current_replicas, usage));
+            }
+
+            return Ok(((usage_ratio * groups.ready as f64).ceil() as i32, usage));
+        }
+
+        if !groups.missing.is_empty() {
+            // Missing pods are assumed to be at the target on a scale down
+            // and to use nothing on a scale up
+            let value = if usage_ratio < 1.0 { target_usage } else { 0 };
+            for pod in &groups.missing {
+                metrics.insert(pod.clone(), self.fallback_metric(value));
+            }
+        }
+        if scale_up_with_unready {
+            for pod in &groups.unready {
+                metrics.insert(pod.clone(), self.fallback_metric(0));
+            }
+        }
+
+        let (new_usage_ratio, _) = metric_usage_ratio(&metrics, target_usage);
+        let replicas = self.resolve_replicas(usage_ratio, new_usage_ratio, metrics.len());
+
+        Ok((replicas, usage))
+    }
+
+    /// Returns the replica count after filling in missing and unready pods,
+    /// keeping the current count if that would reverse or dampen the scaling
+    /// direction.
+    fn resolve_replicas(&self, usage_ratio: f64, new_usage_ratio: f64, metrics: usize) -> i32 {
+        if (1.0 - new_usage_ratio).abs() <= self.options.tolerance
+            || (usage_ratio < 1.0 && new_usage_ratio > 1.0)
+            || (usage_ratio > 1.0 && new_usage_ratio < 1.0)
+        {
+            return self.current_replicas;
+        }
+
+        let replicas = (new_usage_ratio * metrics as f64).ceil() as i32;
+        if (new_usage_ratio < 1.0 && replicas > self.current_replicas)
+            || (new_usage_ratio > 1.0 && replicas < self.current_replicas)
+        {
+            return self.current_replicas;
+        }
+
+        replicas
+    }
+
+    fn fallback_metric(&self, value: i64) -> PodMetric {
+        PodMetric {
+            timestamp: self.now,
+            window: Duration::ZERO,
+            value,
+        }
+    }
+
+    /// Mirrors `groupPods`.
+    fn group_pods(&self, metrics: &PodMetrics, resource: &str) -> PodGroups {
+        let mut groups = PodGroups::default();
+
+        for pod in self.pods {
+            let name = pod.metadata.name.clone().unwrap_or_default();
+            if pod.metadata.deletion_timestamp.is_some() || phase(pod) == "Failed" {
+                groups.ignored.insert(name);
+                continue;
+            }
+            if phase(pod) == "Pending" {
+                groups.unready.insert(name);
+                continue;
+            }
+            let Some(metric) = metrics.get(&name) else {
+                groups.missing.insert(name);
+                continue;
+            };
+
+            if resource == CPU && self.is_cpu_unready(pod, metric) {
+                groups.unready.insert(name);
+                continue;
+            }
+
+            groups.ready += 1;
+        }
+
+        groups
+    }
+
+    /// Returns whether the CPU sample of a pod is skipped, as the pod is not
+    /// ready or was sampled while initializing.
+    fn is_cpu_unready(&self, pod: &Pod, metric: &PodMetric) -> bool {
+        let start_time = pod
+            .status
+            .as_ref()
+            .and_then(|status| status.start_time.as_ref())
+            .map(to_utc);
+        let (Some((status, last_transition)), Some(start_time)) =
+            (ready_condition(pod), start_time)
+        else {
+            return true;
+        };
+        let last_transition = last_transition.unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        if start_time + time_delta(self.options.cpu_initialization_period) > self.now {
+            // Skip samples of unready pods and samples that might include the
+            // initialization
+            status == "False" || metric.timestamp < last_transition + time_delta(metric.window)
+        } else {
+            // Skip samples of pods that never became ready
+            status == "False"
+                && start_time + time_delta(self.options.initial_readiness_delay) > last_transition
+        }
+    }
+
+    /// Returns the usage of the resource by pod in milli-units, i.e.,
+    /// `getPodMetrics` and `getContainerMetrics`.
+    fn resource_metrics(
+        &self,
+        resource: &str,
+        container: Option<&str>,
+    ) -> Result<PodMetrics, HpaError> {
+        if self.metrics.resources.is_empty() {
+            return Err(HpaError::MissingMetric(resource.to_owned()));
+        }
+
+        let mut metrics = PodMetrics::new();
+        for (pod, usage) in &self.metrics.resources {
+            let value = match container {
+                Some(container) => {
+                    let usage = usage.containers.get(container).ok_or_else(|| {
+                        HpaError::MissingMetric(format!("container {container} of pod {pod}"))
+                    })?;
+                    usage.get(resource).map(ParsedQuantity::to_milli_i64)
+                }
+                None if usage.containers.is_empty() => None,
+                // Pods are skipped if any container lacks the resource
+                None => usage
+                    .containers
+                    .values()
+                    .map(|usage| usage.get(resource).map(ParsedQuantity::to_milli_i64))
+                    .sum(),
+            };
+
+            if let Some(value) = value {
+                metrics.insert(
+                    pod.clone(),
+                    PodMetric {
+                        timestamp: usage.timestamp,
+                        window: usage.window,
+                        value,
+                    },
+                );
+            }
+        }
+
+        Ok(metrics)
+    }
+
+    /// Returns the requests of the resource by pod in milli-units, i.e.,
+    /// `calculatePodRequests`.
+    fn pod_requests(
+        &self,
+        resource: &str,
+        container: Option<&str>,
+    ) -> Result<BTreeMap<String, i64>, HpaError> {
+        let mut requests = BTreeMap::new();
+
+        for pod in self.pods {
+            let name = pod.metadata.name.clone().unwrap_or_default();
+            let Some(spec) = &pod.spec else {
+                requests.insert(name, 0);
+                continue;
+            };
+
+            // App containers and sidecars
+            let containers = spec.containers.iter().chain(
+                spec.init_containers
+                    .iter()
+                    .flatten()
+                    .filter(|init| init.restart_policy.as_deref() == Some("Always")),
+            );
+
+            let mut sum = 0_i64;
+            for candidate in containers {
+                if container.is_some_and(|container| container != candidate.name) {
+                    continue;
+                }
+                sum += container_requests(candidate)?
+                    .get(resource)
+                    .map(ParsedQuantity::to_milli_i64)
+                    .ok_or_else(|| HpaError::MissingRequest {
+                        resource: resource.to_owned(),
+                        container: candidate.name.clone(),
+                        pod: name.clone(),
+                    })?;
+            }
+            requests.insert(name, sum);
+        }
+
+        Ok(requests)
+    }
+}
+
+/// Mirrors `GetResourceUtilizationRatio`, returning the usage ratio, the
+/// utilization in percent and the average usage in milli-units.
+fn utilization_ratio(
+    metrics: &PodMetrics,
+    requests: &BTreeMap<String, i64>,
+    target_utilization: i32,
+) -> Result<(f64, i32, i64), HpaError> {
+    let mut metrics_total = 0_i64;
+    let mut requests_total = 0_i64;
+    let mut entries = 0_i64;
+
+    for (pod, metric) in metrics {
+        // Metrics of unknown pods are extraneous
+        let Some(request) = requests.get(pod) else {
+            continue;
+        };
+        metrics_total += metric.value;
+        requests_total += request;
+        entries += 1;
+    }
+
+    if requests_total == 0 {
+        return Err(HpaError::UnknownPods);
+    }
+
+    let utilization = (metrics_total * 100 / requests_total) as i32;
+    Ok((
+        f64::from(utilization) / f64::from(target_utilization),
+        utilization,
+        metrics_total / entries,
+    ))
+}
+
+/// Mirrors `GetMetricUsageRatio`, returning the usage ratio and the average
+/// usage in milli-units.
+fn metric_usage_ratio(metrics: &PodMetrics, target_usage: i64) -> (f64, i64) {
+    let total: i64 = metrics.values().map(|metric| metric.value).sum();
+    let usage = total / metrics.len() as i64;
+
+    (usage as f64 / target_usage as f64, usage)
+}
+
+fn phase(pod: &Pod) -> &str {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref())
+        .unwrap_or_default()
+}
+
+/// Returns the status and last transition time of the `Ready` condition.
+fn ready_condition(pod: &Pod) -> Option<(&str, Option<DateTime<Utc>>)> {
+    pod.status
+        .as_ref()?
+        .conditions
+        .iter()
+        .flatten()
+        .find(|condition| condition.type_ == "Ready")
+        .map(|condition| {
+            (
+                condition.status.as_str(),
+                condition.last_transition_time.as_ref().map(to_utc),
+            )
+        })
+}
+
+fn milli_value(quantity: &Quantity) -> Result<i64, ParseQuantityError> {
+    Ok(ParsedQuantity::try_from(quantity)?.to_milli_i64())
+}
+
+fn from_milli(resource: &str, value: i64) -> ParsedQuantity {
+    quantity_for(resource, Decimal::new(value, 3))
+}
+
+fn missing_source(type_: &str) -> HpaError {
+    HpaError::InvalidMetricSource(format!("{type_} metric source is not set"))
+}
+
+fn time_delta(duration: Duration) -> TimeDelta {
+    TimeDelta::from_std(duration).unwrap_or(TimeDelta::MAX)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::{
+            autoscaling::v2::{
+                HorizontalPodAutoscalerBehavior, HorizontalPodAutoscalerSpec, MetricIdentifier,
+                MetricTarget, PodsMetricSource, ResourceMetricSource,
+            },
+            core::v1::{Container, PodCondition, PodSpec, PodStatus, ResourceRequirements},
+        },
+        apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time},
+    };
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{compat::from_utc, test_util::fixture};
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Scenario {
+        now: Time,
+        current_replicas: i32,
+        hpa: HorizontalPodAutoscaler,
+        pods: Vec<Pod>,
+        metrics: ScenarioMetrics,
+        expected: ScenarioExpectation,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioMetrics {
+        #[serde(default)]
+        resources: BTreeMap<String, ScenarioPodUsage>,
+        #[serde(default)]
+        pods: BTreeMap<String, BTreeMap<String, Quantity>>,
+        #[serde(default)]
+        objects: BTreeMap<String, Quantity>,
+        #[serde(default)]
+        external: BTreeMap<String, Vec<Quantity>>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioPodUsage {
+        timestamp: Time,
+        window_seconds: u64,
+        containers: BTreeMap<String, BTreeMap<String, Quantity>>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ScenarioExpectation {
+        metric_replicas: i32,
+        desired_replicas: i32,
+        metrics: BTreeMap<String, i32>,
+        #[serde(default)]
+        utilization: BTreeMap<String, i32>,
+    }
+
+    fn parse_list(list: BTreeMap<String, Quantity>) -> ParsedResourceList {
+        list.iter()
+            .map(|(name, quantity)| (name.clone(), quantity.try_into().unwrap()))
+            .collect()
+    }
+
+    fn run_scenario(name: &str) {
+        let scenario: Scenario = serde_yaml::from_str(&fixture(&format!("hpa/{name}"))).unwrap();
+
+        let metrics = ObservedMetrics {
+            resources: scenario
+                .metrics
+                .resources
+                .into_iter()
+                .map(|(pod, usage)| {
+                    (
+                        pod,
+                        PodUsage {
+                            timestamp: to_utc(&usage.timestamp),
+                            window: Duration::from_secs(usage.window_seconds),
+                            containers: usage
+                                .containers
+                                .into_iter()
+                                .map(|(container, list)| (container, parse_list(list)))
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+            pods: scenario
+                .metrics
+                .pods
+                .into_iter()
+                .map(|(metric, values)| (metric, parse_list(values)))
+                .collect(),
+            objects: parse_list(scenario.metrics.objects),
+            external: scenario
+                .metrics
+                .external
+                .into_iter()
+                .map(|(metric, values)| {
+                    let values = values.iter().map(|v| v.try_into().unwrap()).collect();
+                    (metric, values)
+                })
+                .collect(),
+        };
+
+        let result = Autoscaler::new(HpaOptions::default())
+            .reconcile(
+                &scenario.hpa,
+                scenario.current_replicas,
+                &scenario.pods,
+                &metrics,
+                to_utc(&scenario.now),
+            )
+            .unwrap();
+
+        assert_eq!(
+            result
+                .metrics
+                .iter()
+                .map(|metric| (metric.metric.clone(), metric.replicas))
+                .collect::<BTreeMap<_, _>>(),
+            scenario.expected.metrics
+        );
+        assert_eq!(
+            result
+                .metrics
+                .iter()
+                .filter_map(|metric| Some((metric.metric.clone(), metric.utilization?)))
+                .collect::<BTreeMap<_, _>>(),
+            scenario.expected.utilization
+        );
+        assert_eq!(
+            result.metric_replicas,
+            Some(scenario.expected.metric_replicas)
+        );
+        assert_eq!(result.desired_replicas, scenario.expected.desired_replicas);
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn running_pod(name: &str, cpu_request: Option<&str>) -> Pod {
+        let requests =
+            cpu_request.map(|cpu| BTreeMap::from([("cpu".to_owned(), Quantity(cpu.to_owned()))]));
+
+        Pod {
+            metadata: ObjectMeta {
+                name: Some(name.to_owned()),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_owned(),
+                    resources: Some(ResourceRequirements {
+                        requests,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            status: Some(PodStatus {
+                phase: Some("Running".to_owned()),
+                start_time: Some(from_utc(now() - TimeDelta::minutes(10))),
+                conditions: Some(vec![PodCondition {
+                    type_: "Ready".to_owned(),
+                    status: "True".to_owned(),
+                    last_transition_time: Some(from_utc(now() - TimeDelta::minutes(9))),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+        }
+    }
+
+    // The spec is optional before k8s-openapi 0.26
+    #[allow(clippy::useless_conversion)]
+    fn hpa(
+        metric: MetricSpec,
+        behavior: Option<HorizontalPodAutoscalerBehavior>,
+    ) -> HorizontalPodAutoscaler {
+        HorizontalPodAutoscaler {
+            spec: HorizontalPodAutoscalerSpec {
+                min_replicas: Some(1),
+                max_replicas: 20,
+                metrics: Some(vec![metric]),
+                behavior,
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    fn cpu_utilization(target: i32) -> MetricSpec {
+        MetricSpec {
+            type_: "Resource".to_owned(),
+            resource: Some(ResourceMetricSource {
+                name: "cpu".to_owned(),
+                target: MetricTarget {
+                    type_: "Utilization".to_owned(),
+                    average_utilization: Some(target),
+                    ..Default::default()
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn pods_average(name: &str, target: &str) -> MetricSpec {
+        MetricSpec {
+            type_: "Pods".to_owned(),
+            pods: Some(PodsMetricSource {
+                metric: MetricIdentifier {
+                    name: name.to_owned(),
+                    ..Default::default()
+                },
+                target: MetricTarget {
+                    type_: "AverageValue".to_owned(),
+                    average_value: Some(Quantity(target.to_owned())),
+                    ..Default::default()
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn cpu_usage(usage: &[(&str, &str)]) -> ObservedMetrics {
+        ObservedMetrics {
+            resources: usage
+                .iter()
+                .map(|(pod, cpu)| {
+                    let usage = PodUsage {
+                        timestamp: now(),
+                        window: Duration::from_secs(30),
+                        containers: BTreeMap::from([(
+                            "app".to_owned(),
+                            BTreeMap::from([("cpu".to_owned(), (*cpu).try_into().unwrap())]),
+                        )]),
+                    };
+                    (pod.to_string(), usage)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `count` running pods that all report the same value of the
+    /// `requests` pods metric.
+    fn pods_with_metric(count: usize, value: &str) -> (Vec<Pod>, ObservedMetrics) {
+        let pods: Vec<Pod> = (0..count)
+            .map(|index| running_pod(&format!("pod-{index}"), None))
+            .collect();
+        let values = pods
+            .iter()
+            .map(|pod| {
+                (
+                    pod.metadata.name.clone().unwrap(),
+                    value.try_into().unwrap(),
+                )
+            })
+            .collect();
+        let metrics = ObservedMetrics {
+            pods: BTreeMap::from([("requests".to_owned(), values)]),
+            ..Default::default()
+        };
+
+        (pods, metrics)
+    }
+
+    #[test]
+    fn test_cpu_unready_scale_up_scenario() {
+        run_scenario("cpu-unready-scale-up.yaml");
+    }
+
+    #[test]
+    fn test_multiple_metrics_scenario() {
+        run_scenario("multiple-metrics.yaml");
+    }
+
+    #[test]
+    fn test_cpu_utilization() {
+        let mut autoscaler = Autoscaler::new(HpaOptions::default());
+
+        // Scale up
+        let pods: Vec<Pod> = ["a", "b", "c"]
+            .iter()
+            .map(|name| running_pod(name, Some("1")))
+            .collect();
+        let metrics = cpu_usage(&[("a", "300m"), ("b", "500m"), ("c", "700m")]);
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(30), None), 3, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.metrics[0].utilization, Some(50));
+        assert_eq!(result.metrics[0].value.to_canonical_string(), "500m");
+        assert_eq!(result.desired_replicas, 5);
+        assert!(result.rescale());
+
+        // Scale down
+        let pods: Vec<Pod> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| running_pod(name, Some("1")))
+            .collect();
+        let metrics = cpu_usage(&[
+            ("a", "100m"),
+            ("b", "300m"),
+            ("c", "500m"),
+            ("d", "250m"),
+            ("e", "250m"),
+        ]);
+        let mut autoscaler = Autoscaler::new(HpaOptions {
+            downscale_stabilization_window: Duration::ZERO,
+            ..Default::default()
+        });
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 5, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.metrics[0].utilization, Some(28));
+        assert_eq!(result.desired_replicas, 3);
+
+        // Within the tolerance
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(27), None), 5, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.desired_replicas, 5);
+        assert!(!result.rescale());
+    }
+
+    #[test]
+    fn test_missing_cpu_metrics() {
+        let mut autoscaler = Autoscaler::new(HpaOptions {
+            downscale_stabilization_window: Duration::ZERO,
+            ..Default::default()
+        });
+        let pods: Vec<Pod> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| running_pod(name, Some("1")))
+            .collect();
+
+        // The pod without metrics is assumed to use its request
+        let metrics = cpu_usage(&[("a", "100m"), ("b", "100m"), ("c", "100m")]);
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 4, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.desired_replicas, 3);
+
+        // ... and to use nothing while scaling up
+        let metrics = cpu_usage(&[("a", "900m"), ("b", "900m"), ("c", "900m")]);
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 4, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.metrics[0].utilization, Some(90));
+        assert_eq!(result.desired_replicas, 6);
+
+        // ... and to be left out if the usage matches the target exactly
+        let pods: Vec<Pod> = (0..8)
+            .map(|index| running_pod(&format!("pod-{index}"), Some("1")))
+            .collect();
+        let metrics = cpu_usage(&[
+            ("pod-0", "500m"),
+            ("pod-1", "500m"),
+            ("pod-2", "500m"),
+            ("pod-3", "500m"),
+        ]);
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 8, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.metrics[0].utilization, Some(50));
+        assert_eq!(result.desired_replicas, 8);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut autoscaler = Autoscaler::new(HpaOptions::default());
+        let metrics = cpu_usage(&[("a", "100m")]);
+
+        // The spec is only optional before k8s-openapi 0.26
+        let without_spec = HorizontalPodAutoscaler::default();
+        if without_spec.spec.as_optional().is_none() {
+            assert!(matches!(
+                autoscaler.reconcile(&without_spec, 1, &[], &metrics, now()),
+                Err(HpaError::MissingSpec)
+            ));
+        }
+
+        let error = autoscaler
+            .reconcile(
+                &hpa(cpu_utilization(50), None),
+                1,
+                &[running_pod("a", None)],
+                &metrics,
+                now(),
+            )
+            .unwrap_err();
+        let HpaError::InvalidMetrics {
+            invalid,
+            total,
+            first,
+        } = error
+        else {
+            panic!("unexpected error: {error}");
+        };
+        assert_eq!((invalid, total), (1, 1));
+        assert!(matches!(*first, HpaError::MissingRequest { .. }));
+
+        // Disabled autoscaling and replica counts out of bounds
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 0, &[], &metrics, now())
+            .unwrap();
+        assert_eq!(result.desired_replicas, 0);
+        let result = autoscaler
+            .reconcile(&hpa(cpu_utilization(50), None), 25, &[], &metrics, now())
+            .unwrap();
+        assert_eq!(result.desired_replicas, 20);
+    }
+
+    #[test]
+    fn test_legacy_normalization() {
+        let mut autoscaler = Autoscaler::new(HpaOptions::default());
+        let spec = hpa(pods_average("requests", "10"), None);
+
+        // Scaling up is limited to doubling the replica count
+        let (pods, metrics) = pods_with_metric(3, "50");
+        let result = autoscaler
+            .reconcile(&spec, 3, &pods, &metrics, now())
+            .unwrap();
+        assert_eq!(result.metric_replicas, Some(15));
+        assert_eq!(result.desired_replicas, 6);
+
+        // Scaling down is stabilized over five minutes
+        let (pods, metrics) = pods_with_metric(6, "1");
+        let later = now() + TimeDelta::seconds(60);
+        let result = autoscaler
+            .reconcile(&spec, 6, &pods, &metrics, later)
+            .unwrap();
+        assert_eq!(result.metric_replicas, Some(1));
+        // The earlier recommendation is still capped by the scale up limit
+        assert_eq!(result.desired_replicas, 12);
+
+        let later = now() + TimeDelta::seconds(301);
+        let result = autoscaler
+            .reconcile(&spec, 6, &pods, &metrics, later)
+            .unwrap();
+        assert_eq!(result.desired_replicas, 1);
+    }
+
+    #[test]
+    fn test_behavior_policies() {
+        let behavior: HorizontalPodAutoscalerBehavior = serde_json::from_value(serde_json::json!({
+            "scaleUp": {
+                "policies": [{ "type": "Pods", "value": 1, "periodSeconds": 60 }],
+            },
+        }))
+        .unwrap();
+        let spec = hpa(pods_average("requests", "10"), Some(behavior));
+        let mut autoscaler = Autoscaler::new(HpaOptions::default());
+        let at = |seconds| now() + TimeDelta::seconds(seconds);
+
+        // One pod is added per minute
+        let (pods, metrics) = pods_with_metric(2, "50");
+        let result = autoscaler
+            .reconcile(&spec, 2, &pods, &metrics, at(0))
+            .unwrap();
+        assert_eq!(result.desired_replicas, 3);
+
+        let (pods, metrics) = pods_with_metric(3, "50");
+        let result = autoscaler
+            .reconcile(&spec, 3, &pods, &metrics, at(30))
+            .unwrap();
+        assert_eq!(result.metric_replicas, Some(15));
+        assert_eq!(result.desired_replicas, 3);
+
+        let result = autoscaler
+            .reconcile(&spec, 3, &pods, &metrics, at(61))
+            .unwrap();
+        assert_eq!(result.desired_replicas, 4);
+
+        // Scaling down waits for the default stabilization window
+        let (pods, metrics) = pods_with_metric(4, "1");
+        let result = autoscaler
+            .reconcile(&spec, 4, &pods, &metrics, at(120))
+            .unwrap();
+        assert_eq!(result.metric_replicas, Some(1));
+        assert_eq!(result.desired_replicas, 4);
+
+        let result = autoscaler
+            .reconcile(&spec, 4, &pods, &metrics, at(500))
+            .unwrap();
+        assert_eq!(result.desired_replicas, 1);
+    }
+}
@@ -0,0 +1,510 @@
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use k8s_openapi::{api::core::v1::Pod, apimachinery::pkg::api::resource::Quantity};
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    kubelet::{node_allocatable, KubeletError, NodeReservation},
+    resources::{fits_within, from_base_map, pod_requests, to_base_map, ParsedResourceList},
+    ParseQuantityError,
+};
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum InstanceSelectionError {
+    /// The catalog file could not be read
+    #[cfg(feature = "serde")]
+    #[error("failed to read catalog: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The catalog is not valid JSON
+    #[cfg(feature = "serde")]
+    #[error("invalid JSON catalog: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The catalog is not valid YAML
+    #[cfg(feature = "serde")]
+    #[error("invalid YAML catalog: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// The catalog file extension is neither JSON nor YAML
+    #[cfg(feature = "serde")]
+    #[error("unsupported catalog file extension: {0}")]
+    UnsupportedFormat(String),
+
+    /// A quantity of the catalog or a pod could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+
+    /// The reserved resources or eviction thresholds of an instance type are
+    /// invalid
+    #[error("invalid reservation: {0}")]
+    InvalidReservation(#[from] KubeletError),
+}
+
+// --- Catalog ---
+
+/// An instance type that nodes can be launched with.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct InstanceType {
+    /// Name of the instance type, e.g., `m5.large`
+    pub name: String,
+    /// Resources of the instance, including `pods` if the number of pods is
+    /// limited
+    pub capacity: BTreeMap<String, Quantity>,
+    /// Resources reserved for Kubernetes system daemons
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub kube_reserved: BTreeMap<String, Quantity>,
+    /// Resources reserved for OS system daemons
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub system_reserved: BTreeMap<String, Quantity>,
+    /// Hard eviction thresholds by signal, e.g., `memory.available: 100Mi`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub eviction_hard: BTreeMap<String, String>,
+    /// Price of running the instance for an hour
+    pub price_per_hour: Decimal,
+}
+
+impl InstanceType {
+    /// Returns the resources allocatable by pods the way the kubelet of a node
+    /// of this type reports them, see [`node_allocatable`].
+    pub fn allocatable(&self) -> Result<ParsedResourceList, KubeletError> {
+        node_allocatable(
+            &self.capacity,
+            &NodeReservation {
+                kube_reserved: self.kube_reserved.clone(),
+                system_reserved: self.system_reserved.clone(),
+                eviction_hard: self.eviction_hard.clone(),
+            },
+        )
+    }
+}
+
+/// A catalog of instance types. With the `serde` feature, it can be loaded
+/// from a local JSON or YAML file.
+///
+/// ```yaml
+/// instanceTypes:
+///   - name: m5.large
+///     capacity: { cpu: "2", memory: 8Gi, pods: "29" }
+///     kubeReserved: { cpu: 70m, memory: 574Mi }
+///     evictionHard: { memory.available: 100Mi }
+///     pricePerHour: "0.096"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct InstanceCatalog {
+    /// Instance types available for selection
+    pub instance_types: Vec<InstanceType>,
+}
+
+#[cfg(feature = "serde")]
+impl InstanceCatalog {
+    /// Parses a catalog from a JSON string.
+    pub fn from_json_str(input: &str) -> Result<Self, InstanceSelectionError> {
+        Ok(serde_json::from_str(input)?)
+    }
+
+    /// Parses a catalog from a YAML string.
+    pub fn from_yaml_str(input: &str) -> Result<Self, InstanceSelectionError> {
+        Ok(serde_yaml::from_str(input)?)
+    }
+
+    /// Reads a catalog from a `.json`, `.yaml` or `.yml` file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, InstanceSelectionError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+
+        match extension {
+            "json" => Self::from_json_str(&std::fs::read_to_string(path)?),
+            "yaml" | "yml" => Self::from_yaml_str(&std::fs::read_to_string(path)?),
+            _ => Err(InstanceSelectionError::UnsupportedFormat(
+                extension.to_owned(),
+            )),
+        }
+    }
+}
+
+// --- Selection ---
+
+/// A pod waiting for a node.
+#[derive(Debug, Clone)]
+pub struct PendingPod {
+    /// Name of the pod
+    pub name: String,
+    /// Effective requests of the pod
+    pub requests: ParsedResourceList,
+}
+
+impl PendingPod {
+    /// Creates a pending pod from its name and effective requests.
+    pub fn new(name: impl Into<String>, requests: ParsedResourceList) -> Self {
+        Self {
+            name: name.into(),
+            requests,
+        }
+    }
+
+    /// Creates a pending pod from a Kubernetes pod.
+    pub fn from_pod(pod: &Pod) -> Result<Self, ParseQuantityError> {
+        Ok(Self {
+            name: pod.metadata.name.clone().unwrap_or_default(),
+            requests: pod
+                .spec
+                .as_ref()
+                .map(pod_requests)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// A node to launch and the pods it is going to run.
+#[derive(Debug, Clone)]
+pub struct SelectedNode {
+    /// Name of the instance type to launch
+    pub instance_type: String,
+    /// Price of the node per hour
+    pub price_per_hour: Decimal,
+    /// Names of the pods placed on the node
+    pub pods: Vec<String>,
+    /// Allocatable resources of the node
+    pub allocatable: ParsedResourceList,
+    /// Sum of the requests of the pods on the node
+    pub used: ParsedResourceList,
+}
+
+/// Cheapest set of nodes found for the pending pods.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    /// Nodes to launch
+    pub nodes: Vec<SelectedNode>,
+    /// Total price of all nodes per hour
+    pub price_per_hour: Decimal,
+    /// Pods that fit on none of the instance types
+    pub unschedulable: Vec<String>,
+}
+
+/// Selects the cheapest combination of instances that fits all pending pods.
+///
+/// Pods are packed largest first. Like Karpenter, every new node starts with
+/// all instance types the pod fits on and keeps accepting pods as long as one
+/// of its candidate types still fits them, after which the cheapest remaining
+/// type is launched. This is compared against packing onto every single
+/// instance type, and the cheapest result is returned.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::instances::{select_instances, InstanceCatalog, InstanceType, PendingPod};
+/// use kube_quantity::ParsedQuantity;
+/// use rust_decimal::Decimal;
+///
+/// let instance_type = |name: &str, cpu: &str, memory: &str, price| InstanceType {
+///     name: name.to_string(),
+///     capacity: BTreeMap::from([
+///         ("cpu".to_string(), Quantity(cpu.to_string())),
+///         ("memory".to_string(), Quantity(memory.to_string())),
+///     ]),
+///     kube_reserved: BTreeMap::from([("cpu".to_string(), Quantity("100m".to_string()))]),
+///     system_reserved: BTreeMap::new(),
+///     eviction_hard: BTreeMap::new(),
+///     price_per_hour: Decimal::new(price, 2),
+/// };
+/// let catalog = InstanceCatalog {
+///     instance_types: vec![
+///         instance_type("small", "2", "4Gi", 5),
+///         instance_type("large", "8", "16Gi", 30),
+///     ],
+/// };
+///
+/// let requests = [("cpu".to_string(), ParsedQuantity::try_from("1").unwrap())];
+/// let pods: Vec<_> = (0..3)
+///     .map(|index| PendingPod::new(format!("web-{index}"), requests.clone().into()))
+///     .collect();
+///
+/// let selection = select_instances(&catalog, &pods).unwrap();
+///
+/// // 1.9 allocatable cores only fit a single pod per small instance
+/// assert_eq!(selection.nodes.len(), 3);
+/// assert_eq!(selection.price_per_hour.to_string(), "0.15");
+/// ```
+pub fn select_instances(
+    catalog: &InstanceCatalog,
+    pods: &[PendingPod],
+) -> Result<Selection, InstanceSelectionError> {
+    let instance_types = catalog
+        .instance_types
+        .iter()
+        .map(|instance_type| Ok((instance_type, to_base_map(&instance_type.allocatable()?))))
+        .collect::<Result<Vec<_>, KubeletError>>()?;
+
+    let mut pods = pods
+        .iter()
+        .map(|pod| (pod.name.as_str(), pod_demand(pod, &instance_types)))
+        .collect::<Vec<_>>();
+    pods.sort_by_key(|(_, demand)| Reverse(size(demand, &instance_types)));
+
+    let all_types: Vec<usize> = (0..instance_types.len()).collect();
+
+    let selection = std::iter::once(all_types)
+        .chain((0..instance_types.len()).map(|index| vec![index]))
+        .map(|candidates| pack(&pods, &instance_types, &candidates))
+        .min_by_key(|selection| (selection.unschedulable.len(), selection.price_per_hour))
+        .unwrap_or_else(|| Selection {
+            nodes: Vec::new(),
+            price_per_hour: Decimal::ZERO,
+            unschedulable: pods.iter().map(|(name, _)| name.to_string()).collect(),
+        });
+
+    Ok(selection)
+}
+
+type Resources = BTreeMap<String, Decimal>;
+
+struct OpenNode<'a> {
+    pods: Vec<&'a str>,
+    used: Resources,
+    candidates: Vec<usize>,
+}
+
+/// Packs the pods first fit onto nodes of the candidate instance types.
+fn pack(
+    pods: &[(&str, Resources)],
+    instance_types: &[(&InstanceType, Resources)],
+    candidates: &[usize],
+) -> Selection {
+    let mut nodes: Vec<OpenNode> = Vec::new();
+    let mut unschedulable = Vec::new();
+
+    'pods: for (name, demand) in pods {
+        for node in nodes.iter_mut() {
+            let mut used = node.used.clone();
+            for (resource, value) in demand {
+                *used.entry(resource.clone()).or_default() += value;
+            }
+
+            let remaining: Vec<usize> = node
+                .candidates
+                .iter()
+                .copied()
+                .filter(|index| fits_within(&used, &instance_types[*index].1))
+                .collect();
+
+            if !remaining.is_empty() {
+                node.pods.push(name);
+                node.used = used;
+                node.candidates = remaining;
+                continue 'pods;
+            }
+        }
+
+        let remaining: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|index| fits_within(demand, &instance_types[*index].1))
+            .collect();
+
+        if remaining.is_empty() {
+            unschedulable.push(name.to_string());
+        } else {
+            nodes.push(OpenNode {
+                pods: vec![name],
+                used: demand.clone(),
+                candidates: remaining,
+            });
+        }
+    }
+
+    let nodes: Vec<SelectedNode> = nodes
+        .into_iter()
+        .map(|node| {
+            let cheapest = node
+                .candidates
+                .iter()
+                .copied()
+                .min_by_key(|index| instance_types[*index].0.price_per_hour)
+                .expect("open nodes always have a candidate");
+            let (instance_type, allocatable) = &instance_types[cheapest];
+
+            SelectedNode {
+                instance_type: instance_type.name.clone(),
+                price_per_hour: instance_type.price_per_hour,
+                pods: node.pods.into_iter().map(str::to_owned).collect(),
+                allocatable: from_base_map(allocatable),
+                used: from_base_map(&node.used),
+            }
+        })
+        .collect();
+
+    Selection {
+        price_per_hour: nodes.iter().map(|node| node.price_per_hour).sum(),
+        nodes,
+        unschedulable,
+    }
+}
+
+/// Returns the demand of a pod, counting it against `pods` if any instance
+/// type limits the number of pods.
+fn pod_demand(pod: &PendingPod, instance_types: &[(&InstanceType, Resources)]) -> Resources {
+    let mut demand = to_base_map(&pod.requests);
+
+    if instance_types
+        .iter()
+        .any(|(_, allocatable)| allocatable.contains_key("pods"))
+    {
+        *demand.entry("pods".to_owned()).or_default() += Decimal::ONE;
+    }
+
+    demand
+}
+
+/// Returns the size of a demand relative to the largest instance types.
+fn size(demand: &Resources, instance_types: &[(&InstanceType, Resources)]) -> Decimal {
+    demand
+        .iter()
+        .filter_map(|(resource, value)| {
+            let largest = instance_types
+                .iter()
+                .filter_map(|(_, allocatable)| allocatable.get(resource))
+                .max()
+                .filter(|largest| !largest.is_zero())?;
+            Some(value / largest)
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::test_util::fixture_path;
+    use crate::test_util::{list, parsed_list};
+
+    /// Returns the catalog of `tests/fixtures/instances/instance-catalog.json`.
+    fn catalog() -> InstanceCatalog {
+        let instance_type =
+            |name: &str, cpu: &str, memory: &str, pods: &str, price: i64| InstanceType {
+                name: name.to_owned(),
+                capacity: list(&[("cpu", cpu), ("memory", memory), ("pods", pods)]),
+                kube_reserved: if cpu == "2" {
+                    list(&[("cpu", "70m"), ("memory", "574Mi")])
+                } else {
+                    list(&[("cpu", "80m"), ("memory", "893Mi")])
+                },
+                system_reserved: list(&[("memory", "100Mi")]),
+                eviction_hard: BTreeMap::from([(
+                    "memory.available".to_owned(),
+                    "100Mi".to_owned(),
+                )]),
+                price_per_hour: Decimal::new(price, 3),
+            };
+
+        InstanceCatalog {
+            instance_types: vec![
+                instance_type("m5.large", "2", "8Gi", "29", 96),
+                instance_type("m5.xlarge", "4", "16Gi", "58", 192),
+                instance_type("r5.xlarge", "4", "32Gi", "58", 252),
+            ],
+        }
+    }
+
+    fn pod(name: &str, entries: &[(&str, &str)]) -> PendingPod {
+        PendingPod::new(name, parsed_list(entries))
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_catalog_json_and_yaml_match() {
+        let json =
+            InstanceCatalog::from_path(fixture_path("instances/instance-catalog.json")).unwrap();
+        let yaml =
+            InstanceCatalog::from_path(fixture_path("instances/instance-catalog.yaml")).unwrap();
+
+        assert_eq!(json, catalog());
+        assert_eq!(yaml, catalog());
+        assert_eq!(json.instance_types[0].price_per_hour, Decimal::new(96, 3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_unsupported_catalog_format() {
+        assert!(matches!(
+            InstanceCatalog::from_path(fixture_path("instances/instance-catalog.toml")),
+            Err(InstanceSelectionError::UnsupportedFormat(extension)) if extension == "toml"
+        ));
+        assert!(matches!(
+            InstanceCatalog::from_json_str("instanceTypes: []"),
+            Err(InstanceSelectionError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_allocatable() {
+        let catalog = catalog();
+        let allocatable = catalog.instance_types[0].allocatable().unwrap();
+
+        assert_eq!(allocatable["cpu"].to_string(), "1.93");
+        // 8Gi minus 574Mi kube, 100Mi system reserved and the 100Mi eviction
+        // threshold
+        assert_eq!(allocatable["memory"].to_string(), "7778336768");
+        assert_eq!(allocatable["pods"].to_string(), "29");
+    }
+
+    #[test]
+    fn test_select_cheapest_combination() {
+        let catalog = catalog();
+        let mut pods: Vec<PendingPod> = (0..3)
+            .map(|index| pod(&format!("api-{index}"), &[("cpu", "1"), ("memory", "2Gi")]))
+            .collect();
+        pods.push(pod("batch-0", &[("cpu", "3"), ("memory", "20Gi")]));
+
+        let selection = select_instances(&catalog, &pods).unwrap();
+
+        // Only an r5.xlarge fits the memory heavy pod, while the api pods share
+        // an m5.xlarge instead of a second r5.xlarge
+        assert_eq!(selection.nodes.len(), 2);
+        assert_eq!(selection.nodes[0].instance_type, "r5.xlarge");
+        assert_eq!(selection.nodes[0].pods, ["batch-0"]);
+        assert_eq!(selection.nodes[1].instance_type, "m5.xlarge");
+        assert_eq!(selection.nodes[1].pods, ["api-0", "api-1", "api-2"]);
+        assert_eq!(selection.price_per_hour, Decimal::new(444, 3));
+        assert!(selection.unschedulable.is_empty());
+    }
+
+    #[test]
+    fn test_select_unschedulable() {
+        let catalog = catalog();
+        let pods = [pod("gpu-0", &[("nvidia.com/gpu", "1")])];
+
+        let selection = select_instances(&catalog, &pods).unwrap();
+
+        assert!(selection.nodes.is_empty());
+        assert_eq!(selection.unschedulable, ["gpu-0"]);
+    }
+}
@@ -0,0 +1,906 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr, time::Duration};
+
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
+use thiserror::Error;
+
+use crate::{
+    resources::{from_base_map, parse_resource_list, to_base_map, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity, QuantityOrPercent,
+};
+
+const MEMORY: &str = "memory";
+const EPHEMERAL_STORAGE: &str = "ephemeral-storage";
+const HUGEPAGES_PREFIX: &str = "hugepages-";
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum KubeletError {
+    /// A threshold is neither a quantity nor a percentage between 0% and 100%
+    #[error("invalid threshold {0:?}")]
+    InvalidThreshold(String),
+
+    /// An eviction signal is not supported by the kubelet
+    #[error("unsupported eviction signal {0:?}")]
+    UnsupportedSignal(String),
+
+    /// An entry of a flag does not match its syntax
+    #[error("invalid flag entry {0:?}")]
+    InvalidFlag(String),
+
+    /// A duration does not match the Go duration syntax
+    #[error("invalid duration {0:?}")]
+    InvalidDuration(String),
+
+    /// A soft eviction threshold has no grace period
+    #[error("threshold for signal {0} must have a grace period")]
+    MissingGracePeriod(EvictionSignal),
+
+    /// A quantity could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Node allocatable ---
+
+/// Resources the kubelet withholds from pods, in the format of the
+/// `kubeReserved`, `systemReserved` and `evictionHard` settings of the
+/// kubelet configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeReservation {
+    /// Resources reserved for Kubernetes system daemons
+    pub kube_reserved: BTreeMap<String, Quantity>,
+    /// Resources reserved for OS system daemons
+    pub system_reserved: BTreeMap<String, Quantity>,
+    /// Hard eviction thresholds by signal, e.g., `memory.available: 100Mi` or
+    /// `nodefs.available: 10%`
+    pub eviction_hard: BTreeMap<String, String>,
+}
+
+/// Returns the allocatable resources of a node the way the kubelet reports
+/// them.
+///
+/// Every resource of the capacity is reduced by its kube and system reserved
+/// amounts. Memory and ephemeral storage are further reduced by the
+/// `memory.available` and `nodefs.available` hard eviction thresholds, which
+/// may be percentages of the capacity, and memory is reduced by the capacity
+/// of all hugepages. Resources never drop below zero.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::kubelet::{node_allocatable, NodeReservation};
+///
+/// let list = |entries: &[(&str, &str)]| -> BTreeMap<String, Quantity> {
+///     entries.iter().map(|(k, v)| (k.to_string(), Quantity(v.to_string()))).collect()
+/// };
+///
+/// let capacity = list(&[("cpu", "4"), ("memory", "16Gi"), ("pods", "110")]);
+/// let reservation = NodeReservation {
+///     kube_reserved: list(&[("cpu", "100m"), ("memory", "1Gi")]),
+///     eviction_hard: BTreeMap::from([("memory.available".to_string(), "100Mi".to_string())]),
+///     ..Default::default()
+/// };
+///
+/// let allocatable = node_allocatable(&capacity, &reservation).unwrap();
+///
+/// assert_eq!(allocatable["cpu"].to_canonical_string(), "3900m");
+/// assert_eq!(allocatable["memory"].to_canonical_string(), "15260Mi");
+/// assert_eq!(allocatable["pods"].to_canonical_string(), "110");
+/// ```
+pub fn node_allocatable(
+    capacity: &BTreeMap<String, Quantity>,
+    reservation: &NodeReservation,
+) -> Result<ParsedResourceList, KubeletError> {
+    let capacity = to_base_map(&parse_resource_list(capacity)?);
+
+    let mut reserved = BTreeMap::<String, Decimal>::new();
+    for list in [&reservation.kube_reserved, &reservation.system_reserved] {
+        for (resource, value) in to_base_map(&parse_resource_list(list)?) {
+            *reserved.entry(resource).or_default() += value;
+        }
+    }
+    for (resource, value) in hard_eviction_reservation(&reservation.eviction_hard, &capacity)? {
+        *reserved.entry(resource).or_default() += value;
+    }
+
+    let mut allocatable: BTreeMap<String, Decimal> = capacity
+        .iter()
+        .map(|(resource, value)| {
+            let reserved = reserved.get(resource).copied().unwrap_or_default();
+            (resource.clone(), (value - reserved).max(Decimal::ZERO))
+        })
+        .collect();
+
+    // Pre-allocated hugepages are not available as regular memory
+    let hugepages: Decimal = capacity
+        .iter()
+        .filter(|(resource, _)| resource.starts_with(HUGEPAGES_PREFIX))
+        .map(|(_, value)| *value)
+        .sum();
+    if let Some(memory) = allocatable.get_mut(MEMORY) {
+        *memory = (*memory - hugepages).max(Decimal::ZERO);
+    }
+
+    Ok(from_base_map(&allocatable))
+}
+
+/// Returns the resources reserved by the hard eviction thresholds.
+fn hard_eviction_reservation(
+    eviction_hard: &BTreeMap<String, String>,
+    capacity: &BTreeMap<String, Decimal>,
+) -> Result<BTreeMap<String, Decimal>, KubeletError> {
+    let mut reservation = BTreeMap::new();
+
+    for (signal, threshold) in eviction_hard {
+        let resource = match signal.parse() {
+            Ok(EvictionSignal::MemoryAvailable) => MEMORY,
+            Ok(EvictionSignal::NodeFsAvailable) => EPHEMERAL_STORAGE,
+            _ => continue,
+        };
+
+        let capacity = capacity.get(resource).copied().unwrap_or_default();
+        let threshold: ThresholdValue = threshold.parse()?;
+        reservation.insert(resource.to_owned(), threshold.resolve_base(capacity));
+    }
+
+    Ok(reservation)
+}
+
+// --- Flags ---
+
+/// Parses a resource map flag such as `--kube-reserved` or
+/// `--system-reserved`, i.e., comma separated `resource=quantity` pairs.
+///
+/// ```rust
+/// use kube_quantity::kubelet::{format_resource_map, parse_resource_map};
+///
+/// let reserved = parse_resource_map("cpu=100m, memory=1Gi,ephemeral-storage=1Gi").unwrap();
+///
+/// assert_eq!(reserved["memory"].to_string(), "1Gi");
+/// assert_eq!(format_resource_map(&reserved), "cpu=100m,ephemeral-storage=1Gi,memory=1Gi");
+/// ```
+pub fn parse_resource_map(flag: &str) -> Result<ParsedResourceList, KubeletError> {
+    parse_map(flag, '=')?
+        .into_iter()
+        .map(|(resource, quantity)| Ok((resource, ParsedQuantity::try_from(quantity)?)))
+        .collect()
+}
+
+/// Formats a resource map in the syntax of `--kube-reserved`.
+pub fn format_resource_map(list: &ParsedResourceList) -> String {
+    format_map(list, '=')
+}
+
+/// Signal the kubelet evicts pods on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EvictionSignal {
+    /// `memory.available`
+    MemoryAvailable,
+    /// `allocatableMemory.available`
+    AllocatableMemoryAvailable,
+    /// `nodefs.available`
+    NodeFsAvailable,
+    /// `nodefs.inodesFree`
+    NodeFsInodesFree,
+    /// `imagefs.available`
+    ImageFsAvailable,
+    /// `imagefs.inodesFree`
+    ImageFsInodesFree,
+    /// `containerfs.available`
+    ContainerFsAvailable,
+    /// `containerfs.inodesFree`
+    ContainerFsInodesFree,
+    /// `pid.available`
+    PidAvailable,
+}
+
+impl EvictionSignal {
+    /// All supported signals
+    pub const ALL: [EvictionSignal; 9] = [
+        EvictionSignal::MemoryAvailable,
+        EvictionSignal::AllocatableMemoryAvailable,
+        EvictionSignal::NodeFsAvailable,
+        EvictionSignal::NodeFsInodesFree,
+        EvictionSignal::ImageFsAvailable,
+        EvictionSignal::ImageFsInodesFree,
+        EvictionSignal::ContainerFsAvailable,
+        EvictionSignal::ContainerFsInodesFree,
+        EvictionSignal::PidAvailable,
+    ];
+
+    /// Returns the name of the signal as used in flags.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionSignal::MemoryAvailable => "memory.available",
+            EvictionSignal::AllocatableMemoryAvailable => "allocatableMemory.available",
+            EvictionSignal::NodeFsAvailable => "nodefs.available",
+            EvictionSignal::NodeFsInodesFree => "nodefs.inodesFree",
+            EvictionSignal::ImageFsAvailable => "imagefs.available",
+            EvictionSignal::ImageFsInodesFree => "imagefs.inodesFree",
+            EvictionSignal::ContainerFsAvailable => "containerfs.available",
+            EvictionSignal::ContainerFsInodesFree => "containerfs.inodesFree",
+            EvictionSignal::PidAvailable => "pid.available",
+        }
+    }
+}
+
+impl FromStr for EvictionSignal {
+    type Err = KubeletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|signal| signal.as_str() == s)
+            .ok_or_else(|| KubeletError::UnsupportedSignal(s.to_owned()))
+    }
+}
+
+impl Display for EvictionSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Value of an eviction threshold or minimum reclaim, i.e., a
+/// [`QuantityOrPercent`] whose percentage lies between `0%` and `100%`.
+///
+/// This is a separate type because the kubelet resolves percentages
+/// differently from [`QuantityOrPercent::resolve`]: the percentage is
+/// converted to a single precision float and the result truncated to whole
+/// units, so the exact decimal result can be off by several bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdValue(QuantityOrPercent);
+
+impl ThresholdValue {
+    /// Resolves the value against a capacity. Percentages are applied with the
+    /// single precision floats of the kubelet and truncated to whole units.
+    ///
+    /// ```rust
+    /// use kube_quantity::{kubelet::ThresholdValue, ParsedQuantity};
+    ///
+    /// let capacity: ParsedQuantity = "10Gi".try_into().unwrap();
+    /// let threshold: ThresholdValue = "5%".parse().unwrap();
+    ///
+    /// assert_eq!(threshold.resolve(&capacity).to_bytes_u64(), Some(536870920));
+    /// ```
+    pub fn resolve(&self, capacity: &ParsedQuantity) -> ParsedQuantity {
+        ParsedQuantity::from_base_decimal(
+            self.resolve_base(capacity.to_base_decimal()),
+            capacity.format().clone(),
+        )
+    }
+
+    /// Returns the quantity or percentage.
+    pub fn as_quantity_or_percent(&self) -> &QuantityOrPercent {
+        &self.0
+    }
+
+    fn resolve_base(&self, capacity: Decimal) -> Decimal {
+        match &self.0 {
+            QuantityOrPercent::Quantity(quantity) => quantity.to_base_decimal(),
+            QuantityOrPercent::Percent(percentage) => {
+                let fraction = percentage.to_f32().unwrap_or_default() / 100.0;
+                let capacity = capacity.ceil().to_f64().unwrap_or_default();
+
+                Decimal::from_f64(capacity * f64::from(fraction))
+                    .unwrap_or_default()
+                    .trunc()
+            }
+        }
+    }
+
+    fn is_positive(&self) -> bool {
+        match &self.0 {
+            QuantityOrPercent::Quantity(quantity) => quantity.to_base_decimal() > Decimal::ZERO,
+            QuantityOrPercent::Percent(percentage) => *percentage > Decimal::ZERO,
+        }
+    }
+}
+
+impl From<ThresholdValue> for QuantityOrPercent {
+    fn from(value: ThresholdValue) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for ThresholdValue {
+    type Err = KubeletError;
+
+    /// Parses a quantity or a percentage between `0%` and `100%`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.ends_with('%') {
+            return Ok(ThresholdValue(QuantityOrPercent::Quantity(
+                ParsedQuantity::try_from(s)?,
+            )));
+        }
+
+        QuantityOrPercent::try_from(s)
+            .ok()
+            .filter(|value| match value {
+                QuantityOrPercent::Percent(percentage) => {
+                    (Decimal::ZERO..=Decimal::ONE_HUNDRED).contains(percentage)
+                }
+                QuantityOrPercent::Quantity(_) => false,
+            })
+            .map(ThresholdValue)
+            .ok_or_else(|| KubeletError::InvalidThreshold(s.to_owned()))
+    }
+}
+
+impl Display for ThresholdValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Operator an eviction threshold compares the signal against its value with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ThresholdOperator {
+    /// `<`, the only operator the kubelet supports
+    #[default]
+    LessThan,
+}
+
+impl ThresholdOperator {
+    /// Returns the operator as used in flags.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThresholdOperator::LessThan => "<",
+        }
+    }
+}
+
+impl Display for ThresholdOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An eviction threshold of the kubelet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvictionThreshold {
+    /// Signal the threshold applies to
+    pub signal: EvictionSignal,
+    /// Operator the signal is compared against the value with
+    pub operator: ThresholdOperator,
+    /// Value the signal is compared against
+    pub value: ThresholdValue,
+    /// Time the threshold must be met before evicting, which is zero for hard
+    /// thresholds
+    pub grace_period: Duration,
+    /// Amount to reclaim beyond the threshold once it is met
+    pub min_reclaim: Option<ThresholdValue>,
+}
+
+impl EvictionThreshold {
+    /// Returns whether the threshold is hard, i.e., evicts without a grace
+    /// period. Like in the kubelet, this includes soft thresholds with a zero
+    /// grace period.
+    pub fn is_hard(&self) -> bool {
+        self.grace_period.is_zero()
+    }
+}
+
+/// Eviction settings in the syntax of the kubelet flags, with empty strings
+/// for unset flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvictionFlags {
+    /// `--eviction-hard`, e.g., `memory.available<100Mi,nodefs.available<10%`
+    pub eviction_hard: String,
+    /// `--eviction-soft`, e.g., `memory.available<1Gi`
+    pub eviction_soft: String,
+    /// `--eviction-soft-grace-period`, e.g., `memory.available=1m30s`
+    pub eviction_soft_grace_period: String,
+    /// `--eviction-minimum-reclaim`, e.g., `memory.available=500Mi`
+    pub eviction_minimum_reclaim: String,
+}
+
+impl EvictionFlags {
+    /// Parses the flags into thresholds the way the kubelet does, i.e.,
+    /// `ParseThresholdConfig`.
+    ///
+    /// Thresholds of `0%` and `100%` are ignored, quantities must be positive,
+    /// every soft threshold needs a grace period, and minimum reclaims apply to
+    /// both hard and soft thresholds of their signal.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use kube_quantity::kubelet::{EvictionFlags, EvictionSignal};
+    ///
+    /// let flags = EvictionFlags {
+    ///     eviction_hard: "memory.available<100Mi,nodefs.available<10%".to_string(),
+    ///     eviction_soft: "memory.available<1Gi".to_string(),
+    ///     eviction_soft_grace_period: "memory.available=1m30s".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let thresholds = flags.parse().unwrap();
+    ///
+    /// assert_eq!(thresholds.len(), 3);
+    /// assert_eq!(thresholds[2].signal, EvictionSignal::MemoryAvailable);
+    /// assert_eq!(thresholds[2].grace_period, Duration::from_secs(90));
+    /// assert_eq!(EvictionFlags::from_thresholds(&thresholds), flags);
+    /// ```
+    pub fn parse(&self) -> Result<Vec<EvictionThreshold>, KubeletError> {
+        let min_reclaims = parse_signal_map(&self.eviction_minimum_reclaim, '=')?
+            .into_iter()
+            .map(|(signal, value)| {
+                let min_reclaim: ThresholdValue = value.parse()?;
+                let is_valid = match min_reclaim.as_quantity_or_percent() {
+                    QuantityOrPercent::Quantity(quantity) => {
+                        !quantity.to_base_decimal().is_sign_negative()
+                    }
+                    QuantityOrPercent::Percent(_) => min_reclaim.is_positive(),
+                };
+
+                if is_valid {
+                    Ok((signal, min_reclaim))
+                } else {
+                    Err(KubeletError::InvalidThreshold(value))
+                }
+            })
+            .collect::<Result<BTreeMap<_, _>, KubeletError>>()?;
+
+        let grace_periods = parse_signal_map(&self.eviction_soft_grace_period, '=')?
+            .into_iter()
+            .map(|(signal, duration)| parse_duration(&duration).map(|duration| (signal, duration)))
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        let mut thresholds = Vec::new();
+        for (flag, is_soft) in [(&self.eviction_hard, false), (&self.eviction_soft, true)] {
+            for (signal, value) in parse_signal_map(flag, '<')? {
+                let Some(value) = parse_threshold_value(&value)? else {
+                    continue;
+                };
+
+                let grace_period = if is_soft {
+                    grace_periods
+                        .get(&signal)
+                        .copied()
+                        .ok_or(KubeletError::MissingGracePeriod(signal))?
+                } else {
+                    Duration::ZERO
+                };
+
+                thresholds.push(EvictionThreshold {
+                    signal,
+                    operator: ThresholdOperator::LessThan,
+                    value,
+                    grace_period,
+                    min_reclaim: min_reclaims.get(&signal).cloned(),
+                });
+            }
+        }
+
+        Ok(thresholds)
+    }
+
+    /// Formats thresholds back into the flag syntax, with entries sorted by
+    /// signal.
+    pub fn from_thresholds(thresholds: &[EvictionThreshold]) -> Self {
+        let mut hard = BTreeMap::new();
+        let mut soft = BTreeMap::new();
+        let mut grace_periods = BTreeMap::new();
+        let mut min_reclaims = BTreeMap::new();
+
+        for threshold in thresholds {
+            let signal = threshold.signal.as_str();
+            if threshold.is_hard() {
+                hard.insert(signal, threshold.value.to_string());
+            } else {
+                soft.insert(signal, threshold.value.to_string());
+                grace_periods.insert(signal, format_duration(threshold.grace_period));
+            }
+            if let Some(min_reclaim) = &threshold.min_reclaim {
+                min_reclaims.insert(signal, min_reclaim.to_string());
+            }
+        }
+
+        Self {
+            eviction_hard: format_map(&hard, '<'),
+            eviction_soft: format_map(&soft, '<'),
+            eviction_soft_grace_period: format_map(&grace_periods, '='),
+            eviction_minimum_reclaim: format_map(&min_reclaims, '='),
+        }
+    }
+}
+
+/// Parses the value of a threshold statement, where `0%` and `100%` disable
+/// the threshold.
+fn parse_threshold_value(value: &str) -> Result<Option<ThresholdValue>, KubeletError> {
+    if value == "0%" || value == "100%" {
+        return Ok(None);
+    }
+
+    let threshold: ThresholdValue = value.parse()?;
+    if !threshold.is_positive() {
+        return Err(KubeletError::InvalidThreshold(value.to_owned()));
+    }
+
+    Ok(Some(threshold))
+}
+
+fn parse_signal_map(
+    flag: &str,
+    separator: char,
+) -> Result<BTreeMap<EvictionSignal, String>, KubeletError> {
+    parse_map(flag, separator)?
+        .into_iter()
+        .map(|(signal, value)| Ok((signal.parse()?, value)))
+        .collect()
+}
+
+/// Parses comma separated `key<separator>value` pairs, ignoring whitespace
+/// around entries.
+fn parse_map(flag: &str, separator: char) -> Result<BTreeMap<String, String>, KubeletError> {
+    flag.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once(separator)
+                .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+                .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+                .ok_or_else(|| KubeletError::InvalidFlag(entry.to_owned()))
+        })
+        .collect()
+}
+
+fn format_map<K: Display, V: Display>(map: &BTreeMap<K, V>, separator: char) -> String {
+    map.iter()
+        .map(|(key, value)| format!("{key}{separator}{value}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// --- Durations ---
+
+const DURATION_UNITS: [(&str, u64); 8] = [
+    ("ns", 1),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("μs", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+];
+
+/// Parses a non-negative Go duration such as `1m30s` or `1.5h`.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kube_quantity::kubelet::{format_duration, parse_duration};
+///
+/// assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+/// assert_eq!(format_duration(Duration::from_secs(3600)), "1h0m0s");
+/// ```
+pub fn parse_duration(input: &str) -> Result<Duration, KubeletError> {
+    let invalid = || KubeletError::InvalidDuration(input.to_owned());
+    let value = input.strip_prefix('+').unwrap_or(input);
+
+    if value == "0" {
+        return Ok(Duration::ZERO);
+    }
+    if value.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut nanos = Decimal::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        let number: Decimal = rest[..number_end].parse().map_err(|_| invalid())?;
+        rest = &rest[number_end..];
+
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let (_, multiplier) = DURATION_UNITS
+            .iter()
+            .find(|(unit, _)| *unit == &rest[..unit_end])
+            .ok_or_else(invalid)?;
+        rest = &rest[unit_end..];
+
+        nanos = number
+            .checked_mul(Decimal::from(*multiplier))
+            .and_then(|value| nanos.checked_add(value))
+            .ok_or_else(invalid)?;
+    }
+
+    nanos
+        .trunc()
+        .to_u64()
+        .map(Duration::from_nanos)
+        .ok_or_else(invalid)
+}
+
+/// Formats a duration like Go's `Duration.String()`, e.g., `1m30s`.
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_owned();
+    }
+
+    let fraction = |value: u128, unit: u128| {
+        let whole = value / unit;
+        let remainder = value % unit;
+        if remainder == 0 {
+            return whole.to_string();
+        }
+
+        let digits = unit.ilog10() as usize;
+        let fraction = format!("{remainder:0digits$}");
+        format!("{whole}.{}", fraction.trim_end_matches('0'))
+    };
+
+    if nanos < 1_000 {
+        return format!("{nanos}ns");
+    }
+    if nanos < 1_000_000 {
+        return format!("{}µs", fraction(nanos, 1_000));
+    }
+    if nanos < 1_000_000_000 {
+        return format!("{}ms", fraction(nanos, 1_000_000));
+    }
+
+    let seconds = nanos / 1_000_000_000;
+    let (hours, minutes) = (seconds / 3600, seconds / 60 % 60);
+    let seconds = fraction(nanos % 60_000_000_000, 1_000_000_000);
+
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, minutes) => format!("{minutes}m{seconds}s"),
+        (hours, minutes) => format!("{hours}h{minutes}m{seconds}s"),
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::list, Rounding};
+
+    fn thresholds(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(signal, value)| (signal.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_node_allocatable() {
+        let capacity = list(&[
+            ("cpu", "4"),
+            ("memory", "16Gi"),
+            ("ephemeral-storage", "100Gi"),
+            ("hugepages-2Mi", "1Gi"),
+            ("pid", "4096"),
+            ("pods", "110"),
+        ]);
+        let reservation = NodeReservation {
+            kube_reserved: list(&[
+                ("cpu", "100m"),
+                ("memory", "1Gi"),
+                ("ephemeral-storage", "1Gi"),
+                ("pid", "1000"),
+            ]),
+            system_reserved: list(&[("cpu", "100m"), ("memory", "500Mi")]),
+            eviction_hard: thresholds(&[
+                ("memory.available", "100Mi"),
+                ("nodefs.available", "10%"),
+                ("imagefs.available", "15%"),
+            ]),
+        };
+
+        let allocatable = node_allocatable(&capacity, &reservation).unwrap();
+        let canonical = |resource: &str| allocatable[resource].to_canonical_string();
+
+        assert_eq!(canonical("cpu"), "3800m");
+        assert_eq!(canonical("memory"), "13736Mi");
+        // 10% in single precision is slightly more than 10 GiB
+        assert_eq!(
+            allocatable["ephemeral-storage"].to_bytes_u64(),
+            Some(95_563_022_176)
+        );
+        assert_eq!(canonical("hugepages-2Mi"), "1Gi");
+        assert_eq!(canonical("pid"), "3096");
+        assert_eq!(canonical("pods"), "110");
+    }
+
+    #[test]
+    fn test_allocatable_clamps_at_zero() {
+        let capacity = list(&[("cpu", "1"), ("memory", "1Gi"), ("hugepages-1Gi", "2Gi")]);
+        let reservation = NodeReservation {
+            kube_reserved: list(&[("cpu", "1500m"), ("gpu", "1")]),
+            ..Default::default()
+        };
+
+        let allocatable = node_allocatable(&capacity, &reservation).unwrap();
+
+        assert_eq!(allocatable["cpu"].to_canonical_string(), "0");
+        assert_eq!(allocatable["memory"].to_canonical_string(), "0");
+        assert!(!allocatable.contains_key("gpu"));
+    }
+
+    #[test]
+    fn test_invalid_thresholds() {
+        let capacity = list(&[("memory", "1Gi")]);
+
+        for threshold in ["110%", "-1%", "ten%", "1Gb"] {
+            let reservation = NodeReservation {
+                eviction_hard: thresholds(&[("memory.available", threshold)]),
+                ..Default::default()
+            };
+            assert!(
+                node_allocatable(&capacity, &reservation).is_err(),
+                "{threshold}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_threshold_value_resolution() {
+        let capacity: ParsedQuantity = "10Gi".try_into().unwrap();
+        let threshold: ThresholdValue = "5%".parse().unwrap();
+        let exact = threshold
+            .as_quantity_or_percent()
+            .resolve(&capacity, Rounding::Down);
+
+        assert_eq!(exact.to_bytes_u64(), Some(536_870_912));
+        assert_eq!(
+            threshold.resolve(&capacity).to_bytes_u64(),
+            Some(536_870_920)
+        );
+        assert_eq!(threshold.to_string(), "5%");
+        assert_eq!(
+            "100Mi"
+                .parse::<ThresholdValue>()
+                .unwrap()
+                .resolve(&capacity),
+            "100Mi".try_into().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resource_map_round_trip() {
+        let flag = "cpu=100m,ephemeral-storage=1Gi,memory=1Gi,pid=1000";
+        let reserved = parse_resource_map(flag).unwrap();
+
+        assert_eq!(reserved["pid"].to_string(), "1000");
+        assert_eq!(format_resource_map(&reserved), flag);
+        assert!(parse_resource_map("").unwrap().is_empty());
+
+        for flag in ["cpu", "cpu=", "=1", "cpu=1Gb"] {
+            assert!(parse_resource_map(flag).is_err(), "{flag}");
+        }
+    }
+
+    #[test]
+    fn test_eviction_flags_round_trip() {
+        let flags = EvictionFlags {
+            eviction_hard: "imagefs.available<15%,memory.available<100Mi,nodefs.inodesFree<5%"
+                .to_owned(),
+            eviction_soft: "memory.available<1.5Gi,nodefs.available<12.5%".to_owned(),
+            eviction_soft_grace_period: "memory.available=1m30s,nodefs.available=1h0m0s".to_owned(),
+            eviction_minimum_reclaim: "imagefs.available=2Gi,memory.available=0Mi".to_owned(),
+        };
+
+        let thresholds = flags.parse().unwrap();
+        let memory: Vec<_> = thresholds
+            .iter()
+            .filter(|threshold| threshold.signal == EvictionSignal::MemoryAvailable)
+            .collect();
+
+        assert_eq!(thresholds.len(), 5);
+        assert_eq!(memory.len(), 2);
+        assert!(memory[0].is_hard());
+        assert_eq!(memory[1].grace_period, Duration::from_secs(90));
+        assert_eq!(memory[1].min_reclaim, Some("0".parse().unwrap()));
+        assert_eq!(
+            thresholds[4].value.as_quantity_or_percent(),
+            &QuantityOrPercent::Percent(Decimal::new(125, 1))
+        );
+
+        assert_eq!(EvictionFlags::from_thresholds(&thresholds), flags);
+    }
+
+    #[test]
+    fn test_eviction_flag_validation() {
+        let parse = |hard: &str, soft: &str, grace_period: &str| {
+            EvictionFlags {
+                eviction_hard: hard.to_owned(),
+                eviction_soft: soft.to_owned(),
+                eviction_soft_grace_period: grace_period.to_owned(),
+                ..Default::default()
+            }
+            .parse()
+        };
+
+        // 0% and 100% disable a threshold
+        assert!(parse("memory.available<0%,nodefs.available<100%", "", "")
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            parse("memory.available<0", "", ""),
+            Err(KubeletError::InvalidThreshold(_))
+        ));
+        assert!(matches!(
+            parse("memory.available<101%", "", ""),
+            Err(KubeletError::InvalidThreshold(_))
+        ));
+        assert!(matches!(
+            parse("swap.available<1Gi", "", ""),
+            Err(KubeletError::UnsupportedSignal(_))
+        ));
+        assert!(matches!(
+            parse("memory.available>1Gi", "", ""),
+            Err(KubeletError::InvalidFlag(_))
+        ));
+        assert!(matches!(
+            parse("", "memory.available<1Gi", "nodefs.available=1m"),
+            Err(KubeletError::MissingGracePeriod(
+                EvictionSignal::MemoryAvailable
+            ))
+        ));
+        assert!(matches!(
+            parse("", "memory.available<1Gi", "memory.available=1y"),
+            Err(KubeletError::InvalidDuration(_))
+        ));
+
+        // A zero grace period is accepted and makes the threshold act as hard
+        let thresholds = parse("", "memory.available<1Gi", "memory.available=0s").unwrap();
+        assert_eq!(thresholds[0].operator, ThresholdOperator::LessThan);
+        assert_eq!(thresholds[0].grace_period, Duration::ZERO);
+        assert!(thresholds[0].is_hard());
+    }
+
+    #[test]
+    fn test_durations() {
+        for (input, expected) in [
+            ("0", Duration::ZERO),
+            ("300ms", Duration::from_millis(300)),
+            ("1.5h", Duration::from_secs(5400)),
+            ("2h45m", Duration::from_secs(9900)),
+            ("1m0.5s", Duration::from_millis(60_500)),
+            ("10us", Duration::from_micros(10)),
+        ] {
+            assert_eq!(parse_duration(input).unwrap(), expected, "{input}");
+        }
+        for input in [
+            "",
+            "1",
+            "-1s",
+            "1d",
+            "s",
+            "1.2.3s",
+            "99999999999999999999h",
+            "20000000000000000h20000000000000000h",
+        ] {
+            assert!(parse_duration(input).is_err(), "{input}");
+        }
+
+        for (duration, expected) in [
+            (Duration::ZERO, "0s"),
+            (Duration::from_nanos(1), "1ns"),
+            (Duration::from_micros(1500), "1.5ms"),
+            (Duration::from_millis(1500), "1.5s"),
+            (Duration::from_secs(90), "1m30s"),
+            (Duration::from_millis(3_600_001), "1h0m0.001s"),
+        ] {
+            assert_eq!(format_duration(duration), expected);
+        }
+    }
+}
@@ -1,17 +1,48 @@
 #![forbid(unsafe_code)]
 #![doc = include_str!("../README.md")]
 
+pub mod accounting;
+pub mod cel;
+pub mod cgroup;
+mod compat;
+pub mod downward_api;
+pub mod eviction;
+pub mod exporter;
+pub mod footprint;
 mod format;
+pub mod hpa;
+pub mod instances;
+pub mod kubelet;
+mod limit;
+pub mod limit_range;
+pub mod memory_qos;
+#[cfg(feature = "serde")]
+pub mod metrics;
+pub mod oci;
+pub mod oom;
 mod parser;
+mod percent;
+pub mod planner;
 mod quantity;
+pub mod quota;
+pub mod recommender;
+pub mod resources;
+pub mod rightsizing;
+pub mod runtime;
 mod scale;
+mod split;
+#[cfg(test)]
+pub(crate) mod test_util;
 mod utils;
 
 use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use parser::parse_quantity_string;
 
+pub use limit::Limit;
 pub use parser::ParseQuantityError;
+pub use percent::{QuantityOrPercent, Rounding};
 pub use quantity::ParsedQuantity;
+pub use split::SplitError;
 
 impl TryFrom<Quantity> for ParsedQuantity {
     type Error = ParseQuantityError;
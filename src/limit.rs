@@ -0,0 +1,326 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign, Sub},
+};
+
+use k8s_openapi::{
+    api::core::v1::ResourceRequirements, apimachinery::pkg::api::resource::Quantity,
+};
+
+use crate::{resources::exact_add, ParseQuantityError, ParsedQuantity};
+
+// - Limit -
+
+/// Limit is a quantity that may be unlimited, e.g., a missing container limit
+/// or a cgroup set to `max`. Unlimited compares greater than any finite
+/// quantity and absorbs any finite quantity in arithmetic.
+///
+/// ```rust
+/// use kube_quantity::{Limit, ParsedQuantity};
+///
+/// let one: ParsedQuantity = "1Gi".try_into().unwrap();
+/// let two: ParsedQuantity = "2Gi".try_into().unwrap();
+///
+/// assert!(Limit::Limited(two.clone()) < Limit::Unlimited);
+/// assert_eq!(Limit::Limited(one.clone()) + Limit::Limited(one.clone()), Limit::Limited(two));
+/// assert_eq!(Limit::Limited(one.clone()) + Limit::Unlimited, Limit::Unlimited);
+/// assert_eq!(Limit::Unlimited.min(Limit::Limited(one.clone())), Limit::Limited(one));
+/// ```
+#[derive(Debug, Clone)]
+pub enum Limit {
+    /// A finite limit
+    Limited(ParsedQuantity),
+    /// No limit is set
+    Unlimited,
+}
+
+impl Limit {
+    /// Returns the limit of a resource in the `limits` of the requirements,
+    /// where a missing limit is unlimited.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// use k8s_openapi::api::core::v1::ResourceRequirements;
+    /// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+    /// use kube_quantity::Limit;
+    ///
+    /// let requirements = ResourceRequirements {
+    ///     limits: Some(BTreeMap::from([("cpu".to_string(), Quantity("500m".to_string()))])),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(Limit::from_requirements(&requirements, "cpu").unwrap().to_string(), "500m");
+    /// assert!(Limit::from_requirements(&requirements, "memory").unwrap().is_unlimited());
+    /// ```
+    pub fn from_requirements(
+        requirements: &ResourceRequirements,
+        resource: &str,
+    ) -> Result<Self, ParseQuantityError> {
+        requirements
+            .limits
+            .as_ref()
+            .and_then(|limits| limits.get(resource))
+            .try_into()
+    }
+
+    /// Returns whether no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        matches!(self, Limit::Unlimited)
+    }
+
+    /// Returns the finite limit, if any.
+    pub fn as_limited(&self) -> Option<&ParsedQuantity> {
+        match self {
+            Limit::Limited(quantity) => Some(quantity),
+            Limit::Unlimited => None,
+        }
+    }
+
+    /// Returns the finite limit, if any.
+    pub fn into_limited(self) -> Option<ParsedQuantity> {
+        match self {
+            Limit::Limited(quantity) => Some(quantity),
+            Limit::Unlimited => None,
+        }
+    }
+
+    /// Returns whether the quantity does not exceed the limit.
+    pub fn allows(&self, quantity: &ParsedQuantity) -> bool {
+        match self {
+            Limit::Limited(limit) => quantity.to_base_decimal() <= limit.to_base_decimal(),
+            Limit::Unlimited => true,
+        }
+    }
+}
+
+impl From<ParsedQuantity> for Limit {
+    fn from(value: ParsedQuantity) -> Self {
+        Limit::Limited(value)
+    }
+}
+
+impl From<Option<ParsedQuantity>> for Limit {
+    fn from(value: Option<ParsedQuantity>) -> Self {
+        value.map_or(Limit::Unlimited, Limit::Limited)
+    }
+}
+
+impl From<Option<&ParsedQuantity>> for Limit {
+    fn from(value: Option<&ParsedQuantity>) -> Self {
+        value.cloned().into()
+    }
+}
+
+impl TryFrom<Option<&Quantity>> for Limit {
+    type Error = ParseQuantityError;
+
+    fn try_from(value: Option<&Quantity>) -> Result<Self, Self::Error> {
+        value
+            .map(ParsedQuantity::try_from)
+            .transpose()
+            .map(Limit::from)
+    }
+}
+
+impl From<Limit> for Option<ParsedQuantity> {
+    fn from(value: Limit) -> Self {
+        value.into_limited()
+    }
+}
+
+/// Limits are compared by their exact value, independent of their suffix.
+impl PartialEq for Limit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Limit {}
+
+impl PartialOrd for Limit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Limit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Limit::Limited(lhs), Limit::Limited(rhs)) => {
+                lhs.to_base_decimal().cmp(&rhs.to_base_decimal())
+            }
+            (Limit::Limited(_), Limit::Unlimited) => Ordering::Less,
+            (Limit::Unlimited, Limit::Limited(_)) => Ordering::Greater,
+            (Limit::Unlimited, Limit::Unlimited) => Ordering::Equal,
+        }
+    }
+}
+
+impl PartialEq<ParsedQuantity> for Limit {
+    fn eq(&self, other: &ParsedQuantity) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd<ParsedQuantity> for Limit {
+    fn partial_cmp(&self, other: &ParsedQuantity) -> Option<Ordering> {
+        Some(match self {
+            Limit::Limited(limit) => limit.to_base_decimal().cmp(&other.to_base_decimal()),
+            Limit::Unlimited => Ordering::Greater,
+        })
+    }
+}
+
+impl Add for Limit {
+    type Output = Limit;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        match (self, rhs) {
+            (Limit::Limited(lhs), Limit::Limited(rhs)) => Limit::Limited(exact_add(&lhs, &rhs)),
+            _ => Limit::Unlimited,
+        }
+    }
+}
+
+impl Add<ParsedQuantity> for Limit {
+    type Output = Limit;
+
+    fn add(self, rhs: ParsedQuantity) -> Self::Output {
+        self + Limit::Limited(rhs)
+    }
+}
+
+impl AddAssign for Limit {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = std::mem::replace(self, Limit::Unlimited) + rhs;
+    }
+}
+
+impl Sub<ParsedQuantity> for Limit {
+    type Output = Limit;
+
+    fn sub(self, rhs: ParsedQuantity) -> Self::Output {
+        match self {
+            Limit::Limited(lhs) => Limit::Limited(ParsedQuantity::from_base_decimal(
+                lhs.to_base_decimal() - rhs.to_base_decimal(),
+                lhs.format().clone(),
+            )),
+            Limit::Unlimited => Limit::Unlimited,
+        }
+    }
+}
+
+/// The sum of no limits is a zero limit.
+impl Sum for Limit {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Limit::Limited(ParsedQuantity::default()), Add::add)
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Limited(quantity) => write!(f, "{}", quantity),
+            Limit::Unlimited => write!(f, "unlimited"),
+        }
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn limited(value: &str) -> Limit {
+        Limit::Limited(value.try_into().unwrap())
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut limits = [
+            Limit::Unlimited,
+            limited("1Gi"),
+            limited("1024Mi"),
+            limited("500M"),
+        ];
+        limits.sort();
+
+        assert_eq!(
+            limits.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["500M", "1Gi", "1024Mi", "unlimited"]
+        );
+        assert_eq!(limited("1Gi"), limited("1024Mi"));
+        assert_eq!(Limit::Unlimited, Limit::Unlimited);
+        assert_eq!(limited("1").max(Limit::Unlimited), Limit::Unlimited);
+        assert_eq!(limited("1").min(limited("999m")), limited("999m"));
+
+        let quantity: ParsedQuantity = "2".try_into().unwrap();
+        assert!(limited("1") < quantity);
+        assert!(Limit::Unlimited > quantity);
+        assert!(limited("2000m") == quantity);
+        assert!(limited("2").allows(&quantity));
+        assert!(!limited("1999m").allows(&quantity));
+        assert!(Limit::Unlimited.allows(&quantity));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(limited("1Gi") + limited("512Mi"), limited("1.5Gi"));
+        assert_eq!(limited("1Gi") + Limit::Unlimited, Limit::Unlimited);
+        assert_eq!(Limit::Unlimited + limited("1Gi"), Limit::Unlimited);
+        assert_eq!(
+            limited("100m") - "300m".try_into().unwrap(),
+            limited("-200m")
+        );
+        assert_eq!(
+            Limit::Unlimited - "300m".try_into().unwrap(),
+            Limit::Unlimited
+        );
+
+        let mut total = limited("100m");
+        total += limited("0.2");
+        assert_eq!(total, limited("300m"));
+
+        assert_eq!(
+            vec![limited("1"), limited("2")].into_iter().sum::<Limit>(),
+            limited("3")
+        );
+        assert_eq!(
+            vec![limited("1"), Limit::Unlimited]
+                .into_iter()
+                .sum::<Limit>(),
+            Limit::Unlimited
+        );
+        assert_eq!(Vec::<Limit>::new().into_iter().sum::<Limit>(), limited("0"));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let requirements = ResourceRequirements {
+            limits: Some(BTreeMap::from([
+                ("memory".to_owned(), Quantity("1Gi".to_owned())),
+                ("cpu".to_owned(), Quantity("1.2.3".to_owned())),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Limit::from_requirements(&requirements, "memory").unwrap(),
+            limited("1Gi")
+        );
+        assert!(Limit::from_requirements(&requirements, "cpu").is_err());
+        assert!(
+            Limit::from_requirements(&ResourceRequirements::default(), "cpu")
+                .unwrap()
+                .is_unlimited()
+        );
+
+        assert_eq!(Option::<ParsedQuantity>::from(Limit::Unlimited), None);
+    }
+}
@@ -0,0 +1,748 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{
+    Container, LimitRange, LimitRangeItem, PersistentVolumeClaimSpec, PodSpec,
+};
+use rust_decimal::prelude::*;
+
+use crate::{
+    resources::{
+        parse_optional_list, pod_container_limits, pod_container_requests, ParsedResourceList,
+    },
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Limit type constraining single containers
+pub const LIMIT_TYPE_CONTAINER: &str = "Container";
+/// Limit type constraining the sum over all containers of a pod
+pub const LIMIT_TYPE_POD: &str = "Pod";
+/// Limit type constraining persistent volume claims
+pub const LIMIT_TYPE_PERSISTENT_VOLUME_CLAIM: &str = "PersistentVolumeClaim";
+
+/// Annotation the LimitRanger admission plugin records defaulted resources in
+pub const LIMIT_RANGER_ANNOTATION: &str = "kubernetes.io/limit-ranger";
+
+/// Largest value that can be compared in milli units without overflowing an
+/// int64, i.e., `resource.MaxMilliValue`
+const MAX_MILLI_VALUE: i64 = i64::MAX / 1000;
+
+// --- Evaluation ---
+
+/// Violation of a LimitRange constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Type of the violated limit, e.g., `Container`
+    pub limit_type: String,
+    /// Name of the constrained resource
+    pub resource: String,
+    /// Name of the violating container for `Container` limits
+    pub container: Option<String>,
+    /// Message as reported by the API server
+    pub message: String,
+}
+
+/// Result of evaluating a LimitRange against a resource.
+#[derive(Debug, Clone)]
+pub struct LimitRangeEvaluation<T> {
+    /// Resource after applying the defaults of the LimitRange
+    pub resource: T,
+    /// Value of the `kubernetes.io/limit-ranger` annotation, if any default
+    /// was applied
+    pub annotation: Option<String>,
+    /// Violated constraints, in the order the API server reports them
+    pub violations: Vec<Violation>,
+}
+
+impl<T> LimitRangeEvaluation<T> {
+    /// Returns whether the resource would be admitted.
+    pub fn is_allowed(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the aggregated message of all violations the way the API
+    /// server prints it, i.e., a single message as is and multiple ones as a
+    /// bracketed list.
+    pub fn message(&self) -> Option<String> {
+        match self.violations.as_slice() {
+            [] => None,
+            [violation] => Some(violation.message.clone()),
+            violations => Some(format!(
+                "[{}]",
+                violations
+                    .iter()
+                    .map(|violation| violation.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Applies the container defaults of a LimitRange to a pod and validates it
+/// against the `Container` and `Pod` limits, like the LimitRanger admission
+/// plugin does.
+///
+/// The pod and LimitRange are defaulted the way the API server would before
+/// admission, e.g., requests default to limits.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, LimitRange, LimitRangeItem, LimitRangeSpec, PodSpec};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::limit_range::evaluate_pod;
+///
+/// let list = |cpu: &str| Some(BTreeMap::from([("cpu".to_string(), Quantity(cpu.to_string()))]));
+///
+/// let limit_range = LimitRange {
+///     spec: Some(LimitRangeSpec {
+///         limits: vec![LimitRangeItem {
+///             type_: "Container".to_string(),
+///             default: list("500m"),
+///             max: list("1"),
+///             ..Default::default()
+///         }],
+///     }),
+///     ..Default::default()
+/// };
+/// let spec = PodSpec {
+///     containers: vec![Container { name: "app".to_string(), ..Default::default() }],
+///     ..Default::default()
+/// };
+///
+/// let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+/// let resources = evaluation.resource.containers[0].resources.as_ref().unwrap();
+///
+/// assert!(evaluation.is_allowed());
+/// assert_eq!(resources.limits.as_ref().unwrap()["cpu"].0, "500m");
+/// assert_eq!(resources.requests.as_ref().unwrap()["cpu"].0, "500m");
+/// assert_eq!(
+///     evaluation.annotation.as_deref(),
+///     Some("LimitRanger plugin set: cpu request for container app; cpu limit for container app"),
+/// );
+/// ```
+pub fn evaluate_pod(
+    limit_range: &LimitRange,
+    spec: &PodSpec,
+) -> Result<LimitRangeEvaluation<PodSpec>, ParseQuantityError> {
+    let items = limit_items(limit_range);
+    let mut spec = spec.clone();
+
+    // Requests default to limits when the pod is created
+    for container in containers_mut(&mut spec) {
+        if let Some(resources) = &mut container.resources {
+            if let Some(limits) = &resources.limits {
+                let requests = resources.requests.get_or_insert_with(BTreeMap::new);
+                for (resource, quantity) in limits {
+                    requests
+                        .entry(resource.clone())
+                        .or_insert_with(|| quantity.clone());
+                }
+            }
+        }
+    }
+
+    let annotation = apply_container_defaults(&items, &mut spec);
+
+    let mut violations = Vec::new();
+    for item in &items {
+        match item.type_.as_str() {
+            LIMIT_TYPE_CONTAINER => {
+                let containers = spec
+                    .containers
+                    .iter()
+                    .chain(spec.init_containers.iter().flatten());
+
+                for container in containers {
+                    let resources = container.resources.as_ref();
+                    let requests =
+                        parse_optional_list(resources.and_then(|r| r.requests.as_ref()))?;
+                    let limits = parse_optional_list(resources.and_then(|r| r.limits.as_ref()))?;
+
+                    violations.extend(validate_item(item, &requests, &limits)?.into_iter().map(
+                        |violation| Violation {
+                            container: Some(container.name.clone()),
+                            ..violation
+                        },
+                    ));
+                }
+            }
+            LIMIT_TYPE_POD => {
+                // Like the LimitRanger, the pod overhead is not counted
+                violations.extend(validate_item(
+                    item,
+                    &pod_container_requests(&spec)?,
+                    &pod_container_limits(&spec)?,
+                )?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(LimitRangeEvaluation {
+        resource: spec,
+        annotation,
+        violations,
+    })
+}
+
+/// Validates a persistent volume claim against the `PersistentVolumeClaim`
+/// limits of a LimitRange. Claims have no defaults, so the spec is returned
+/// unchanged.
+pub fn evaluate_persistent_volume_claim(
+    limit_range: &LimitRange,
+    spec: &PersistentVolumeClaimSpec,
+) -> Result<LimitRangeEvaluation<PersistentVolumeClaimSpec>, ParseQuantityError> {
+    let requests = parse_optional_list(
+        spec.resources
+            .as_ref()
+            .and_then(|resources| resources.requests.as_ref()),
+    )?;
+
+    let mut violations = Vec::new();
+    for item in limit_items(limit_range) {
+        if item.type_ != LIMIT_TYPE_PERSISTENT_VOLUME_CLAIM {
+            continue;
+        }
+
+        for (resource, enforced) in parse_optional_list(item.min.as_ref())? {
+            // Claim limits are not user input, only the requests are checked
+            let message = min_constraint(
+                &item.type_,
+                &resource,
+                &enforced,
+                &requests,
+                &ParsedResourceList::new(),
+            );
+            violations.extend(violation(&item.type_, &resource, message));
+        }
+
+        for (resource, enforced) in parse_optional_list(item.max.as_ref())? {
+            let message = max_request_constraint(&item.type_, &resource, &enforced, &requests);
+            violations.extend(violation(&item.type_, &resource, message));
+        }
+    }
+
+    Ok(LimitRangeEvaluation {
+        resource: spec.clone(),
+        annotation: None,
+        violations,
+    })
+}
+
+// --- Defaulting ---
+
+/// Returns the limit items with the API server defaults applied, i.e.,
+/// container limits default to the max and requests to the default limit or
+/// the min.
+fn limit_items(limit_range: &LimitRange) -> Vec<LimitRangeItem> {
+    let mut items = limit_range
+        .spec
+        .as_ref()
+        .map(|spec| spec.limits.clone())
+        .unwrap_or_default();
+
+    for item in items.iter_mut() {
+        if item.type_ != LIMIT_TYPE_CONTAINER {
+            continue;
+        }
+
+        let default = item.default.get_or_insert_with(BTreeMap::new);
+        for (resource, quantity) in item.max.iter().flatten() {
+            default
+                .entry(resource.clone())
+                .or_insert_with(|| quantity.clone());
+        }
+
+        let default_request = item.default_request.get_or_insert_with(BTreeMap::new);
+        for (resource, quantity) in default.iter().chain(item.min.iter().flatten()) {
+            default_request
+                .entry(resource.clone())
+                .or_insert_with(|| quantity.clone());
+        }
+    }
+
+    items
+}
+
+/// Merges the container defaults into all containers and returns the
+/// annotation describing what was set.
+fn apply_container_defaults(items: &[LimitRangeItem], spec: &mut PodSpec) -> Option<String> {
+    let mut default_requests = BTreeMap::new();
+    let mut default_limits = BTreeMap::new();
+    for item in items
+        .iter()
+        .filter(|item| item.type_ == LIMIT_TYPE_CONTAINER)
+    {
+        default_requests.extend(item.default_request.clone().unwrap_or_default());
+        default_limits.extend(item.default.clone().unwrap_or_default());
+    }
+
+    let mut annotations = Vec::new();
+    let init_containers = spec.init_containers.iter_mut().flatten();
+    let containers = spec
+        .containers
+        .iter_mut()
+        .map(|container| ("container", container))
+        .chain(init_containers.map(|container| ("init container", container)));
+
+    for (prefix, container) in containers {
+        let resources = container.resources.get_or_insert_with(Default::default);
+
+        for (kind, defaults, list) in [
+            ("request", &default_requests, &mut resources.requests),
+            ("limit", &default_limits, &mut resources.limits),
+        ] {
+            let mut set: Vec<&str> = Vec::new();
+            for (resource, quantity) in defaults {
+                let list = list.get_or_insert_with(BTreeMap::new);
+                if !list.contains_key(resource) {
+                    list.insert(resource.clone(), quantity.clone());
+                    set.push(resource);
+                }
+            }
+
+            if !set.is_empty() {
+                annotations.push(format!(
+                    "{} {kind} for {prefix} {}",
+                    set.join(", "),
+                    container.name
+                ));
+            }
+        }
+
+        if resources.requests.as_ref().is_some_and(BTreeMap::is_empty) {
+            resources.requests = None;
+        }
+        if resources.limits.as_ref().is_some_and(BTreeMap::is_empty) {
+            resources.limits = None;
+        }
+    }
+
+    (!annotations.is_empty()).then(|| format!("LimitRanger plugin set: {}", annotations.join("; ")))
+}
+
+fn containers_mut(spec: &mut PodSpec) -> impl Iterator<Item = &mut Container> {
+    spec.containers
+        .iter_mut()
+        .chain(spec.init_containers.iter_mut().flatten())
+}
+
+// --- Validation ---
+
+fn validate_item(
+    item: &LimitRangeItem,
+    requests: &ParsedResourceList,
+    limits: &ParsedResourceList,
+) -> Result<Vec<Violation>, ParseQuantityError> {
+    let mut violations = Vec::new();
+    let limit_type = &item.type_;
+
+    for (resource, enforced) in parse_optional_list(item.min.as_ref())? {
+        let message = min_constraint(limit_type, &resource, &enforced, requests, limits);
+        violations.extend(violation(limit_type, &resource, message));
+    }
+    for (resource, enforced) in parse_optional_list(item.max.as_ref())? {
+        let message = max_constraint(limit_type, &resource, &enforced, requests, limits);
+        violations.extend(violation(limit_type, &resource, message));
+    }
+    for (resource, enforced) in parse_optional_list(item.max_limit_request_ratio.as_ref())? {
+        let message = ratio_constraint(limit_type, &resource, &enforced, requests, limits);
+        violations.extend(violation(limit_type, &resource, message));
+    }
+
+    Ok(violations)
+}
+
+fn violation(limit_type: &str, resource: &str, message: Option<String>) -> Option<Violation> {
+    message.map(|message| Violation {
+        limit_type: limit_type.to_owned(),
+        resource: resource.to_owned(),
+        container: None,
+        message,
+    })
+}
+
+fn min_constraint(
+    limit_type: &str,
+    resource: &str,
+    enforced: &ParsedQuantity,
+    requests: &ParsedResourceList,
+    limits: &ParsedResourceList,
+) -> Option<String> {
+    let request = requests.get(resource);
+    let limit = limits.get(resource);
+    let (observed_request, observed_limit, enforced_value) =
+        enforced_values(request, limit, enforced);
+    let enforced = enforced.to_canonical_string();
+
+    match (request, limit) {
+        (None, _) => Some(format!(
+            "minimum {resource} usage per {limit_type} is {enforced}.  No request is specified"
+        )),
+        (Some(request), _) if observed_request < enforced_value => Some(format!(
+            "minimum {resource} usage per {limit_type} is {enforced}, but request is {}",
+            request.to_canonical_string()
+        )),
+        (_, Some(limit)) if observed_limit < enforced_value => Some(format!(
+            "minimum {resource} usage per {limit_type} is {enforced}, but limit is {}",
+            limit.to_canonical_string()
+        )),
+        _ => None,
+    }
+}
+
+fn max_constraint(
+    limit_type: &str,
+    resource: &str,
+    enforced: &ParsedQuantity,
+    requests: &ParsedResourceList,
+    limits: &ParsedResourceList,
+) -> Option<String> {
+    let request = requests.get(resource);
+    let limit = limits.get(resource);
+    let (observed_request, observed_limit, enforced_value) =
+        enforced_values(request, limit, enforced);
+    let enforced = enforced.to_canonical_string();
+
+    match (request, limit) {
+        (_, None) => Some(format!(
+            "maximum {resource} usage per {limit_type} is {enforced}.  No limit is specified"
+        )),
+        (_, Some(limit)) if observed_limit > enforced_value => Some(format!(
+            "maximum {resource} usage per {limit_type} is {enforced}, but limit is {}",
+            limit.to_canonical_string()
+        )),
+        (Some(request), _) if observed_request > enforced_value => Some(format!(
+            "maximum {resource} usage per {limit_type} is {enforced}, but request is {}",
+            request.to_canonical_string()
+        )),
+        _ => None,
+    }
+}
+
+fn max_request_constraint(
+    limit_type: &str,
+    resource: &str,
+    enforced: &ParsedQuantity,
+    requests: &ParsedResourceList,
+) -> Option<String> {
+    let request = requests.get(resource);
+    let (observed_request, _, enforced_value) = enforced_values(request, None, enforced);
+    let enforced = enforced.to_canonical_string();
+
+    match request {
+        None => Some(format!(
+            "maximum {resource} usage per {limit_type} is {enforced}.  No request is specified"
+        )),
+        Some(request) if observed_request > enforced_value => Some(format!(
+            "maximum {resource} usage per {limit_type} is {enforced}, but request is {}",
+            request.to_canonical_string()
+        )),
+        _ => None,
+    }
+}
+
+fn ratio_constraint(
+    limit_type: &str,
+    resource: &str,
+    enforced: &ParsedQuantity,
+    requests: &ParsedResourceList,
+    limits: &ParsedResourceList,
+) -> Option<String> {
+    let request = requests.get(resource);
+    let limit = limits.get(resource);
+    let (observed_request, observed_limit, _) = enforced_values(request, limit, enforced);
+    let enforced_string = enforced.to_canonical_string();
+
+    if request.is_none() || observed_request == 0 {
+        return Some(format!(
+            "{resource} max limit to request ratio per {limit_type} is {enforced_string}, but no request is specified or request is 0"
+        ));
+    }
+    if limit.is_none() || observed_limit == 0 {
+        return Some(format!(
+            "{resource} max limit to request ratio per {limit_type} is {enforced_string}, but no limit is specified or limit is 0"
+        ));
+    }
+
+    // Mirrors the float comparison of the API server
+    let display_ratio = observed_limit as f64 / observed_request as f64;
+    let mut observed_ratio = display_ratio;
+    let mut max_ratio = value(enforced) as f64;
+    if value(enforced) <= MAX_MILLI_VALUE {
+        observed_ratio *= 1000.0;
+        max_ratio = milli_value(enforced) as f64;
+    }
+
+    (observed_ratio > max_ratio).then(|| {
+        format!(
+            "{resource} max limit to request ratio per {limit_type} is {enforced_string}, but provided ratio is {display_ratio:.6}"
+        )
+    })
+}
+
+/// Returns the request, limit and enforced values in milli units, or in units
+/// if any of them would overflow, with missing values being zero.
+fn enforced_values(
+    request: Option<&ParsedQuantity>,
+    limit: Option<&ParsedQuantity>,
+    enforced: &ParsedQuantity,
+) -> (i64, i64, i64) {
+    let values = [request, limit, Some(enforced)].map(|quantity| quantity.map(value));
+
+    if values
+        .iter()
+        .all(|value| value.unwrap_or_default() <= MAX_MILLI_VALUE)
+    {
+        let [request, limit, enforced] =
+            [request, limit, Some(enforced)].map(|quantity| quantity.map(milli_value));
+        return (
+            request.unwrap_or_default(),
+            limit.unwrap_or_default(),
+            enforced.unwrap_or_default(),
+        );
+    }
+
+    let [request, limit, enforced] = values;
+    (
+        request.unwrap_or_default(),
+        limit.unwrap_or_default(),
+        enforced.unwrap_or_default(),
+    )
+}
+
+/// Returns the value rounded up to units, i.e., `Quantity.Value()`.
+fn value(quantity: &ParsedQuantity) -> i64 {
+    round_up(quantity.to_base_decimal())
+}
+
+/// Returns the value rounded up to milli units, i.e., `Quantity.MilliValue()`.
+fn milli_value(quantity: &ParsedQuantity) -> i64 {
+    quantity.to_milli_i64()
+}
+
+fn round_up(value: Decimal) -> i64 {
+    value
+        .round_dp_with_strategy(0, RoundingStrategy::AwayFromZero)
+        .to_i64()
+        .unwrap_or(if value.is_sign_negative() {
+            i64::MIN
+        } else {
+            i64::MAX
+        })
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::{LimitRangeSpec, VolumeResourceRequirements};
+
+    use super::*;
+    use crate::test_util::{container, list};
+
+    fn limit_range(items: Vec<LimitRangeItem>) -> LimitRange {
+        LimitRange {
+            spec: Some(LimitRangeSpec { limits: items }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_defaults_are_merged() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_CONTAINER.to_owned(),
+            default: Some(list(&[("cpu", "1"), ("memory", "512Mi")])),
+            default_request: Some(list(&[("cpu", "100m"), ("memory", "256Mi")])),
+            ..Default::default()
+        }]);
+        let spec = PodSpec {
+            containers: vec![container("app", &[], &[("memory", "1Gi")])],
+            init_containers: Some(vec![container("init", &[("cpu", "50m")], &[])]),
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+        let app = evaluation.resource.containers[0].resources.clone().unwrap();
+        let init = evaluation.resource.init_containers.unwrap()[0]
+            .resources
+            .clone()
+            .unwrap();
+
+        // The memory request defaults to the memory limit of the container
+        assert_eq!(
+            app.requests,
+            Some(list(&[("cpu", "100m"), ("memory", "1Gi")]))
+        );
+        assert_eq!(app.limits, Some(list(&[("cpu", "1"), ("memory", "1Gi")])));
+        assert_eq!(
+            init.requests,
+            Some(list(&[("cpu", "50m"), ("memory", "256Mi")]))
+        );
+        assert_eq!(
+            init.limits,
+            Some(list(&[("cpu", "1"), ("memory", "512Mi")]))
+        );
+        assert_eq!(
+            evaluation.annotation.as_deref(),
+            Some(
+                "LimitRanger plugin set: cpu request for container app; cpu limit for container app; \
+                 memory request for init container init; cpu, memory limit for init container init"
+            )
+        );
+    }
+
+    #[test]
+    fn test_container_min_max() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_CONTAINER.to_owned(),
+            min: Some(list(&[("cpu", "0.1")])),
+            max: Some(list(&[("memory", "1Gi")])),
+            ..Default::default()
+        }]);
+        let spec = PodSpec {
+            containers: vec![
+                container("small", &[("cpu", "50m")], &[("cpu", "1")]),
+                container("large", &[("memory", "1Gi")], &[("memory", "1536Mi")]),
+            ],
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+        let messages: Vec<_> = evaluation
+            .violations
+            .iter()
+            .map(|violation| violation.message.as_str())
+            .collect();
+
+        // The max defaults the memory limit of the small container, while the
+        // min defaults the cpu request of the large one
+        assert_eq!(
+            messages,
+            [
+                "minimum cpu usage per Container is 100m, but request is 50m",
+                "maximum memory usage per Container is 1Gi, but limit is 1536Mi",
+            ]
+        );
+        assert_eq!(evaluation.violations[0].container.as_deref(), Some("small"));
+        assert_eq!(evaluation.violations[1].container.as_deref(), Some("large"));
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "[minimum cpu usage per Container is 100m, but request is 50m, \
+             maximum memory usage per Container is 1Gi, but limit is 1536Mi]"
+        );
+    }
+
+    #[test]
+    fn test_limit_request_ratio() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_CONTAINER.to_owned(),
+            max_limit_request_ratio: Some(list(&[("cpu", "4"), ("memory", "2")])),
+            ..Default::default()
+        }]);
+        let spec = PodSpec {
+            containers: vec![container(
+                "app",
+                &[("cpu", "100m"), ("memory", "1Gi")],
+                &[("cpu", "500m"), ("memory", "2Gi")],
+            )],
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "cpu max limit to request ratio per Container is 4, but provided ratio is 5.000000"
+        );
+    }
+
+    #[test]
+    fn test_limit_request_ratio_without_limit() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_CONTAINER.to_owned(),
+            max_limit_request_ratio: Some(list(&[("cpu", "1500m")])),
+            ..Default::default()
+        }]);
+        let spec = PodSpec {
+            containers: vec![container("app", &[("cpu", "100m")], &[])],
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "cpu max limit to request ratio per Container is 1500m, but no limit is specified or limit is 0"
+        );
+    }
+
+    #[test]
+    fn test_pod_max() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_POD.to_owned(),
+            max: Some(list(&[("cpu", "2")])),
+            ..Default::default()
+        }]);
+        let spec = PodSpec {
+            containers: vec![
+                container("a", &[], &[("cpu", "1500m")]),
+                container("b", &[], &[("cpu", "1")]),
+            ],
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_pod(&limit_range, &spec).unwrap();
+
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "maximum cpu usage per Pod is 2, but limit is 2500m"
+        );
+        assert_eq!(evaluation.violations[0].container, None);
+        assert_eq!(evaluation.annotation, None);
+
+        // The pod overhead is not counted against the maximum
+        let spec = PodSpec {
+            containers: vec![container("a", &[], &[("cpu", "2")])],
+            overhead: Some(list(&[("cpu", "250m")])),
+            ..Default::default()
+        };
+        assert!(evaluate_pod(&limit_range, &spec)
+            .unwrap()
+            .violations
+            .is_empty());
+    }
+
+    #[test]
+    fn test_persistent_volume_claim() {
+        let limit_range = limit_range(vec![LimitRangeItem {
+            type_: LIMIT_TYPE_PERSISTENT_VOLUME_CLAIM.to_owned(),
+            min: Some(list(&[("storage", "1Gi")])),
+            max: Some(list(&[("storage", "10Gi")])),
+            ..Default::default()
+        }]);
+        let claim = |storage: &str| PersistentVolumeClaimSpec {
+            resources: Some(VolumeResourceRequirements {
+                requests: Some(list(&[("storage", storage)])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let evaluation = evaluate_persistent_volume_claim(&limit_range, &claim("5Gi")).unwrap();
+        assert!(evaluation.is_allowed());
+
+        let evaluation = evaluate_persistent_volume_claim(&limit_range, &claim("20Gi")).unwrap();
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "maximum storage usage per PersistentVolumeClaim is 10Gi, but request is 20Gi"
+        );
+
+        let evaluation = evaluate_persistent_volume_claim(&limit_range, &claim("500Mi")).unwrap();
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "minimum storage usage per PersistentVolumeClaim is 1Gi, but request is 500Mi"
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{Container, PodSpec};
+use rust_decimal::Decimal;
+
+use crate::{
+    format::Format,
+    resources::{container_limits, container_requests, pod_requests},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Default memory throttling factor of the kubelet
+pub const DEFAULT_THROTTLING_FACTOR: Decimal = Decimal::from_parts(9, 0, 0, false, 1);
+/// Default page size `memory.high` is rounded down to
+pub const DEFAULT_PAGE_SIZE: u64 = 4096;
+
+const MEMORY: &str = "memory";
+
+/// Configuration of the kubelet's MemoryQoS feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryQosConfig {
+    /// Fraction of the range between request and limit at which memory is
+    /// throttled, i.e., `memoryThrottlingFactor`
+    pub throttling_factor: Decimal,
+    /// Page size in bytes
+    pub page_size: u64,
+    /// Allocatable memory of the node, used in place of missing limits
+    pub node_allocatable: Option<ParsedQuantity>,
+}
+
+impl Default for MemoryQosConfig {
+    fn default() -> Self {
+        Self {
+            throttling_factor: DEFAULT_THROTTLING_FACTOR,
+            page_size: DEFAULT_PAGE_SIZE,
+            node_allocatable: None,
+        }
+    }
+}
+
+/// Memory protection and throttling boundaries of a cgroup, in bytes.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryQos {
+    /// `memory.min`, i.e., the memory request, unless it is zero
+    pub min: Option<ParsedQuantity>,
+    /// `memory.high`, above which the cgroup is throttled, unless unset
+    pub high: Option<ParsedQuantity>,
+}
+
+/// Memory QoS values of a pod-level cgroup and its containers.
+#[derive(Debug, Clone, Default)]
+pub struct PodMemoryQos {
+    /// Values of the pod-level cgroup
+    pub pod: MemoryQos,
+    /// Values of the init and app containers by name
+    pub containers: BTreeMap<String, MemoryQos>,
+}
+
+/// Returns the `memory.min` and `memory.high` values the kubelet writes for a
+/// container with MemoryQoS enabled.
+///
+/// `memory.high` is `request + factor * (limit - request)` rounded down to
+/// the page size, where the node allocatable memory stands in for a missing
+/// limit. It is left unset if the request equals the limit, or if it would not
+/// exceed the request.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, ResourceRequirements};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::memory_qos::{container_memory_qos, MemoryQosConfig};
+///
+/// let memory = |value: &str| Some(BTreeMap::from([("memory".to_string(), Quantity(value.to_string()))]));
+/// let container = Container {
+///     resources: Some(ResourceRequirements {
+///         requests: memory("1Gi"),
+///         limits: memory("2Gi"),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+///
+/// let qos = container_memory_qos(&container, &MemoryQosConfig::default()).unwrap();
+///
+/// assert_eq!(qos.min.unwrap().to_canonical_string(), "1Gi");
+/// assert_eq!(qos.high.unwrap().to_bytes_u64(), Some(2040107008));
+/// ```
+pub fn container_memory_qos(
+    container: &Container,
+    config: &MemoryQosConfig,
+) -> Result<MemoryQos, ParseQuantityError> {
+    let request = container_requests(container)?.get(MEMORY).map(value);
+    let limit = container_limits(container)?.get(MEMORY).map(value);
+
+    Ok(memory_qos(request, limit, config))
+}
+
+/// Returns the Memory QoS values of a pod and all of its containers.
+///
+/// The pod-level `memory.min` is the pod's memory request including the
+/// overhead. The kubelet does not write `memory.high` to the pod cgroup; it is
+/// derived with the container formula from the pod's request and limit, where
+/// the pod has a limit only if all of its containers do.
+pub fn pod_memory_qos(
+    spec: &PodSpec,
+    config: &MemoryQosConfig,
+) -> Result<PodMemoryQos, ParseQuantityError> {
+    let containers = spec
+        .init_containers
+        .iter()
+        .flatten()
+        .chain(&spec.containers)
+        .map(|container| {
+            container_memory_qos(container, config).map(|qos| (container.name.clone(), qos))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let request = pod_requests(spec)?.get(MEMORY).map(value);
+    let limit = pod_memory_limit(spec)?;
+
+    Ok(PodMemoryQos {
+        pod: memory_qos(request, limit, config),
+        containers,
+    })
+}
+
+/// Returns the memory limit of the pod-level cgroup, which is the sum of the
+/// container limits plus overhead, if every container declares one.
+fn pod_memory_limit(spec: &PodSpec) -> Result<Option<Decimal>, ParseQuantityError> {
+    let limit = |container: &Container| -> Result<Option<Decimal>, ParseQuantityError> {
+        Ok(container_limits(container)?.get(MEMORY).map(value))
+    };
+
+    let mut total = Decimal::ZERO;
+    for container in &spec.containers {
+        let Some(limit) = limit(container)? else {
+            return Ok(None);
+        };
+        total += limit;
+    }
+
+    // Init containers run before the app containers, apart from sidecars
+    let mut sidecars = Decimal::ZERO;
+    let mut init_peak = Decimal::ZERO;
+    for container in spec.init_containers.iter().flatten() {
+        let Some(limit) = limit(container)? else {
+            return Ok(None);
+        };
+
+        if container.restart_policy.as_deref() == Some("Always") {
+            total += limit;
+            sidecars += limit;
+            init_peak = init_peak.max(sidecars);
+        } else {
+            init_peak = init_peak.max(sidecars + limit);
+        }
+    }
+
+    let overhead = spec
+        .overhead
+        .as_ref()
+        .and_then(|overhead| overhead.get(MEMORY))
+        .map(|quantity| ParsedQuantity::try_from(quantity).map(|quantity| value(&quantity)))
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(Some(total.max(init_peak) + overhead))
+}
+
+fn memory_qos(
+    request: Option<Decimal>,
+    limit: Option<Decimal>,
+    config: &MemoryQosConfig,
+) -> MemoryQos {
+    let request = request.unwrap_or_default();
+    let limit = limit.unwrap_or_default();
+
+    let min = (request != Decimal::ZERO).then(|| bytes(request));
+
+    // Requests equal to limits are not throttled
+    if request == limit {
+        return MemoryQos { min, high: None };
+    }
+
+    let upper = if limit != Decimal::ZERO {
+        limit
+    } else {
+        match config.node_allocatable.as_ref().map(value) {
+            Some(allocatable) if allocatable > Decimal::ZERO => allocatable,
+            _ => return MemoryQos { min, high: None },
+        }
+    };
+
+    let page_size = Decimal::from(config.page_size.max(1));
+    let high =
+        ((request + (upper - request) * config.throttling_factor) / page_size).floor() * page_size;
+
+    MemoryQos {
+        min,
+        high: (high != Decimal::ZERO && high > request).then(|| bytes(high)),
+    }
+}
+
+/// Returns the value in whole bytes, rounded up like `Quantity.Value()`.
+fn value(quantity: &ParsedQuantity) -> Decimal {
+    quantity.to_base_decimal().ceil()
+}
+
+fn bytes(value: Decimal) -> ParsedQuantity {
+    ParsedQuantity::from_base_decimal(value, Format::BinarySI)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::core::v1::ResourceRequirements, apimachinery::pkg::api::resource::Quantity,
+    };
+
+    use super::*;
+
+    fn container(name: &str, request: Option<&str>, limit: Option<&str>) -> Container {
+        let memory = |value: Option<&str>| {
+            value.map(|value| BTreeMap::from([(MEMORY.to_owned(), Quantity(value.to_owned()))]))
+        };
+
+        Container {
+            name: name.to_owned(),
+            resources: Some(ResourceRequirements {
+                requests: memory(request),
+                limits: memory(limit),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn canonical(quantity: Option<ParsedQuantity>) -> Option<String> {
+        quantity.map(|quantity| quantity.to_canonical_string())
+    }
+
+    #[test]
+    fn test_container_memory_qos() {
+        let config = MemoryQosConfig::default();
+
+        // Guaranteed memory is never throttled
+        let qos = container_memory_qos(&container("app", None, Some("1Gi")), &config).unwrap();
+        assert_eq!(canonical(qos.min), Some("1Gi".to_owned()));
+        assert_eq!(canonical(qos.high), None);
+
+        // 100Mi + 0.9 * 100Mi is exactly 48640 pages
+        let qos =
+            container_memory_qos(&container("app", Some("100Mi"), Some("200Mi")), &config).unwrap();
+        assert_eq!(qos.high.unwrap().to_bytes_u64(), Some(199229440));
+
+        let config = MemoryQosConfig {
+            throttling_factor: Decimal::new(5, 1),
+            page_size: 1024 * 1024,
+            ..Default::default()
+        };
+        let qos = container_memory_qos(&container("app", None, Some("2Gi")), &config).unwrap();
+        assert_eq!(canonical(qos.high), None);
+        let qos =
+            container_memory_qos(&container("app", Some("1Gi"), Some("2Gi")), &config).unwrap();
+        assert_eq!(canonical(qos.high), Some("1536Mi".to_owned()));
+    }
+
+    #[test]
+    fn test_missing_limit_uses_node_allocatable() {
+        let best_effort = container("app", None, None);
+
+        let qos = container_memory_qos(&best_effort, &MemoryQosConfig::default()).unwrap();
+        assert_eq!(canonical(qos.min), None);
+        assert_eq!(canonical(qos.high), None);
+
+        let config = MemoryQosConfig {
+            throttling_factor: Decimal::new(75, 2),
+            node_allocatable: Some("8Gi".try_into().unwrap()),
+            ..Default::default()
+        };
+        let qos = container_memory_qos(&best_effort, &config).unwrap();
+        assert_eq!(canonical(qos.high), None);
+
+        let burstable = container("app", Some("2Gi"), None);
+        let qos = container_memory_qos(&burstable, &config).unwrap();
+        assert_eq!(canonical(qos.high), Some("6656Mi".to_owned()));
+    }
+
+    #[test]
+    fn test_pod_memory_qos() {
+        let config = MemoryQosConfig {
+            throttling_factor: Decimal::new(5, 1),
+            node_allocatable: Some("16Gi".try_into().unwrap()),
+            ..Default::default()
+        };
+        let mut spec = PodSpec {
+            containers: vec![
+                container("app", Some("1Gi"), Some("3Gi")),
+                container("proxy", Some("1Gi"), Some("1Gi")),
+            ],
+            overhead: Some(BTreeMap::from([(
+                MEMORY.to_owned(),
+                Quantity("512Mi".to_owned()),
+            )])),
+            ..Default::default()
+        };
+
+        let qos = pod_memory_qos(&spec, &config).unwrap();
+        assert_eq!(canonical(qos.pod.min.clone()), Some("2560Mi".to_owned()));
+        assert_eq!(canonical(qos.pod.high.clone()), Some("3584Mi".to_owned()));
+        assert_eq!(
+            canonical(qos.containers["app"].high.clone()),
+            Some("2Gi".to_owned())
+        );
+        assert_eq!(canonical(qos.containers["proxy"].high.clone()), None);
+
+        // Without a limit on every container, the pod falls back to the node
+        spec.init_containers = Some(vec![container("migrate", Some("256Mi"), None)]);
+        let qos = pod_memory_qos(&spec, &config).unwrap();
+        assert_eq!(canonical(qos.pod.high), Some("9472Mi".to_owned()));
+        assert_eq!(
+            canonical(qos.containers["migrate"].high.clone()),
+            Some("8320Mi".to_owned())
+        );
+    }
+}
@@ -0,0 +1,661 @@
+use std::time::Duration;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, Time};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    compat::to_utc, hpa::PodUsage, kubelet::parse_duration, resources::ParsedResourceList,
+    ParsedQuantity,
+};
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    /// A CPU sample has no cumulative usage
+    #[error("cumulative CPU usage is missing")]
+    MissingCpuUsage,
+
+    /// The later CPU sample is not after the earlier one
+    #[error("CPU sample at {current} is not after the sample at {previous}")]
+    InvalidInterval { previous: String, current: String },
+
+    /// The cumulative CPU usage decreased, e.g., because the container
+    /// restarted
+    #[error("cumulative CPU usage decreased from {previous} to {current}")]
+    CounterReset { previous: String, current: String },
+
+    /// The window is not a valid Go duration
+    #[error("invalid window: {0}")]
+    InvalidWindow(String),
+}
+
+// --- metrics.k8s.io ---
+
+/// Resource usage of a pod as served by `metrics.k8s.io/v1beta1`, e.g., by
+/// metrics-server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodMetrics {
+    /// Standard object metadata
+    #[serde(default)]
+    pub metadata: ObjectMeta,
+    /// End of the window the usage was averaged over
+    pub timestamp: Time,
+    /// Window the usage was averaged over as a Go duration, e.g., `10.5s`
+    pub window: String,
+    /// Usage by container
+    #[serde(default)]
+    pub containers: Vec<ContainerMetrics>,
+}
+
+impl PodMetrics {
+    /// Returns the window the usage was averaged over.
+    pub fn window(&self) -> Result<Duration, MetricsError> {
+        parse_go_duration(&self.window)
+    }
+
+    /// Converts the metrics into the usage the autoscaler consumes.
+    pub fn to_pod_usage(&self) -> Result<PodUsage, MetricsError> {
+        Ok(PodUsage {
+            timestamp: to_utc(&self.timestamp),
+            window: self.window()?,
+            containers: self
+                .containers
+                .iter()
+                .map(|container| (container.name.clone(), container.usage.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Resource usage of a single container of a [`PodMetrics`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerMetrics {
+    /// Name of the container
+    pub name: String,
+    /// Usage by resource, e.g., `cpu` in nanocores and `memory` in Ki
+    #[serde(with = "resource_list")]
+    pub usage: ParsedResourceList,
+}
+
+/// Resource usage of a node as served by `metrics.k8s.io/v1beta1`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeMetrics {
+    /// Standard object metadata
+    #[serde(default)]
+    pub metadata: ObjectMeta,
+    /// End of the window the usage was averaged over
+    pub timestamp: Time,
+    /// Window the usage was averaged over as a Go duration
+    pub window: String,
+    /// Usage by resource
+    #[serde(with = "resource_list")]
+    pub usage: ParsedResourceList,
+}
+
+impl NodeMetrics {
+    /// Returns the window the usage was averaged over.
+    pub fn window(&self) -> Result<Duration, MetricsError> {
+        parse_go_duration(&self.window)
+    }
+}
+
+/// A `PodMetricsList`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PodMetricsList {
+    /// Metrics of all pods
+    pub items: Vec<PodMetrics>,
+}
+
+/// A `NodeMetricsList`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeMetricsList {
+    /// Metrics of all nodes
+    pub items: Vec<NodeMetrics>,
+}
+
+// --- Kubelet Summary API ---
+
+/// Payload of the kubelet `/stats/summary` endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Summary {
+    /// Statistics of the node
+    pub node: NodeStats,
+    /// Statistics of all pods on the node
+    #[serde(default)]
+    pub pods: Vec<PodStats>,
+}
+
+/// Statistics of a node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStats {
+    /// Name of the node
+    pub node_name: String,
+    /// Statistics of system daemons, e.g., `kubelet` and `runtime`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_containers: Vec<ContainerStats>,
+    /// Time the node started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    /// CPU usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuStats>,
+    /// Memory usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryStats>,
+    /// Usage of the filesystem holding the kubelet root directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs: Option<FsStats>,
+}
+
+/// Reference to the pod of [`PodStats`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PodReference {
+    /// Name of the pod
+    pub name: String,
+    /// Namespace of the pod
+    pub namespace: String,
+    /// UID of the pod
+    #[serde(default)]
+    pub uid: String,
+}
+
+/// Statistics of a pod.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodStats {
+    /// Reference to the pod
+    pub pod_ref: PodReference,
+    /// Time the pod started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    /// Statistics of the containers of the pod
+    #[serde(default)]
+    pub containers: Vec<ContainerStats>,
+    /// CPU usage of the pod cgroup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuStats>,
+    /// Memory usage of the pod cgroup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryStats>,
+    /// Usage of ephemeral storage, i.e., writable layers, logs and local
+    /// volumes
+    #[serde(
+        rename = "ephemeral-storage",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ephemeral_storage: Option<FsStats>,
+}
+
+/// Statistics of a container.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerStats {
+    /// Name of the container
+    pub name: String,
+    /// Time the container started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<Time>,
+    /// CPU usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuStats>,
+    /// Memory usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryStats>,
+    /// Usage of the writable layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rootfs: Option<FsStats>,
+    /// Usage of the container logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<FsStats>,
+}
+
+/// CPU usage. Nanocores and core-nanoseconds are decoded as quantities of
+/// cores and core-seconds with the `n` suffix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuStats {
+    /// Time the usage was sampled at
+    pub time: Time,
+    /// Usage averaged over the last sampling interval, in cores
+    #[serde(default, with = "nanos", skip_serializing_if = "Option::is_none")]
+    pub usage_nano_cores: Option<ParsedQuantity>,
+    /// Cumulative usage since the container started, in core-seconds
+    #[serde(default, with = "nanos", skip_serializing_if = "Option::is_none")]
+    pub usage_core_nano_seconds: Option<ParsedQuantity>,
+}
+
+/// Memory usage in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    /// Time the usage was sampled at
+    pub time: Time,
+    /// Memory available before hitting the limit or node capacity
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub available_bytes: Option<ParsedQuantity>,
+    /// Total memory in use, including caches
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub usage_bytes: Option<ParsedQuantity>,
+    /// Memory that cannot be reclaimed, which drives evictions and OOM kills
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub working_set_bytes: Option<ParsedQuantity>,
+    /// Anonymous and swap cache memory
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub rss_bytes: Option<ParsedQuantity>,
+    /// Cumulative number of page faults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_faults: Option<u64>,
+    /// Cumulative number of major page faults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub major_page_faults: Option<u64>,
+}
+
+/// Filesystem usage in bytes and inodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsStats {
+    /// Time the usage was sampled at
+    pub time: Time,
+    /// Bytes available to non-root users
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub available_bytes: Option<ParsedQuantity>,
+    /// Total size of the filesystem
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub capacity_bytes: Option<ParsedQuantity>,
+    /// Bytes used by the measured consumer
+    #[serde(default, with = "bytes", skip_serializing_if = "Option::is_none")]
+    pub used_bytes: Option<ParsedQuantity>,
+    /// Free inodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inodes_free: Option<u64>,
+    /// Total inodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inodes: Option<u64>,
+    /// Inodes used by the measured consumer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inodes_used: Option<u64>,
+}
+
+// --- CPU rates ---
+
+/// Returns the average CPU usage between two samples of the cumulative
+/// usage, truncated to nanocores like metrics-server computes it.
+///
+/// ```rust
+/// use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+/// use kube_quantity::metrics::{cpu_usage_rate, CpuStats};
+///
+/// let sample = |seconds: i64, usage: &str| CpuStats {
+///     time: Time(format!("2024-01-01T00:00:{seconds}Z").parse().unwrap()),
+///     usage_nano_cores: None,
+///     usage_core_nano_seconds: Some(usage.try_into().unwrap()),
+/// };
+///
+/// // 3 core-seconds within 10 seconds
+/// let rate = cpu_usage_rate(&sample(10, "12"), &sample(20, "15")).unwrap();
+///
+/// assert_eq!(rate.to_string(), "300000000n");
+/// assert_eq!(rate.to_canonical_string(), "300m");
+/// ```
+pub fn cpu_usage_rate(
+    previous: &CpuStats,
+    current: &CpuStats,
+) -> Result<ParsedQuantity, MetricsError> {
+    let (Some(previous_usage), Some(current_usage)) = (
+        &previous.usage_core_nano_seconds,
+        &current.usage_core_nano_seconds,
+    ) else {
+        return Err(MetricsError::MissingCpuUsage);
+    };
+
+    let (previous_time, current_time) = (to_utc(&previous.time), to_utc(&current.time));
+    let interval = (current_time - previous_time)
+        .num_nanoseconds()
+        .filter(|nanoseconds| *nanoseconds > 0)
+        .ok_or_else(|| MetricsError::InvalidInterval {
+            previous: previous_time.to_rfc3339(),
+            current: current_time.to_rfc3339(),
+        })?;
+
+    let usage = current_usage.to_base_decimal() - previous_usage.to_base_decimal();
+    if usage.is_sign_negative() {
+        return Err(MetricsError::CounterReset {
+            previous: previous_usage.to_canonical_string(),
+            current: current_usage.to_canonical_string(),
+        });
+    }
+
+    // Core-seconds per second, in nanocores
+    let seconds = Decimal::new(interval, 9);
+    let nanocores = (usage / seconds * Decimal::from(1_000_000_000)).trunc();
+
+    Ok(ParsedQuantity::from_nano_decimal(nanocores))
+}
+
+/// Parses a Go duration string such as `1m30s` or `10.039s` like
+/// [`parse_duration`], reporting failures as [`MetricsError::InvalidWindow`].
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use kube_quantity::metrics::parse_go_duration;
+///
+/// assert_eq!(parse_go_duration("1m30s").unwrap(), Duration::from_secs(90));
+/// assert_eq!(parse_go_duration("10.039s").unwrap(), Duration::from_millis(10_039));
+/// assert!(parse_go_duration("10").is_err());
+/// ```
+pub fn parse_go_duration(value: &str) -> Result<Duration, MetricsError> {
+    parse_duration(value).map_err(|_| MetricsError::InvalidWindow(value.to_owned()))
+}
+
+// --- Serde helpers ---
+
+/// Resource lists whose quantities are strings.
+mod resource_list {
+    use std::collections::BTreeMap;
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::{resources::ParsedResourceList, ParsedQuantity};
+
+    pub fn serialize<S: Serializer>(
+        list: &ParsedResourceList,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            list.iter()
+                .map(|(name, quantity)| (name, quantity.to_string())),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ParsedResourceList, D::Error> {
+        BTreeMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, quantity)| {
+                ParsedQuantity::try_from(quantity.as_str())
+                    .map(|quantity| (name, quantity))
+                    .map_err(de::Error::custom)
+            })
+            .collect()
+    }
+}
+
+/// Integers counting billionths of the base unit, e.g., nanocores.
+mod nanos {
+    use rust_decimal::prelude::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::ParsedQuantity;
+
+    pub fn serialize<S: Serializer>(
+        quantity: &Option<ParsedQuantity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let nanos = quantity.as_ref().and_then(|quantity| {
+            (quantity.to_base_decimal() * Decimal::from(1_000_000_000))
+                .trunc()
+                .to_u64()
+        });
+        match nanos {
+            Some(nanos) => serializer.serialize_u64(nanos),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<ParsedQuantity>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?
+            .map(|nanos| ParsedQuantity::from_nano_decimal(Decimal::from(nanos))))
+    }
+}
+
+/// Integers counting bytes.
+mod bytes {
+    use rust_decimal::prelude::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::{format::Format, ParsedQuantity};
+
+    pub fn serialize<S: Serializer>(
+        quantity: &Option<ParsedQuantity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let bytes = quantity
+            .as_ref()
+            .and_then(|quantity| quantity.to_base_decimal().ceil().to_u64());
+        match bytes {
+            Some(bytes) => serializer.serialize_u64(bytes),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<ParsedQuantity>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?
+            .map(|bytes| ParsedQuantity::from_base_decimal(Decimal::from(bytes), Format::BinarySI)))
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeDelta};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{compat::from_utc, test_util::fixture};
+
+    fn json<T: serde::de::DeserializeOwned>(name: &str) -> T {
+        serde_json::from_str(&fixture(&format!("metrics/{name}"))).unwrap()
+    }
+
+    fn container_cpu<'a>(summary: &'a Summary, container: &str) -> &'a CpuStats {
+        summary.pods[0]
+            .containers
+            .iter()
+            .find(|stats| stats.name == container)
+            .and_then(|stats| stats.cpu.as_ref())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pod_metrics() {
+        let list: PodMetricsList = json("pod-metrics.json");
+        let web = &list.items[0];
+        let app = &web.containers[0].usage;
+
+        assert_eq!(web.metadata.name.as_deref(), Some("web-7c9d8b6f5-x2kqp"));
+        assert_eq!(app["cpu"].to_string(), "123456789n");
+        assert_eq!(app["memory"].to_bytes_u64(), Some(45678 * 1024));
+        assert_eq!(web.window().unwrap(), Duration::from_millis(10_039));
+
+        let usage = list.items[1].to_pod_usage().unwrap();
+        assert_eq!(usage.window, Duration::from_secs(60));
+        assert_eq!(
+            usage.containers["worker"]["cpu"].to_canonical_string(),
+            "1500m"
+        );
+
+        // Quantities are serialized as they were received
+        let raw: Value = json("pod-metrics.json");
+        assert_eq!(serde_json::to_value(web).unwrap(), raw["items"][0]);
+    }
+
+    #[test]
+    fn test_node_metrics() {
+        let list: NodeMetricsList = json("node-metrics.json");
+        let node = &list.items[0];
+
+        assert_eq!(node.usage["cpu"].to_canonical_string(), "1843245126n");
+        assert_eq!(node.usage["memory"].to_canonical_string(), "6158412Ki");
+        assert_eq!(node.window().unwrap(), Duration::from_millis(20_052));
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary: Summary = json("summary-1.json");
+        let node = &summary.node;
+        let memory = node.memory.as_ref().unwrap();
+        let pod = &summary.pods[0];
+
+        assert_eq!(node.node_name, "node-a");
+        assert_eq!(node.system_containers[0].name, "kubelet");
+        assert_eq!(
+            node.cpu
+                .as_ref()
+                .unwrap()
+                .usage_nano_cores
+                .as_ref()
+                .unwrap()
+                .to_canonical_string(),
+            "1843245126n"
+        );
+        assert_eq!(
+            memory
+                .working_set_bytes
+                .as_ref()
+                .unwrap()
+                .to_canonical_string(),
+            "6158432Ki"
+        );
+        assert_eq!(memory.major_page_faults, Some(12));
+        assert_eq!(
+            node.fs
+                .as_ref()
+                .unwrap()
+                .capacity_bytes
+                .as_ref()
+                .unwrap()
+                .to_canonical_string(),
+            "100Gi"
+        );
+
+        assert_eq!(pod.pod_ref.namespace, "default");
+        assert_eq!(
+            pod.ephemeral_storage
+                .as_ref()
+                .unwrap()
+                .used_bytes
+                .as_ref()
+                .unwrap()
+                .to_canonical_string(),
+            "1076Ki"
+        );
+        assert_eq!(
+            container_cpu(&summary, "app")
+                .usage_core_nano_seconds
+                .as_ref()
+                .unwrap()
+                .to_string(),
+            "1002500000000n"
+        );
+
+        // Numbers are serialized in their original units
+        let cpu = serde_json::to_value(container_cpu(&summary, "proxy")).unwrap();
+        assert_eq!(cpu["usageNanoCores"], 2105341);
+        assert_eq!(cpu["usageCoreNanoSeconds"], 18021053410u64);
+        let raw: Value = json("summary-1.json");
+        assert_eq!(
+            serde_json::to_value(&pod.containers[0].memory).unwrap(),
+            raw["pods"][0]["containers"][0]["memory"]
+        );
+    }
+
+    #[test]
+    fn test_cpu_usage_rate() {
+        let previous: Summary = json("summary-0.json");
+        let current: Summary = json("summary-1.json");
+
+        for container in ["app", "proxy"] {
+            let rate = cpu_usage_rate(
+                container_cpu(&previous, container),
+                container_cpu(&current, container),
+            )
+            .unwrap();
+            let reported = container_cpu(&current, container)
+                .usage_nano_cores
+                .as_ref()
+                .unwrap();
+            assert_eq!(rate.to_canonical_string(), reported.to_canonical_string());
+        }
+
+        let node_rate = cpu_usage_rate(
+            previous.node.cpu.as_ref().unwrap(),
+            current.node.cpu.as_ref().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(node_rate.to_string(), "1843245126n");
+
+        // Rates are truncated to nanocores
+        let sample = |seconds: i64, usage: &str| CpuStats {
+            time: from_utc(DateTime::from_timestamp(seconds, 0).unwrap()),
+            usage_nano_cores: None,
+            usage_core_nano_seconds: Some(usage.try_into().unwrap()),
+        };
+        assert_eq!(
+            cpu_usage_rate(&sample(0, "0"), &sample(3, "1"))
+                .unwrap()
+                .to_string(),
+            "333333333n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let current: Summary = json("summary-1.json");
+        let previous: Summary = json("summary-0.json");
+        let cpu = container_cpu(&current, "app");
+
+        assert!(matches!(
+            cpu_usage_rate(cpu, cpu),
+            Err(MetricsError::InvalidInterval { .. })
+        ));
+        // The container restarted
+        let restarted = CpuStats {
+            time: from_utc(to_utc(&cpu.time) + TimeDelta::seconds(10)),
+            usage_nano_cores: None,
+            usage_core_nano_seconds: Some("1".try_into().unwrap()),
+        };
+        assert!(matches!(
+            cpu_usage_rate(cpu, &restarted),
+            Err(MetricsError::CounterReset { .. })
+        ));
+        let mut missing = cpu.clone();
+        missing.usage_core_nano_seconds = None;
+        assert!(matches!(
+            cpu_usage_rate(container_cpu(&previous, "app"), &missing),
+            Err(MetricsError::MissingCpuUsage)
+        ));
+
+        assert_eq!(
+            parse_go_duration("1h2m3.5s").unwrap(),
+            Duration::from_millis(3_723_500)
+        );
+        assert_eq!(
+            parse_go_duration("1ms500us").unwrap(),
+            Duration::from_micros(1_500)
+        );
+        assert_eq!(parse_go_duration("0").unwrap(), Duration::ZERO);
+        for invalid in ["", "s", "1", "1d", "-1s", "1.2.3s"] {
+            assert!(
+                matches!(
+                    parse_go_duration(invalid),
+                    Err(MetricsError::InvalidWindow(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+}
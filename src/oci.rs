@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::{
+    api::core::v1::{Container, ResourceRequirements},
+    apimachinery::pkg::api::resource::Quantity,
+};
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    cgroup::{
+        cpu_limit_from_quota, cpu_quota, cpu_request_from_shares, cpu_shares, MIN_SHARES,
+        QUOTA_PERIOD,
+    },
+    format::Format,
+    memory_qos::{container_memory_qos, MemoryQosConfig},
+    resources::{container_limits, container_requests},
+    ParseQuantityError, ParsedQuantity,
+};
+
+const CPU: &str = "cpu";
+const MEMORY: &str = "memory";
+const HUGEPAGES_PREFIX: &str = "hugepages-";
+
+/// Page size suffixes used by the runtime spec, which are binary multiples
+/// despite their names
+const PAGE_SIZE_UNITS: [(&str, u64); 4] = [
+    ("GB", 1024 * 1024 * 1024),
+    ("MB", 1024 * 1024),
+    ("KB", 1024),
+    ("B", 1),
+];
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum OciError {
+    /// A page size is not a positive number of bytes, KB, MB or GB
+    #[error("invalid hugepage size: {0}")]
+    InvalidPageSize(String),
+
+    /// A quantity of the resource requirements could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Runtime spec ---
+
+/// The `linux.resources` block of an OCI runtime `config.json`, limited to
+/// the fields derived from Kubernetes container resources.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct LinuxResources {
+    /// Memory restrictions
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub memory: Option<LinuxMemory>,
+    /// CPU restrictions
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub cpu: Option<LinuxCpu>,
+    /// Hugetlb limits per page size
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub hugepage_limits: Vec<LinuxHugepageLimit>,
+    /// Raw cgroup v2 files and their values
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub unified: BTreeMap<String, String>,
+}
+
+/// The `linux.resources.memory` block, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct LinuxMemory {
+    /// Memory limit
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub limit: Option<i64>,
+    /// Soft memory limit
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub reservation: Option<i64>,
+    /// Limit of memory plus swap
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub swap: Option<i64>,
+}
+
+/// The `linux.resources.cpu` block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct LinuxCpu {
+    /// Relative CPU weight
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub shares: Option<u64>,
+    /// CFS quota in microseconds
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub quota: Option<i64>,
+    /// CFS period in microseconds
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub period: Option<u64>,
+}
+
+/// An entry of `linux.resources.hugepageLimits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct LinuxHugepageLimit {
+    /// Page size, e.g., `2MB`
+    pub page_size: String,
+    /// Limit in bytes
+    pub limit: u64,
+}
+
+/// Options for converting container resources to the runtime spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinuxResourcesOptions {
+    /// CFS period in microseconds
+    pub cpu_period: u64,
+    /// Whether to set the memory plus swap limit to the memory limit, which
+    /// prevents the container from swapping
+    pub disable_swap: bool,
+    /// Memory QoS configuration, which adds `memory.min` and `memory.high` to
+    /// the unified cgroup v2 files when set
+    pub memory_qos: Option<MemoryQosConfig>,
+}
+
+impl Default for LinuxResourcesOptions {
+    fn default() -> Self {
+        Self {
+            cpu_period: QUOTA_PERIOD,
+            disable_swap: true,
+            memory_qos: None,
+        }
+    }
+}
+
+/// Converts container resources to the `linux.resources` block the kubelet
+/// would have a container runtime apply.
+///
+/// CPU requests become shares and CPU limits become a CFS quota, memory limits
+/// and requests become the limit and reservation, and hugepages limits are
+/// set per page size.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::ResourceRequirements;
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::oci::{to_linux_resources, LinuxResourcesOptions};
+///
+/// let requirements = ResourceRequirements {
+///     limits: Some(BTreeMap::from([
+///         ("cpu".to_string(), Quantity("500m".to_string())),
+///         ("memory".to_string(), Quantity("256Mi".to_string())),
+///     ])),
+///     ..Default::default()
+/// };
+///
+/// let resources = to_linux_resources(&requirements, &LinuxResourcesOptions::default()).unwrap();
+/// let cpu = resources.cpu.unwrap();
+///
+/// assert_eq!(cpu.shares, Some(512));
+/// assert_eq!(cpu.quota, Some(50000));
+/// assert_eq!(resources.memory.unwrap().limit, Some(268435456));
+/// ```
+pub fn to_linux_resources(
+    requirements: &ResourceRequirements,
+    options: &LinuxResourcesOptions,
+) -> Result<LinuxResources, OciError> {
+    let container = Container {
+        resources: Some(requirements.clone()),
+        ..Default::default()
+    };
+    let requests = container_requests(&container)?;
+    let limits = container_limits(&container)?;
+
+    let cpu = LinuxCpu {
+        shares: Some(cpu_shares(
+            requests.get(CPU).unwrap_or(&ParsedQuantity::default()),
+        )),
+        quota: cpu_quota(limits.get(CPU), options.cpu_period).map(saturating_i64),
+        period: Some(options.cpu_period),
+    };
+
+    let limit = limits.get(MEMORY).map(bytes).map(saturating_i64);
+    let memory = LinuxMemory {
+        limit,
+        reservation: requests.get(MEMORY).map(bytes).map(saturating_i64),
+        swap: limit.filter(|_| options.disable_swap),
+    };
+
+    let hugepage_limits = limits
+        .iter()
+        .filter_map(|(name, limit)| {
+            let page_size = name.strip_prefix(HUGEPAGES_PREFIX)?;
+            Some(
+                format_page_size(page_size).map(|page_size| LinuxHugepageLimit {
+                    page_size,
+                    limit: bytes(limit),
+                }),
+            )
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut unified = BTreeMap::new();
+    if let Some(config) = &options.memory_qos {
+        let qos = container_memory_qos(&container, config)?;
+        for (file, value) in [("memory.min", qos.min), ("memory.high", qos.high)] {
+            if let Some(value) = value {
+                unified.insert(file.to_owned(), bytes(&value).to_string());
+            }
+        }
+    }
+
+    Ok(LinuxResources {
+        memory: (memory != LinuxMemory::default()).then_some(memory),
+        cpu: Some(cpu),
+        hugepage_limits,
+        unified,
+    })
+}
+
+/// Converts a `linux.resources` block back to container resources.
+///
+/// CPU shares and quota are converted to millicores, the memory reservation
+/// and limit become the memory request and limit, and hugepages are both
+/// requested and limited at their limit. Values that map to the defaults of
+/// an unrestricted container (e.g., the minimum of 2 shares) are omitted.
+///
+/// ```rust
+/// use kube_quantity::oci::{from_linux_resources, LinuxCpu, LinuxResources};
+///
+/// let resources = LinuxResources {
+///     cpu: Some(LinuxCpu { shares: Some(512), quota: Some(50000), period: Some(100000) }),
+///     ..Default::default()
+/// };
+///
+/// let requirements = from_linux_resources(&resources).unwrap();
+///
+/// assert_eq!(requirements.requests.unwrap()["cpu"].0, "500m");
+/// assert_eq!(requirements.limits.unwrap()["cpu"].0, "500m");
+/// ```
+pub fn from_linux_resources(resources: &LinuxResources) -> Result<ResourceRequirements, OciError> {
+    let mut requests = BTreeMap::new();
+    let mut limits = BTreeMap::new();
+
+    if let Some(cpu) = &resources.cpu {
+        let period = cpu
+            .period
+            .filter(|period| *period > 0)
+            .unwrap_or(QUOTA_PERIOD);
+        if let Some(limit) =
+            positive(cpu.quota).and_then(|quota| cpu_limit_from_quota(Some(quota), period))
+        {
+            limits.insert(CPU.to_owned(), limit);
+        }
+
+        // The minimum shares stand for an unset request
+        if let Some(shares) = cpu.shares.filter(|shares| *shares > MIN_SHARES) {
+            requests.insert(CPU.to_owned(), cpu_request_from_shares(shares));
+        }
+    }
+
+    if let Some(memory) = &resources.memory {
+        if let Some(limit) = positive(memory.limit) {
+            limits.insert(MEMORY.to_owned(), memory_quantity(limit));
+        }
+        if let Some(reservation) = positive(memory.reservation) {
+            requests.insert(MEMORY.to_owned(), memory_quantity(reservation));
+        }
+    }
+
+    for hugepage_limit in &resources.hugepage_limits {
+        let name = format!(
+            "{HUGEPAGES_PREFIX}{}",
+            parse_page_size(&hugepage_limit.page_size)?.to_canonical_string()
+        );
+        let limit = memory_quantity(hugepage_limit.limit);
+        requests.insert(name.clone(), limit.clone());
+        limits.insert(name, limit);
+    }
+
+    // Limits without requests are requested at their limit
+    for (name, limit) in &limits {
+        requests
+            .entry(name.clone())
+            .or_insert_with(|| limit.clone());
+    }
+
+    let to_list = |list: BTreeMap<String, ParsedQuantity>| {
+        (!list.is_empty()).then(|| {
+            list.into_iter()
+                .map(|(name, quantity)| (name, Quantity(quantity.to_canonical_string())))
+                .collect()
+        })
+    };
+
+    Ok(ResourceRequirements {
+        requests: to_list(requests),
+        limits: to_list(limits),
+        ..Default::default()
+    })
+}
+
+/// Formats the page size of a hugepages resource, e.g., `2Mi` as `2MB`.
+fn format_page_size(page_size: &str) -> Result<String, OciError> {
+    let bytes = ParsedQuantity::try_from(page_size)
+        .ok()
+        .map(|quantity| quantity.to_base_decimal())
+        .filter(|bytes| *bytes > Decimal::ZERO && bytes.fract().is_zero())
+        .and_then(|bytes| u64::try_from(bytes).ok())
+        .ok_or_else(|| OciError::InvalidPageSize(page_size.to_owned()))?;
+
+    let (unit, size) = PAGE_SIZE_UNITS
+        .into_iter()
+        .find(|(_, size)| bytes % size == 0)
+        .unwrap_or(("B", 1));
+
+    Ok(format!("{}{unit}", bytes / size))
+}
+
+/// Parses a page size of the runtime spec, e.g., `2MB` as `2Mi`.
+fn parse_page_size(page_size: &str) -> Result<ParsedQuantity, OciError> {
+    PAGE_SIZE_UNITS
+        .into_iter()
+        .find_map(|(unit, size)| {
+            let value: u64 = page_size.strip_suffix(unit)?.parse().ok()?;
+            value.checked_mul(size).filter(|bytes| *bytes > 0)
+        })
+        .map(memory_quantity)
+        .ok_or_else(|| OciError::InvalidPageSize(page_size.to_owned()))
+}
+
+/// Returns the value in whole bytes, rounded up like `Quantity.Value()`.
+fn bytes(quantity: &ParsedQuantity) -> u64 {
+    let bytes = quantity.to_base_decimal().ceil();
+    bytes.max(Decimal::ZERO).try_into().unwrap_or(u64::MAX)
+}
+
+fn memory_quantity(bytes: u64) -> ParsedQuantity {
+    ParsedQuantity::from_base_decimal(Decimal::from(bytes), Format::BinarySI)
+}
+
+fn positive(value: Option<i64>) -> Option<u64> {
+    value
+        .and_then(|value| u64::try_from(value).ok())
+        .filter(|value| *value > 0)
+}
+
+fn saturating_i64(value: u64) -> i64 {
+    value.try_into().unwrap_or(i64::MAX)
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::test_util::fixture;
+    use crate::test_util::list;
+
+    fn burstable() -> ResourceRequirements {
+        ResourceRequirements {
+            requests: Some(list(&[("cpu", "250m"), ("memory", "512Mi")])),
+            limits: Some(list(&[("cpu", "1500m"), ("memory", "1Gi")])),
+            ..Default::default()
+        }
+    }
+
+    fn guaranteed_hugepages() -> ResourceRequirements {
+        ResourceRequirements {
+            limits: Some(list(&[
+                ("cpu", "2"),
+                ("memory", "4Gi"),
+                ("hugepages-2Mi", "256Mi"),
+                ("hugepages-1Gi", "2Gi"),
+            ])),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn assert_golden(resources: &LinuxResources, name: &str) {
+        let golden: serde_json::Value =
+            serde_json::from_str(&fixture(&format!("oci/{name}"))).unwrap();
+
+        assert_eq!(serde_json::to_value(resources).unwrap(), golden);
+        assert_eq!(
+            &serde_json::from_value::<LinuxResources>(golden).unwrap(),
+            resources
+        );
+    }
+
+    #[test]
+    fn test_burstable_golden() {
+        let options = LinuxResourcesOptions {
+            memory_qos: Some(MemoryQosConfig::default()),
+            ..Default::default()
+        };
+        let resources = to_linux_resources(&burstable(), &options).unwrap();
+
+        #[cfg(feature = "serde")]
+        assert_golden(&resources, "burstable.json");
+        assert_eq!(from_linux_resources(&resources).unwrap(), burstable());
+    }
+
+    #[test]
+    fn test_guaranteed_hugepages_golden() {
+        let options = LinuxResourcesOptions {
+            cpu_period: 50_000,
+            disable_swap: false,
+            ..Default::default()
+        };
+        let resources = to_linux_resources(&guaranteed_hugepages(), &options).unwrap();
+
+        #[cfg(feature = "serde")]
+        assert_golden(&resources, "guaranteed-hugepages.json");
+
+        let requirements = from_linux_resources(&resources).unwrap();
+        assert_eq!(requirements.limits, guaranteed_hugepages().limits);
+        assert_eq!(requirements.requests, guaranteed_hugepages().limits);
+    }
+
+    #[test]
+    fn test_best_effort() {
+        let resources =
+            to_linux_resources(&ResourceRequirements::default(), &Default::default()).unwrap();
+
+        assert_eq!(
+            resources,
+            LinuxResources {
+                cpu: Some(LinuxCpu {
+                    shares: Some(2),
+                    quota: None,
+                    period: Some(100_000),
+                }),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            from_linux_resources(&resources).unwrap(),
+            ResourceRequirements::default()
+        );
+    }
+
+    #[test]
+    fn test_page_sizes() {
+        assert_eq!(format_page_size("64Ki").unwrap(), "64KB");
+        assert_eq!(format_page_size("2048Ki").unwrap(), "2MB");
+        assert_eq!(format_page_size("1Gi").unwrap(), "1GB");
+        assert!(format_page_size("0").is_err());
+        assert!(format_page_size("1.5").is_err());
+
+        assert_eq!(parse_page_size("2MB").unwrap().to_canonical_string(), "2Mi");
+        assert_eq!(
+            parse_page_size("512B").unwrap().to_canonical_string(),
+            "512"
+        );
+        assert!(parse_page_size("2MiB").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{Container, PodSpec};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use thiserror::Error;
+
+use crate::{
+    resources::{container_requests, pod_qos_class, QosClass},
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// OOM score adjustment of the kubelet itself
+pub const KUBELET_OOM_SCORE_ADJ: i64 = -999;
+/// OOM score adjustment of guaranteed and node critical containers
+pub const GUARANTEED_OOM_SCORE_ADJ: i64 = -997;
+/// OOM score adjustment of best effort containers
+pub const BEST_EFFORT_OOM_SCORE_ADJ: i64 = 1000;
+
+/// Priority class of pods that are critical to the node
+pub const SYSTEM_NODE_CRITICAL: &str = "system-node-critical";
+/// Lowest priority of critical pods
+pub const SYSTEM_CRITICAL_PRIORITY: i32 = 2_000_000_000;
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum OomScoreError {
+    /// The memory capacity of the node is not positive
+    #[error("memory capacity must be greater than zero, got {0}")]
+    InvalidCapacity(String),
+
+    /// A quantity of the pod could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- OOM score ---
+
+/// Returns the `oom_score_adj` the kubelet assigns to a container of a pod on
+/// a node with the given memory capacity, i.e., `GetContainerOOMScoreAdjust`.
+///
+/// Node critical and guaranteed containers get [`GUARANTEED_OOM_SCORE_ADJ`],
+/// best effort containers get [`BEST_EFFORT_OOM_SCORE_ADJ`]. Burstable
+/// containers get `1000 - 1000 * request / capacity`, clamped to `[3, 999]`,
+/// where sidecars are boosted to the smallest request of the app containers.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, PodSpec, ResourceRequirements};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::{oom::container_oom_score_adj, ParsedQuantity};
+///
+/// let container = Container {
+///     name: "app".to_string(),
+///     resources: Some(ResourceRequirements {
+///         requests: Some(BTreeMap::from([("memory".to_string(), Quantity("1600Mi".to_string()))])),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// let spec = PodSpec { containers: vec![container.clone()], ..Default::default() };
+/// let capacity: ParsedQuantity = "16000Mi".try_into().unwrap();
+///
+/// assert_eq!(container_oom_score_adj(&spec, &container, &capacity).unwrap(), 900);
+/// ```
+pub fn container_oom_score_adj(
+    spec: &PodSpec,
+    container: &Container,
+    memory_capacity: &ParsedQuantity,
+) -> Result<i64, OomScoreError> {
+    let capacity = value(memory_capacity);
+    if capacity <= Decimal::ZERO {
+        return Err(OomScoreError::InvalidCapacity(
+            memory_capacity.to_canonical_string(),
+        ));
+    }
+
+    if is_node_critical(spec) {
+        return Ok(GUARANTEED_OOM_SCORE_ADJ);
+    }
+
+    match pod_qos_class(spec)? {
+        QosClass::Guaranteed => return Ok(GUARANTEED_OOM_SCORE_ADJ),
+        QosClass::BestEffort => return Ok(BEST_EFFORT_OOM_SCORE_ADJ),
+        QosClass::Burstable => {}
+    }
+
+    let mut request = memory_request(container)?;
+    if is_sidecar(spec, container) {
+        // Sidecars should be killed after the containers they serve
+        request = request.max(min_app_container_request(spec)?);
+    }
+
+    // Mirrors the truncating integer division of the kubelet
+    let adjust = BEST_EFFORT_OOM_SCORE_ADJ
+        - (Decimal::from(BEST_EFFORT_OOM_SCORE_ADJ).saturating_mul(request) / capacity)
+            .trunc()
+            .to_i64()
+            .unwrap_or(i64::MAX);
+
+    // Burstable containers are killed before guaranteed and after best effort
+    // containers
+    Ok(adjust.clamp(
+        BEST_EFFORT_OOM_SCORE_ADJ + GUARANTEED_OOM_SCORE_ADJ,
+        BEST_EFFORT_OOM_SCORE_ADJ - 1,
+    ))
+}
+
+/// Returns the `oom_score_adj` of every init and app container of a pod by
+/// name.
+pub fn pod_oom_score_adj(
+    spec: &PodSpec,
+    memory_capacity: &ParsedQuantity,
+) -> Result<BTreeMap<String, i64>, OomScoreError> {
+    spec.init_containers
+        .iter()
+        .flatten()
+        .chain(&spec.containers)
+        .map(|container| {
+            container_oom_score_adj(spec, container, memory_capacity)
+                .map(|adjust| (container.name.clone(), adjust))
+        })
+        .collect()
+}
+
+/// Returns whether a pod is critical to the node. Static and mirror pods are
+/// only recognized by their priority, as they cannot be told apart by spec.
+fn is_node_critical(spec: &PodSpec) -> bool {
+    spec.priority_class_name.as_deref() == Some(SYSTEM_NODE_CRITICAL)
+        && spec
+            .priority
+            .is_some_and(|priority| priority >= SYSTEM_CRITICAL_PRIORITY)
+}
+
+fn is_sidecar(spec: &PodSpec, container: &Container) -> bool {
+    container.restart_policy.as_deref() == Some("Always")
+        && spec
+            .init_containers
+            .iter()
+            .flatten()
+            .any(|init_container| init_container.name == container.name)
+}
+
+/// Returns the smallest memory request of the app containers.
+fn min_app_container_request(spec: &PodSpec) -> Result<Decimal, ParseQuantityError> {
+    let mut min = None;
+    for container in &spec.containers {
+        let request = memory_request(container)?;
+        min = Some(min.map_or(request, |min: Decimal| min.min(request)));
+    }
+
+    Ok(min.unwrap_or_default())
+}
+
+fn memory_request(container: &Container) -> Result<Decimal, ParseQuantityError> {
+    Ok(container_requests(container)?
+        .get("memory")
+        .map(value)
+        .unwrap_or_default())
+}
+
+/// Returns the value in whole bytes, rounded up like `Quantity.Value()`.
+fn value(quantity: &ParsedQuantity) -> Decimal {
+    quantity.to_base_decimal().ceil()
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::container;
+
+    fn capacity(value: &str) -> ParsedQuantity {
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_qos_classes() {
+        let guaranteed = PodSpec {
+            containers: vec![container("app", &[], &[("cpu", "1"), ("memory", "1Gi")])],
+            ..Default::default()
+        };
+        let best_effort = PodSpec {
+            containers: vec![container("app", &[], &[])],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            pod_oom_score_adj(&guaranteed, &capacity("8Gi")).unwrap()["app"],
+            GUARANTEED_OOM_SCORE_ADJ
+        );
+        assert_eq!(
+            pod_oom_score_adj(&best_effort, &capacity("8Gi")).unwrap()["app"],
+            BEST_EFFORT_OOM_SCORE_ADJ
+        );
+    }
+
+    #[test]
+    fn test_burstable_boundaries() {
+        let score = |request: &str, capacity_value: &str| {
+            let spec = PodSpec {
+                containers: vec![container("app", &[("memory", request)], &[])],
+                ..Default::default()
+            };
+            pod_oom_score_adj(&spec, &capacity(capacity_value)).unwrap()["app"]
+        };
+
+        // Tiny requests would score like best effort
+        assert_eq!(score("1", "8Gi"), 999);
+        assert_eq!(score("1Mi", "8Gi"), 999);
+        // 1000 * 1Gi / 3Gi = 333.33 is truncated
+        assert_eq!(score("1Gi", "3Gi"), 667);
+        assert_eq!(score("1.5Gi", "2Gi"), 250);
+        // Requests close to or above the capacity score like guaranteed + 1000
+        assert_eq!(score("1999Mi", "2000Mi"), 3);
+        assert_eq!(score("4Gi", "2Gi"), 3);
+    }
+
+    #[test]
+    fn test_sidecar_and_node_critical() {
+        let mut sidecar = container("proxy", &[("memory", "64Mi")], &[]);
+        sidecar.restart_policy = Some("Always".to_owned());
+        let mut spec = PodSpec {
+            init_containers: Some(vec![
+                container("migrate", &[("memory", "64Mi")], &[]),
+                sidecar,
+            ]),
+            containers: vec![
+                container("app", &[("memory", "1Gi")], &[]),
+                container("worker", &[("memory", "512Mi")], &[]),
+            ],
+            ..Default::default()
+        };
+
+        let scores = pod_oom_score_adj(&spec, &capacity("8Gi")).unwrap();
+        assert_eq!(scores["migrate"], 993);
+        assert_eq!(scores["proxy"], 938);
+        assert_eq!(scores["worker"], 938);
+        assert_eq!(scores["app"], 875);
+
+        spec.priority_class_name = Some(SYSTEM_NODE_CRITICAL.to_owned());
+        spec.priority = Some(2_000_001_000);
+        let scores = pod_oom_score_adj(&spec, &capacity("8Gi")).unwrap();
+        assert!(scores
+            .values()
+            .all(|score| *score == GUARANTEED_OOM_SCORE_ADJ));
+    }
+
+    #[test]
+    fn test_invalid_capacity() {
+        let spec = PodSpec::default();
+        let result = container_oom_score_adj(&spec, &Container::default(), &capacity("0"));
+
+        assert!(matches!(result, Err(OomScoreError::InvalidCapacity(_))));
+    }
+}
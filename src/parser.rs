@@ -39,18 +39,6 @@ pub(crate) fn parse_quantity_string(
         return Err(ParseQuantityError::EmptyString);
     }
 
-    let error_mapper = |err: nom::Err<nom::error::Error<&str>>| match err {
-        nom::Err::Incomplete(err) => nom::Err::Incomplete(err),
-        nom::Err::Error(err) => nom::Err::Error(nom::error::Error {
-            input: err.input.to_owned(),
-            code: err.code,
-        }),
-        nom::Err::Failure(err) => nom::Err::Failure(nom::error::Error {
-            input: err.input.to_owned(),
-            code: err.code,
-        }),
-    };
-
     let (input, signed_number) = parse_signed_number(input).map_err(error_mapper)?;
     let (input, (format, scale)) = parse_suffix(input).map_err(error_mapper)?;
     let (input, _) = eof(input).map_err(error_mapper)?;
@@ -66,6 +54,42 @@ pub(crate) fn parse_quantity_string(
     ))
 }
 
+/// Parses a signed percentage such as `12.5%` from a string and returns the
+/// remaining input and the percentage
+pub(crate) fn parse_percent_string(input: &str) -> Result<(&str, Decimal), ParseQuantityError> {
+    if input.is_empty() {
+        return Err(ParseQuantityError::EmptyString);
+    }
+
+    let (rest, signed_number) = parse_signed_number(input).map_err(error_mapper)?;
+    // Unlike quantities, percentages require a number
+    if !input[..input.len() - rest.len()].contains(|c: char| c.is_ascii_digit()) {
+        return Err(ParseQuantityError::DecimalParsingFailed);
+    }
+    let (input, _) = tag("%")(rest).map_err(error_mapper)?;
+    let (input, _) = eof(input).map_err(error_mapper)?;
+
+    Ok((
+        input,
+        Decimal::from_f64(signed_number).ok_or(ParseQuantityError::DecimalParsingFailed)?,
+    ))
+}
+
+/// Converts a parsing error into one owning its input
+fn error_mapper(err: nom::Err<nom::error::Error<&str>>) -> nom::Err<nom::error::Error<String>> {
+    match err {
+        nom::Err::Incomplete(err) => nom::Err::Incomplete(err),
+        nom::Err::Error(err) => nom::Err::Error(nom::error::Error {
+            input: err.input.to_owned(),
+            code: err.code,
+        }),
+        nom::Err::Failure(err) => nom::Err::Failure(nom::error::Error {
+            input: err.input.to_owned(),
+            code: err.code,
+        }),
+    }
+}
+
 /// Parses a signed number from a string and returns the remaining input and the
 /// signed number
 fn parse_signed_number(input: &str) -> IResult<&str, f64> {
@@ -191,6 +215,22 @@ mod tests {
         assert_eq!(quantity.to_string(), "1250000".to_owned());
     }
 
+    #[test]
+    fn test_percent_parsing() {
+        let (_, percent) = parse_percent_string("12.5%").unwrap();
+        assert_eq!(percent, Decimal::new(125, 1));
+
+        let (_, percent) = parse_percent_string("-10%").unwrap();
+        assert_eq!(percent, Decimal::new(-10, 0));
+
+        assert!(parse_percent_string("").is_err());
+        assert!(parse_percent_string("10").is_err());
+        assert!(parse_percent_string("10%%").is_err());
+        assert!(parse_percent_string("10Mi%").is_err());
+        assert!(parse_percent_string("%").is_err());
+        assert!(parse_percent_string("-%").is_err());
+    }
+
     #[test]
     fn test_incorrect_quantity() {
         let quantity = parse_quantity_string("1.25.123K");
@@ -0,0 +1,296 @@
+use std::{fmt::Display, str::FromStr};
+
+use rust_decimal::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    parser::{parse_percent_string, parse_quantity_string},
+    scale::Scale,
+    ParseQuantityError, ParsedQuantity,
+};
+
+// - Quantity or percent -
+
+/// QuantityOrPercent represents either an absolute Kubernetes quantity or a
+/// percentage of some base quantity, e.g., `100Mi` or `10%`.
+///
+/// ```rust
+/// use kube_quantity::{ParsedQuantity, QuantityOrPercent, Rounding};
+///
+/// let base: ParsedQuantity = "2Gi".try_into().unwrap();
+///
+/// let value: QuantityOrPercent = "10%".parse().unwrap();
+/// assert_eq!(value.to_string(), "10%");
+/// assert_eq!(value.resolve(&base, Rounding::Exact).to_canonical_string(), "214748364800m");
+/// assert_eq!(value.resolve(&base, Rounding::Up).to_canonical_string(), "214748365");
+///
+/// let value: QuantityOrPercent = "100Mi".parse().unwrap();
+/// assert_eq!(value.resolve(&base, Rounding::Exact).to_string(), "100Mi");
+/// ```
+#[derive(Debug, Clone)]
+pub enum QuantityOrPercent {
+    /// An absolute quantity
+    Quantity(ParsedQuantity),
+    /// A percentage of the base quantity, e.g., `12.5` for `12.5%`
+    Percent(Decimal),
+}
+
+/// Rounding applied when resolving a [`QuantityOrPercent`].
+///
+/// Results are rounded to whole milli units (e.g., millicores) if the base
+/// quantity uses a sub-unit suffix or has a fractional value, and to whole
+/// units (e.g., bytes) otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Keeps the exact result
+    #[default]
+    Exact,
+    /// Rounds towards positive infinity
+    Up,
+    /// Rounds towards negative infinity
+    Down,
+    /// Rounds to the nearest value, with halves away from zero
+    Nearest,
+}
+
+impl QuantityOrPercent {
+    /// Resolves the value against a base quantity. Absolute quantities are
+    /// returned as is, percentages are applied to the base exactly and then
+    /// rounded, keeping the suffix format of the base.
+    pub fn resolve(&self, base: &ParsedQuantity, rounding: Rounding) -> ParsedQuantity {
+        let percent = match self {
+            QuantityOrPercent::Quantity(quantity) => return quantity.clone(),
+            QuantityOrPercent::Percent(percent) => percent,
+        };
+
+        let base_value = base.to_base_decimal();
+        let value = base_value.saturating_mul(*percent) / Decimal::ONE_HUNDRED;
+
+        let decimal_places = if base.scale < Scale::One || !base_value.fract().is_zero() {
+            3
+        } else {
+            0
+        };
+        let value = rounding.round_dp(value, decimal_places);
+
+        ParsedQuantity::from_base_decimal(value, base.format().clone())
+    }
+
+    /// Returns whether the value is a percentage.
+    pub fn is_percent(&self) -> bool {
+        matches!(self, QuantityOrPercent::Percent(_))
+    }
+}
+
+impl Rounding {
+    /// Rounds the value to the given number of decimal places.
+    pub(crate) fn round_dp(self, value: Decimal, decimal_places: u32) -> Decimal {
+        let strategy = match self {
+            Rounding::Exact => return value,
+            Rounding::Up => RoundingStrategy::ToPositiveInfinity,
+            Rounding::Down => RoundingStrategy::ToNegativeInfinity,
+            Rounding::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        };
+
+        value.round_dp_with_strategy(decimal_places, strategy)
+    }
+}
+
+impl From<ParsedQuantity> for QuantityOrPercent {
+    fn from(value: ParsedQuantity) -> Self {
+        QuantityOrPercent::Quantity(value)
+    }
+}
+
+/// Values are compared exactly, independent of their suffix. Quantities never
+/// equal percentages.
+impl PartialEq for QuantityOrPercent {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (QuantityOrPercent::Quantity(lhs), QuantityOrPercent::Quantity(rhs)) => {
+                lhs.to_base_decimal() == rhs.to_base_decimal()
+            }
+            (QuantityOrPercent::Percent(lhs), QuantityOrPercent::Percent(rhs)) => lhs == rhs,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for QuantityOrPercent {}
+
+impl Display for QuantityOrPercent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuantityOrPercent::Quantity(quantity) => write!(f, "{}", quantity),
+            QuantityOrPercent::Percent(percent) => write!(f, "{}%", percent.normalize()),
+        }
+    }
+}
+
+impl TryFrom<&str> for QuantityOrPercent {
+    type Error = ParseQuantityError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.ends_with('%') {
+            parse_percent_string(value).map(|(_, percent)| QuantityOrPercent::Percent(percent))
+        } else {
+            parse_quantity_string(value).map(|(_, quantity)| QuantityOrPercent::Quantity(quantity))
+        }
+    }
+}
+
+impl TryFrom<String> for QuantityOrPercent {
+    type Error = ParseQuantityError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.as_str().try_into()
+    }
+}
+
+impl FromStr for QuantityOrPercent {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for QuantityOrPercent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for QuantityOrPercent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = QuantityOrPercent;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a quantity, a percentage or a number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.try_into().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(ParsedQuantity::from(Decimal::from(v)).into())
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(ParsedQuantity::from(Decimal::from(v)).into())
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Decimal::from_f64(v)
+                    .map(|value| ParsedQuantity::from(value).into())
+                    .ok_or_else(|| E::custom(ParseQuantityError::DecimalParsingFailed))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(input: &str) -> QuantityOrPercent {
+        input.parse().unwrap()
+    }
+
+    fn quantity(input: &str) -> ParsedQuantity {
+        input.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_parsing_and_display() {
+        assert_eq!(value("10%"), QuantityOrPercent::Percent(Decimal::TEN));
+        assert_eq!(value("12.50%").to_string(), "12.5%");
+        assert_eq!(
+            value("1Gi"),
+            QuantityOrPercent::Quantity(quantity("1024Mi"))
+        );
+        assert_eq!(value("250m").to_string(), "250m");
+        assert!(!value("1Gi").is_percent());
+
+        for input in ["", "%", "10 %", "1Gi%", "ten%", "1.2.3"] {
+            assert!(QuantityOrPercent::try_from(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_rounding() {
+        let cpu = quantity("500m");
+        let third = QuantityOrPercent::Percent(Decimal::new(3333, 2));
+
+        assert_eq!(
+            third.resolve(&cpu, Rounding::Exact).to_base_decimal(),
+            Decimal::new(16665, 5)
+        );
+        assert_eq!(
+            third.resolve(&cpu, Rounding::Up).to_canonical_string(),
+            "167m"
+        );
+        assert_eq!(
+            third.resolve(&cpu, Rounding::Down).to_canonical_string(),
+            "166m"
+        );
+        assert_eq!(
+            third.resolve(&cpu, Rounding::Nearest).to_canonical_string(),
+            "167m"
+        );
+
+        let memory = quantity("1Ki");
+        assert_eq!(
+            value("10%")
+                .resolve(&memory, Rounding::Up)
+                .to_canonical_string(),
+            "103"
+        );
+        assert_eq!(
+            value("10%")
+                .resolve(&memory, Rounding::Down)
+                .to_canonical_string(),
+            "102"
+        );
+        assert_eq!(
+            value("50%")
+                .resolve(&memory, Rounding::Exact)
+                .to_canonical_string(),
+            "512"
+        );
+        assert_eq!(
+            value("-10%")
+                .resolve(&memory, Rounding::Down)
+                .to_canonical_string(),
+            "-103"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let values: Vec<QuantityOrPercent> =
+            serde_json::from_str(r#"["10%", "100Mi", 3, 1.5]"#).unwrap();
+
+        assert_eq!(
+            values,
+            vec![value("10%"), value("100Mi"), value("3"), value("1500m")]
+        );
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            r#"["10%","100Mi","3","1.5"]"#
+        );
+        assert!(serde_json::from_str::<QuantityOrPercent>(r#""10%%""#).is_err());
+    }
+}
@@ -0,0 +1,531 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use k8s_openapi::{
+    api::{
+        apps::v1::{Deployment, StatefulSet},
+        core::v1::PodTemplateSpec,
+    },
+    apimachinery::pkg::api::resource::Quantity,
+};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::{
+    resources::{
+        fits_within, from_base_map, parse_resource_list, pod_requests, to_base_map,
+        ParsedResourceList,
+    },
+    ParseQuantityError, ParsedQuantity,
+};
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum PlannerError {
+    /// No node shape was given to pack onto
+    #[error("no node shapes given")]
+    NoNodeShapes,
+
+    /// The workload has no pod template
+    #[error("workload {0} has no pod template")]
+    MissingPodTemplate(String),
+
+    /// A quantity of a workload or node shape could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Inputs ---
+
+/// A set of identical pods, e.g., the replicas of a Deployment.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    /// Name used as prefix for the pods of the workload
+    pub name: String,
+    /// Number of pods
+    pub replicas: u32,
+    /// Effective requests of a single pod
+    pub requests: ParsedResourceList,
+}
+
+impl Workload {
+    /// Creates a workload from a name, replica count and per-pod requests.
+    pub fn new(name: impl Into<String>, replicas: u32, requests: ParsedResourceList) -> Self {
+        Self {
+            name: name.into(),
+            replicas,
+            requests,
+        }
+    }
+
+    /// Creates a workload from a Deployment, defaulting to a single replica.
+    pub fn from_deployment(deployment: &Deployment) -> Result<Self, PlannerError> {
+        let name = deployment.metadata.name.clone().unwrap_or_default();
+        let spec = deployment
+            .spec
+            .as_ref()
+            .ok_or_else(|| PlannerError::MissingPodTemplate(name.clone()))?;
+
+        Self::from_template(name, spec.replicas, &spec.template)
+    }
+
+    /// Creates a workload from a StatefulSet, defaulting to a single replica.
+    pub fn from_stateful_set(stateful_set: &StatefulSet) -> Result<Self, PlannerError> {
+        let name = stateful_set.metadata.name.clone().unwrap_or_default();
+        let spec = stateful_set
+            .spec
+            .as_ref()
+            .ok_or_else(|| PlannerError::MissingPodTemplate(name.clone()))?;
+
+        Self::from_template(name, spec.replicas, &spec.template)
+    }
+
+    fn from_template(
+        name: String,
+        replicas: Option<i32>,
+        template: &PodTemplateSpec,
+    ) -> Result<Self, PlannerError> {
+        let spec = template
+            .spec
+            .as_ref()
+            .ok_or_else(|| PlannerError::MissingPodTemplate(name.clone()))?;
+
+        Ok(Self {
+            requests: pod_requests(spec)?,
+            replicas: replicas.unwrap_or(1).max(0).unsigned_abs(),
+            name,
+        })
+    }
+}
+
+/// A node type that pods can be packed onto.
+#[derive(Debug, Clone)]
+pub struct NodeShape {
+    /// Name of the node shape, e.g., the instance type
+    pub name: String,
+    /// Allocatable resources of a single node. If it contains `pods`, every
+    /// pod consumes one unit of it.
+    pub allocatable: ParsedResourceList,
+}
+
+impl NodeShape {
+    /// Creates a node shape from its allocatable resources.
+    pub fn new(name: impl Into<String>, allocatable: ParsedResourceList) -> Self {
+        Self {
+            name: name.into(),
+            allocatable,
+        }
+    }
+
+    /// Creates a node shape from a Kubernetes resource list, e.g., a node's
+    /// `status.allocatable`.
+    pub fn from_resource_list(
+        name: impl Into<String>,
+        allocatable: &BTreeMap<String, Quantity>,
+    ) -> Result<Self, PlannerError> {
+        Ok(Self::new(name, parse_resource_list(allocatable)?))
+    }
+}
+
+/// Heuristic used to assign pods to nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    /// Places each pod, largest first, on the first node it fits on
+    #[default]
+    FirstFitDecreasing,
+    /// Places each pod, largest first, on the node with the least capacity
+    /// left after placing it
+    BestFit,
+}
+
+// --- Report ---
+
+/// A node of the plan with the pods packed onto it.
+#[derive(Debug, Clone)]
+pub struct PackedNode {
+    /// Names of the pods on the node, i.e., `<workload>-<index>`
+    pub pods: Vec<String>,
+    /// Sum of the requests of the pods on the node
+    pub used: ParsedResourceList,
+    /// Allocatable capacity left on the node
+    pub free: ParsedResourceList,
+    /// Resource with the highest utilization on the node
+    pub bottleneck: Option<String>,
+}
+
+/// Result of packing workloads onto a node shape.
+#[derive(Debug, Clone)]
+pub struct PlanReport {
+    /// Name of the node shape used
+    pub shape: String,
+    /// Heuristic used
+    pub heuristic: Heuristic,
+    /// Nodes needed to fit the workloads
+    pub nodes: Vec<PackedNode>,
+    /// Lower bound of the number of nodes, ignoring fragmentation
+    pub lower_bound: usize,
+    /// Ratio of requested to allocatable capacity over all nodes, per resource
+    pub utilization: BTreeMap<String, Decimal>,
+    /// Allocatable capacity left unused over all nodes, per resource
+    pub stranded: ParsedResourceList,
+    /// Resource with the highest utilization over all nodes
+    pub bottleneck: Option<String>,
+    /// Pods that do not fit on an empty node of the shape
+    pub unschedulable: Vec<String>,
+}
+
+impl PlanReport {
+    /// Returns the number of nodes of the plan.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+// --- Planner ---
+
+/// Packs the workloads onto each node shape and returns the plan needing the
+/// fewest nodes. Plans leaving fewer pods unschedulable are always preferred,
+/// ties are resolved by the order of the shapes.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use kube_quantity::planner::{plan, Heuristic, NodeShape, Workload};
+/// use kube_quantity::ParsedQuantity;
+///
+/// let list = |cpu: &str, memory: &str| {
+///     BTreeMap::from([
+///         ("cpu".to_string(), ParsedQuantity::try_from(cpu).unwrap()),
+///         ("memory".to_string(), ParsedQuantity::try_from(memory).unwrap()),
+///     ])
+/// };
+///
+/// let workloads = [Workload::new("web", 6, list("500m", "1Gi"))];
+/// let shapes = [NodeShape::new("small", list("2", "8Gi"))];
+///
+/// let report = plan(&workloads, &shapes, Heuristic::FirstFitDecreasing).unwrap();
+///
+/// assert_eq!(report.node_count(), 2);
+/// assert_eq!(report.bottleneck.as_deref(), Some("cpu"));
+/// assert_eq!(report.stranded["memory"].to_string(), "10Gi");
+/// ```
+pub fn plan(
+    workloads: &[Workload],
+    shapes: &[NodeShape],
+    heuristic: Heuristic,
+) -> Result<PlanReport, PlannerError> {
+    shapes
+        .iter()
+        .map(|shape| plan_for_shape(workloads, shape, heuristic))
+        .reduce(|best, report| {
+            let key = |report: &PlanReport| (report.unschedulable.len(), report.node_count());
+            if key(&report) < key(&best) {
+                report
+            } else {
+                best
+            }
+        })
+        .ok_or(PlannerError::NoNodeShapes)
+}
+
+/// Packs the workloads onto nodes of a single shape.
+pub fn plan_for_shape(
+    workloads: &[Workload],
+    shape: &NodeShape,
+    heuristic: Heuristic,
+) -> PlanReport {
+    let allocatable = to_base_map(&shape.allocatable);
+    let tracks_pods = allocatable.contains_key("pods");
+
+    // Expand the workloads into single pods, largest first
+    let mut pods: Vec<(String, BTreeMap<String, Decimal>, Decimal)> = workloads
+        .iter()
+        .flat_map(|workload| {
+            let mut demand = to_base_map(&workload.requests);
+            if tracks_pods {
+                *demand.entry("pods".to_owned()).or_default() += Decimal::ONE;
+            }
+            let size = dominant_share(&demand, &allocatable);
+
+            (0..workload.replicas)
+                .map(move |index| (format!("{}-{index}", workload.name), demand.clone(), size))
+        })
+        .collect();
+    pods.sort_by_key(|(_, _, size)| Reverse(*size));
+
+    let mut nodes: Vec<(Vec<String>, BTreeMap<String, Decimal>)> = Vec::new();
+    let mut unschedulable = Vec::new();
+
+    for (name, demand, _) in pods {
+        if !fits_within(&demand, &allocatable) {
+            unschedulable.push(name);
+            continue;
+        }
+
+        let candidates = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, free))| fits_within(&demand, free));
+
+        let target = match heuristic {
+            Heuristic::FirstFitDecreasing => candidates.map(|(index, _)| index).next(),
+            Heuristic::BestFit => candidates
+                .min_by_key(|(_, (_, free))| remaining_share(free, &demand, &allocatable))
+                .map(|(index, _)| index),
+        };
+
+        let index = target.unwrap_or_else(|| {
+            nodes.push((Vec::new(), allocatable.clone()));
+            nodes.len() - 1
+        });
+
+        let (node_pods, free) = &mut nodes[index];
+        node_pods.push(name);
+        for (resource, value) in &demand {
+            if let Some(free) = free.get_mut(resource) {
+                *free -= value;
+            }
+        }
+    }
+
+    build_report(
+        shape,
+        heuristic,
+        &allocatable,
+        nodes,
+        unschedulable,
+        workloads,
+    )
+}
+
+fn build_report(
+    shape: &NodeShape,
+    heuristic: Heuristic,
+    allocatable: &BTreeMap<String, Decimal>,
+    nodes: Vec<(Vec<String>, BTreeMap<String, Decimal>)>,
+    unschedulable: Vec<String>,
+    workloads: &[Workload],
+) -> PlanReport {
+    let node_count = Decimal::from(nodes.len());
+
+    let mut total_free: BTreeMap<String, Decimal> = allocatable
+        .keys()
+        .map(|resource| (resource.clone(), Decimal::ZERO))
+        .collect();
+
+    let nodes: Vec<PackedNode> = nodes
+        .into_iter()
+        .map(|(pods, free)| {
+            let used: BTreeMap<String, Decimal> = free
+                .iter()
+                .map(|(resource, free)| (resource.clone(), allocatable[resource] - free))
+                .collect();
+
+            for (resource, free) in &free {
+                *total_free.entry(resource.clone()).or_default() += free;
+            }
+
+            PackedNode {
+                pods,
+                bottleneck: bottleneck(&utilization(&used, allocatable, Decimal::ONE)),
+                used: suffixed(&used),
+                free: suffixed(&free),
+            }
+        })
+        .collect();
+
+    let total_used: BTreeMap<String, Decimal> = total_free
+        .iter()
+        .map(|(resource, free)| (resource.clone(), allocatable[resource] * node_count - free))
+        .collect();
+    let utilization = utilization(&total_used, allocatable, node_count);
+
+    // Without fragmentation, every resource could be filled up completely
+    let mut demand: BTreeMap<String, Decimal> = BTreeMap::new();
+    for workload in workloads {
+        for (resource, value) in to_base_map(&workload.requests) {
+            *demand.entry(resource).or_default() += value * Decimal::from(workload.replicas);
+        }
+        if allocatable.contains_key("pods") {
+            *demand.entry("pods".to_owned()).or_default() += Decimal::from(workload.replicas);
+        }
+    }
+    let lower_bound = demand
+        .iter()
+        .filter_map(|(resource, value)| {
+            let capacity = allocatable.get(resource).filter(|value| !value.is_zero())?;
+            (value / capacity).ceil().try_into().ok()
+        })
+        .max()
+        .unwrap_or_default();
+
+    PlanReport {
+        shape: shape.name.clone(),
+        heuristic,
+        bottleneck: bottleneck(&utilization),
+        nodes,
+        lower_bound,
+        utilization,
+        stranded: suffixed(&total_free),
+        unschedulable,
+    }
+}
+
+/// Converts base unit values into quantities with suffixes, e.g., `10Gi`
+/// instead of `10737418240`.
+fn suffixed(list: &BTreeMap<String, Decimal>) -> ParsedResourceList {
+    from_base_map(list)
+        .into_iter()
+        .map(|(resource, quantity)| {
+            let quantity = ParsedQuantity::from_base_decimal_suffixed(
+                quantity.to_base_decimal(),
+                quantity.format().clone(),
+            );
+            (resource, quantity)
+        })
+        .collect()
+}
+
+/// Returns the largest share of the allocatable capacity a pod requests.
+fn dominant_share(
+    demand: &BTreeMap<String, Decimal>,
+    allocatable: &BTreeMap<String, Decimal>,
+) -> Decimal {
+    demand
+        .iter()
+        .filter_map(|(resource, value)| {
+            let capacity = allocatable.get(resource).filter(|value| !value.is_zero())?;
+            Some(value / capacity)
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+/// Returns the sum of the capacity shares left on a node after placing a pod.
+fn remaining_share(
+    free: &BTreeMap<String, Decimal>,
+    demand: &BTreeMap<String, Decimal>,
+    allocatable: &BTreeMap<String, Decimal>,
+) -> Decimal {
+    free.iter()
+        .filter_map(|(resource, free)| {
+            let capacity = allocatable.get(resource).filter(|value| !value.is_zero())?;
+            let demand = demand.get(resource).copied().unwrap_or_default();
+            Some((free - demand) / capacity)
+        })
+        .sum()
+}
+
+fn utilization(
+    used: &BTreeMap<String, Decimal>,
+    allocatable: &BTreeMap<String, Decimal>,
+    node_count: Decimal,
+) -> BTreeMap<String, Decimal> {
+    used.iter()
+        .filter_map(|(resource, used)| {
+            let capacity = allocatable[resource] * node_count;
+            (!capacity.is_zero()).then(|| (resource.clone(), (used / capacity).normalize()))
+        })
+        .collect()
+}
+
+fn bottleneck(utilization: &BTreeMap<String, Decimal>) -> Option<String> {
+    utilization
+        .iter()
+        .filter(|(_, ratio)| ratio.is_sign_positive() && !ratio.is_zero())
+        .max_by(|lhs, rhs| lhs.1.cmp(rhs.1).then_with(|| rhs.0.cmp(lhs.0)))
+        .map(|(resource, _)| resource.clone())
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parsed_list;
+
+    #[test]
+    fn test_first_fit_decreasing() {
+        let workloads = [
+            Workload::new(
+                "small",
+                4,
+                parsed_list(&[("cpu", "250m"), ("memory", "512Mi")]),
+            ),
+            Workload::new(
+                "large",
+                2,
+                parsed_list(&[("cpu", "1500m"), ("memory", "2Gi")]),
+            ),
+        ];
+        let shape = NodeShape::new("node", parsed_list(&[("cpu", "2"), ("memory", "4Gi")]));
+
+        let report = plan_for_shape(&workloads, &shape, Heuristic::FirstFitDecreasing);
+
+        assert_eq!(report.node_count(), 2);
+        assert_eq!(report.lower_bound, 2);
+        assert_eq!(report.nodes[0].pods, ["large-0", "small-0", "small-1"]);
+        assert_eq!(report.nodes[1].pods, ["large-1", "small-2", "small-3"]);
+        assert_eq!(report.utilization["cpu"], Decimal::ONE);
+        assert_eq!(report.utilization["memory"], Decimal::new(75, 2));
+        assert_eq!(report.bottleneck.as_deref(), Some("cpu"));
+        assert_eq!(report.stranded["cpu"].to_string(), "0");
+        assert_eq!(report.stranded["memory"].to_string(), "2Gi");
+    }
+
+    #[test]
+    fn test_best_fit() {
+        let workloads = [
+            Workload::new("a", 1, parsed_list(&[("cpu", "3")])),
+            Workload::new("b", 1, parsed_list(&[("cpu", "2")])),
+            Workload::new("c", 1, parsed_list(&[("cpu", "2")])),
+            Workload::new("d", 1, parsed_list(&[("cpu", "1")])),
+        ];
+        let shape = NodeShape::new("node", parsed_list(&[("cpu", "4")]));
+
+        let ffd = plan_for_shape(&workloads, &shape, Heuristic::FirstFitDecreasing);
+        let best_fit = plan_for_shape(&workloads, &shape, Heuristic::BestFit);
+
+        assert_eq!(ffd.node_count(), 2);
+        assert_eq!(best_fit.node_count(), 2);
+        assert_eq!(best_fit.nodes[0].pods, ["a-0", "d-0"]);
+        assert_eq!(best_fit.nodes[1].pods, ["b-0", "c-0"]);
+    }
+
+    #[test]
+    fn test_pods_limit_and_unschedulable() {
+        let workloads = [
+            Workload::new("tiny", 5, parsed_list(&[("cpu", "10m")])),
+            Workload::new("gpu", 1, parsed_list(&[("nvidia.com/gpu", "1")])),
+        ];
+        let shape = NodeShape::new("node", parsed_list(&[("cpu", "4"), ("pods", "2")]));
+
+        let report = plan_for_shape(&workloads, &shape, Heuristic::FirstFitDecreasing);
+
+        assert_eq!(report.node_count(), 3);
+        assert_eq!(report.bottleneck.as_deref(), Some("pods"));
+        assert_eq!(report.unschedulable, ["gpu-0"]);
+    }
+
+    #[test]
+    fn test_plan_picks_shape_with_fewest_nodes() {
+        let workloads = [Workload::new(
+            "web",
+            8,
+            parsed_list(&[("cpu", "1"), ("memory", "1Gi")]),
+        )];
+        let shapes = [
+            NodeShape::new("small", parsed_list(&[("cpu", "2"), ("memory", "8Gi")])),
+            NodeShape::new("large", parsed_list(&[("cpu", "8"), ("memory", "16Gi")])),
+        ];
+
+        let report = plan(&workloads, &shapes, Heuristic::BestFit).unwrap();
+
+        assert_eq!(report.shape, "large");
+        assert_eq!(report.node_count(), 1);
+        assert!(matches!(
+            plan(&workloads, &[], Heuristic::BestFit),
+            Err(PlannerError::NoNodeShapes)
+        ));
+    }
+}
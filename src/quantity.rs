@@ -266,6 +266,74 @@ impl ParsedQuantity {
         )
     }
 
+    /// Returns the canonical string representation Kubernetes uses for the
+    /// quantity, e.g., in API responses and error messages.
+    ///
+    /// Values are rounded up to nano precision. Binary quantities keep a
+    /// binary suffix only if they are integral and at least 1024, all other
+    /// values use the largest decimal suffix without a fractional part.
+    ///
+    /// ```rust
+    /// use kube_quantity::ParsedQuantity;
+    ///
+    /// let canonical = |value: &str| ParsedQuantity::try_from(value).unwrap().to_canonical_string();
+    ///
+    /// assert_eq!(canonical("0.5"), "500m");
+    /// assert_eq!(canonical("2000"), "2k");
+    /// assert_eq!(canonical("1.5Gi"), "1536Mi");
+    /// assert_eq!(canonical("0.5Ki"), "512");
+    /// ```
+    pub fn to_canonical_string(&self) -> String {
+        let value = self
+            .to_base_decimal()
+            .round_dp_with_strategy(9, RoundingStrategy::AwayFromZero)
+            .normalize();
+
+        if value.is_zero() {
+            return "0".to_owned();
+        }
+
+        if self.format == Format::BinarySI
+            && value.abs() >= Decimal::from(1024)
+            && value.fract().is_zero()
+        {
+            if let Some(mut mantissa) = value.to_i128() {
+                let mut exponent = 0;
+                while exponent < 6 && mantissa % 1024 == 0 {
+                    mantissa /= 1024;
+                    exponent += 1;
+                }
+
+                let scale = Scale::try_from(exponent).unwrap_or_default();
+                return format!(
+                    "{mantissa}{}",
+                    scale_format_to_string(&scale, &Format::BinarySI)
+                );
+            }
+        }
+
+        // Find the largest exponent that is a multiple of three and leaves no
+        // fractional part
+        let mut mantissa = value.mantissa();
+        let mut exponent = -(value.scale() as i32);
+        while mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        while exponent.rem_euclid(3) != 0 {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+
+        match Scale::try_from(exponent / 3) {
+            Ok(scale) => format!(
+                "{mantissa}{}",
+                scale_format_to_string(&scale, &Format::DecimalSI)
+            ),
+            Err(_) => format!("{mantissa}e{exponent}"),
+        }
+    }
+
     /// Returns the value of the quantity as an f64.
     ///
     /// ```rust
@@ -482,6 +550,142 @@ impl ParsedQuantity {
     }
 }
 
+impl ParsedQuantity {
+    /// Returns the exact value of the quantity in base units (e.g., cores or
+    /// bytes), saturating at the bounds of `Decimal`.
+    pub(crate) fn to_base_decimal(&self) -> Decimal {
+        self.value
+            .saturating_mul(scale_multiplier(&self.scale, &self.format))
+    }
+
+    /// Returns the exact value of the quantity in base units, or `None` if it
+    /// does not fit into a `Decimal`.
+    pub(crate) fn checked_base_decimal(&self) -> Option<Decimal> {
+        self.value
+            .checked_mul(scale_multiplier(&self.scale, &self.format))
+    }
+
+    /// Creates a quantity from a value expressed in base units, keeping the
+    /// given suffix format.
+    pub(crate) fn from_base_decimal(value: Decimal, format: Format) -> Self {
+        Self {
+            value: value.normalize(),
+            scale: Scale::One,
+            format,
+        }
+    }
+
+    /// Creates a quantity from a value expressed in base units with the
+    /// largest suffix that represents it exactly, e.g., `10Gi` instead of
+    /// `10737418240`. Follows the suffix choice of `to_canonical_string`
+    /// without rounding the value or going through a string.
+    pub(crate) fn from_base_decimal_suffixed(value: Decimal, format: Format) -> Self {
+        let value = value.normalize();
+
+        if value.is_zero() {
+            return Self::from_base_decimal(value, format);
+        }
+
+        if format == Format::BinarySI
+            && value.abs() >= Decimal::from(1024)
+            && value.fract().is_zero()
+        {
+            let base = Decimal::from(1024);
+            let mut mantissa = value;
+            let mut exponent = 0;
+            while exponent < 6 && (mantissa % base).is_zero() {
+                mantissa /= base;
+                exponent += 1;
+            }
+
+            return Self {
+                value: mantissa,
+                scale: Scale::try_from(exponent).unwrap_or_default(),
+                format: Format::BinarySI,
+            };
+        }
+
+        // Find the largest exponent that is a multiple of three and leaves no
+        // fractional part, within the range of the decimal suffixes
+        let mut mantissa = value.mantissa();
+        let mut exponent = -(value.scale() as i32);
+        while mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        let scale = Scale::try_from(exponent.div_euclid(3).clamp(-3, 6)).unwrap_or_default();
+        let format = if scale == Scale::One {
+            format
+        } else {
+            Format::DecimalSI
+        };
+
+        Self {
+            value: (value / scale_multiplier(&scale, &Format::DecimalSI)).normalize(),
+            scale,
+            format,
+        }
+    }
+
+    /// Creates a decimal quantity from a value expressed in thousandths of
+    /// base units (e.g., millicores).
+    pub(crate) fn from_milli_decimal(value: Decimal) -> Self {
+        Self {
+            value: value.normalize(),
+            scale: Scale::Milli,
+            format: Format::DecimalSI,
+        }
+    }
+
+    /// Creates a decimal quantity from a value expressed in billionths of
+    /// base units (e.g., nanocores).
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_nano_decimal(value: Decimal) -> Self {
+        Self {
+            value: value.normalize(),
+            scale: Scale::Nano,
+            format: Format::DecimalSI,
+        }
+    }
+
+    /// Returns the value in thousandths of base units, rounded away from zero
+    /// like `Quantity.MilliValue()` and saturating at the bounds of `i64`.
+    pub(crate) fn to_milli_i64(&self) -> i64 {
+        let milli = self.to_base_decimal().saturating_mul(Decimal::ONE_THOUSAND);
+
+        milli
+            .round_dp_with_strategy(0, RoundingStrategy::AwayFromZero)
+            .to_i64()
+            .unwrap_or(if milli.is_sign_negative() {
+                i64::MIN
+            } else {
+                i64::MAX
+            })
+    }
+
+    /// Returns the suffix format of the quantity.
+    pub(crate) fn format(&self) -> &Format {
+        &self.format
+    }
+}
+
+/// Returns the exact multiplier of a scale in the given format.
+/// Negative scales are always base-10, as there are no binary suffixes for them.
+fn scale_multiplier(scale: &Scale, format: &Format) -> Decimal {
+    let exponent: i32 = scale.into();
+
+    if exponent < 0 {
+        return Decimal::new(1, exponent.unsigned_abs() * 3);
+    }
+
+    let base = match format {
+        Format::BinarySI => Decimal::from(1024),
+        Format::DecimalSI => Decimal::from(1000),
+    };
+
+    (0..exponent).fold(Decimal::ONE, |acc, _| acc * base)
+}
+
 fn normalize_scales(lhs: &mut ParsedQuantity, rhs: &mut ParsedQuantity) {
     let rhs_scale: i32 = (&rhs.scale).into();
     let lhs_scale: i32 = (&lhs.scale).into();
@@ -674,4 +878,62 @@ mod tests {
         assert_eq!(quantity.scale, Scale::One);
         assert_eq!(quantity.format, Format::BinarySI);
     }
+
+    #[test]
+    fn test_canonical_string() {
+        let canonical = |value: &str| {
+            ParsedQuantity::try_from(value)
+                .unwrap()
+                .to_canonical_string()
+        };
+
+        assert_eq!(canonical("0"), "0");
+        assert_eq!(canonical("100m"), "100m");
+        assert_eq!(canonical("1500m"), "1500m");
+        assert_eq!(canonical("1.5"), "1500m");
+        assert_eq!(canonical("-0.25"), "-250m");
+        assert_eq!(canonical("1e3"), "1k");
+        assert_eq!(canonical("12e6"), "12M");
+        assert_eq!(canonical("1024"), "1024");
+        assert_eq!(canonical("1Ki"), "1Ki");
+        assert_eq!(canonical("2048Ki"), "2Mi");
+        assert_eq!(canonical("1.5Ki"), "1536");
+        assert_eq!(canonical("0.1Ki"), "102400m");
+        assert_eq!(canonical("100000000n"), "100m");
+        assert_eq!(canonical("1.0000000001"), "1000000001n");
+    }
+
+    #[test]
+    fn test_from_base_decimal_suffixed() {
+        let suffixed = |value: &str, format: Format| {
+            ParsedQuantity::from_base_decimal_suffixed(value.parse().unwrap(), format).to_string()
+        };
+
+        assert_eq!(suffixed("0", Format::BinarySI), "0");
+        assert_eq!(suffixed("10737418240", Format::BinarySI), "10Gi");
+        assert_eq!(suffixed("1536", Format::BinarySI), "1536");
+        assert_eq!(suffixed("512", Format::BinarySI), "512");
+        assert_eq!(suffixed("0.5", Format::BinarySI), "500m");
+        assert_eq!(suffixed("2000", Format::DecimalSI), "2k");
+        assert_eq!(suffixed("-0.25", Format::DecimalSI), "-250m");
+        assert_eq!(suffixed("1.0000000001", Format::DecimalSI), "1000000000.1n");
+        assert_eq!(
+            suffixed("12000000000000000000000", Format::DecimalSI),
+            "12000E"
+        );
+
+        // Values beyond the exact range of f64 keep every digit
+        assert_eq!(
+            suffixed("9007199254740993", Format::BinarySI),
+            "9007199254740993"
+        );
+        assert_eq!(
+            suffixed("9223372036854770001", Format::DecimalSI),
+            "9223372036854770001"
+        );
+        assert_eq!(
+            suffixed("9007199254740993000", Format::DecimalSI),
+            "9007199254740993k"
+        );
+    }
 }
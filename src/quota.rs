@@ -0,0 +1,804 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::{
+    PersistentVolumeClaim, Pod, PodSpec, ResourceQuota, ScopedResourceSelectorRequirement, Service,
+};
+use rust_decimal::Decimal;
+
+use crate::{
+    resources::{
+        add_lists, parse_optional_list, pod_limits, pod_qos_class, pod_requests, quantity_for,
+        ParsedResourceList, QosClass,
+    },
+    ParseQuantityError, ParsedQuantity,
+};
+
+/// Suffix of storage class scoped quota resources, e.g.,
+/// `gold.storageclass.storage.k8s.io/requests.storage`
+pub const STORAGE_CLASS_SUFFIX: &str = ".storageclass.storage.k8s.io/";
+
+/// Legacy storage class annotation of persistent volume claims, which takes
+/// precedence over `spec.storageClassName`
+const STORAGE_CLASS_ANNOTATION: &str = "volume.beta.kubernetes.io/storage-class";
+
+/// Core resources which are counted under their plain name in addition to
+/// `count/<resource>`
+const LEGACY_OBJECT_COUNTS: [&str; 4] = [
+    "configmaps",
+    "replicationcontrollers",
+    "resourcequotas",
+    "secrets",
+];
+
+// --- Objects ---
+
+/// An object that is charged against a ResourceQuota.
+#[derive(Debug, Clone)]
+pub enum QuotaObject {
+    /// A pod, charged with its compute resources
+    Pod(Box<Pod>),
+    /// A claim, charged with its requested storage
+    PersistentVolumeClaim(Box<PersistentVolumeClaim>),
+    /// A service, charged with its load balancers and node ports
+    Service(Box<Service>),
+    /// Any other object, only counted as `count/<resource>`, where the
+    /// resource is the plural name qualified by its group, e.g.,
+    /// `deployments.apps` or `configmaps` for the core group
+    Object(String),
+}
+
+impl From<Pod> for QuotaObject {
+    fn from(value: Pod) -> Self {
+        QuotaObject::Pod(Box::new(value))
+    }
+}
+
+impl From<PersistentVolumeClaim> for QuotaObject {
+    fn from(value: PersistentVolumeClaim) -> Self {
+        QuotaObject::PersistentVolumeClaim(Box::new(value))
+    }
+}
+
+impl From<Service> for QuotaObject {
+    fn from(value: Service) -> Self {
+        QuotaObject::Service(Box::new(value))
+    }
+}
+
+/// Returns the quota usage of a single object, the way the quota admission
+/// plugin computes it.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{
+///     PersistentVolumeClaim, PersistentVolumeClaimSpec, VolumeResourceRequirements,
+/// };
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::quota::{object_usage, QuotaObject};
+///
+/// let claim = PersistentVolumeClaim {
+///     spec: Some(PersistentVolumeClaimSpec {
+///         storage_class_name: Some("gold".to_string()),
+///         resources: Some(VolumeResourceRequirements {
+///             requests: Some(BTreeMap::from([("storage".to_string(), Quantity("10Gi".to_string()))])),
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+///
+/// let usage = object_usage(&claim.into()).unwrap();
+///
+/// assert_eq!(usage["requests.storage"].to_string(), "10Gi");
+/// assert_eq!(usage["gold.storageclass.storage.k8s.io/requests.storage"].to_string(), "10Gi");
+/// assert_eq!(usage["count/persistentvolumeclaims"].to_string(), "1");
+/// ```
+pub fn object_usage(object: &QuotaObject) -> Result<ParsedResourceList, ParseQuantityError> {
+    match object {
+        QuotaObject::Pod(pod) => pod_usage(pod),
+        QuotaObject::PersistentVolumeClaim(claim) => claim_usage(claim),
+        QuotaObject::Service(service) => Ok(service_usage(service)),
+        QuotaObject::Object(resource) => {
+            let mut usage = ParsedResourceList::from([(format!("count/{resource}"), count(1))]);
+            if LEGACY_OBJECT_COUNTS.contains(&resource.as_str()) {
+                usage.insert(resource.clone(), count(1));
+            }
+            Ok(usage)
+        }
+    }
+}
+
+fn pod_usage(pod: &Pod) -> Result<ParsedResourceList, ParseQuantityError> {
+    // Object counts track every pod, compute resources only running ones
+    let mut usage = ParsedResourceList::from([("count/pods".to_owned(), count(1))]);
+
+    let phase = pod
+        .status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref());
+    let Some(spec) = pod.spec.as_ref() else {
+        return Ok(usage);
+    };
+    if matches!(phase, Some("Succeeded" | "Failed")) {
+        return Ok(usage);
+    }
+
+    let requests = pod_requests(spec)?;
+    let limits = pod_limits(spec)?;
+
+    usage.insert("pods".to_owned(), count(1));
+    for resource in ["cpu", "memory", "ephemeral-storage"] {
+        if let Some(request) = requests.get(resource) {
+            usage.insert(resource.to_owned(), request.clone());
+            usage.insert(format!("requests.{resource}"), request.clone());
+        }
+        if let Some(limit) = limits.get(resource) {
+            usage.insert(format!("limits.{resource}"), limit.clone());
+        }
+    }
+    for (resource, request) in &requests {
+        if resource.starts_with("hugepages-") {
+            usage.insert(resource.clone(), request.clone());
+            usage.insert(format!("requests.{resource}"), request.clone());
+        }
+        if is_extended_resource(resource) {
+            usage.insert(format!("requests.{resource}"), request.clone());
+        }
+    }
+
+    Ok(usage)
+}
+
+fn claim_usage(claim: &PersistentVolumeClaim) -> Result<ParsedResourceList, ParseQuantityError> {
+    let mut usage = ParsedResourceList::from([
+        ("persistentvolumeclaims".to_owned(), count(1)),
+        ("count/persistentvolumeclaims".to_owned(), count(1)),
+    ]);
+
+    let storage_class = claim
+        .metadata
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(STORAGE_CLASS_ANNOTATION))
+        .or(claim
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.storage_class_name.as_ref()))
+        .filter(|storage_class| !storage_class.is_empty());
+
+    if let Some(storage_class) = storage_class {
+        usage.insert(
+            format!("{storage_class}{STORAGE_CLASS_SUFFIX}persistentvolumeclaims"),
+            count(1),
+        );
+    }
+
+    // Claims are charged with the larger of the requested and allocated size
+    let requested = parse_optional_list(
+        claim
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.resources.as_ref())
+            .and_then(|resources| resources.requests.as_ref()),
+    )?
+    .remove("storage");
+    let allocated = parse_optional_list(
+        claim
+            .status
+            .as_ref()
+            .and_then(|status| status.allocated_resources.as_ref()),
+    )?
+    .remove("storage");
+
+    let storage = match (requested, allocated) {
+        (Some(requested), Some(allocated)) => Some(
+            if allocated.to_base_decimal() > requested.to_base_decimal() {
+                allocated
+            } else {
+                requested
+            },
+        ),
+        (requested, allocated) => requested.or(allocated),
+    };
+
+    if let Some(storage) = storage {
+        if let Some(storage_class) = storage_class {
+            usage.insert(
+                format!("{storage_class}{STORAGE_CLASS_SUFFIX}requests.storage"),
+                storage.clone(),
+            );
+        }
+        usage.insert("requests.storage".to_owned(), storage);
+    }
+
+    Ok(usage)
+}
+
+fn service_usage(service: &Service) -> ParsedResourceList {
+    let mut usage = ParsedResourceList::from([
+        ("services".to_owned(), count(1)),
+        ("count/services".to_owned(), count(1)),
+    ]);
+
+    let Some(spec) = &service.spec else {
+        return usage;
+    };
+    let ports = spec.ports.as_deref().unwrap_or_default();
+
+    match spec.type_.as_deref() {
+        Some("NodePort") => {
+            usage.insert("services.nodeports".to_owned(), count(ports.len()));
+        }
+        Some("LoadBalancer") => {
+            // Without allocated node ports, only explicitly set ones count
+            let node_ports = if spec.allocate_load_balancer_node_ports == Some(false) {
+                ports.iter().filter(|port| port.node_port.is_some()).count()
+            } else {
+                ports.len()
+            };
+            usage.insert("services.nodeports".to_owned(), count(node_ports));
+            usage.insert("services.loadbalancers".to_owned(), count(1));
+        }
+        _ => {}
+    }
+
+    usage
+}
+
+/// Returns whether a resource is an extended resource, i.e., a domain
+/// qualified resource outside of the `kubernetes.io` domain.
+fn is_extended_resource(resource: &str) -> bool {
+    resource.contains('/')
+        && !resource.contains("kubernetes.io/")
+        && !resource.starts_with("requests.")
+}
+
+fn count(value: usize) -> ParsedQuantity {
+    quantity_for("count", Decimal::from(value))
+}
+
+// --- Scopes ---
+
+/// Returns whether an object is tracked by a quota, i.e., whether it matches
+/// all scopes of the quota. Only pods can match scoped quotas.
+pub fn matches_scopes(
+    quota: &ResourceQuota,
+    object: &QuotaObject,
+) -> Result<bool, ParseQuantityError> {
+    let selectors = scope_selectors(quota);
+    if selectors.is_empty() {
+        return Ok(true);
+    }
+
+    let QuotaObject::Pod(pod) = object else {
+        return Ok(false);
+    };
+    let spec = pod.spec.clone().unwrap_or_default();
+
+    for selector in selectors {
+        if !pod_matches_scope(&spec, &selector)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Returns the scopes and scope selector expressions of a quota as selector
+/// requirements, with plain scopes requiring existence.
+fn scope_selectors(quota: &ResourceQuota) -> Vec<ScopedResourceSelectorRequirement> {
+    let Some(spec) = &quota.spec else {
+        return Vec::new();
+    };
+
+    let scopes = spec
+        .scopes
+        .iter()
+        .flatten()
+        .map(|scope| ScopedResourceSelectorRequirement {
+            scope_name: scope.clone(),
+            operator: "Exists".to_owned(),
+            values: None,
+        });
+    let expressions = spec
+        .scope_selector
+        .iter()
+        .flat_map(|selector| selector.match_expressions.iter().flatten().cloned());
+
+    scopes.chain(expressions).collect()
+}
+
+fn pod_matches_scope(
+    spec: &PodSpec,
+    selector: &ScopedResourceSelectorRequirement,
+) -> Result<bool, ParseQuantityError> {
+    // Pods with a non-negative deadline are terminating, negative deadlines
+    // count as none
+    let is_terminating = spec
+        .active_deadline_seconds
+        .is_some_and(|seconds| seconds >= 0);
+
+    let matches = match selector.scope_name.as_str() {
+        "Terminating" => is_terminating,
+        "NotTerminating" => !is_terminating,
+        "BestEffort" => pod_qos_class(spec)? == QosClass::BestEffort,
+        "NotBestEffort" => pod_qos_class(spec)? != QosClass::BestEffort,
+        "PriorityClass" => {
+            let priority_class = spec.priority_class_name.as_deref().unwrap_or_default();
+            let values = selector.values.as_deref().unwrap_or_default();
+
+            // The priority class is only set as label if the pod has one, so
+            // an empty name never matches `In` but always matches `NotIn`
+            let is_set = !priority_class.is_empty();
+            match selector.operator.as_str() {
+                "Exists" => is_set,
+                "DoesNotExist" => !is_set,
+                "In" => is_set && values.iter().any(|value| value == priority_class),
+                "NotIn" => !is_set || values.iter().all(|value| value != priority_class),
+                _ => false,
+            }
+        }
+        "CrossNamespacePodAffinity" => uses_cross_namespace_affinity(spec),
+        _ => false,
+    };
+
+    Ok(matches)
+}
+
+fn uses_cross_namespace_affinity(spec: &PodSpec) -> bool {
+    let Some(affinity) = &spec.affinity else {
+        return false;
+    };
+
+    let affinity_terms = affinity.pod_affinity.iter().flat_map(|affinity| {
+        let required = affinity
+            .required_during_scheduling_ignored_during_execution
+            .iter()
+            .flatten();
+        let preferred = affinity
+            .preferred_during_scheduling_ignored_during_execution
+            .iter()
+            .flatten()
+            .map(|term| &term.pod_affinity_term);
+        required.chain(preferred)
+    });
+    let anti_affinity_terms = affinity.pod_anti_affinity.iter().flat_map(|affinity| {
+        let required = affinity
+            .required_during_scheduling_ignored_during_execution
+            .iter()
+            .flatten();
+        let preferred = affinity
+            .preferred_during_scheduling_ignored_during_execution
+            .iter()
+            .flatten()
+            .map(|term| &term.pod_affinity_term);
+        required.chain(preferred)
+    });
+
+    affinity_terms.chain(anti_affinity_terms).any(|term| {
+        term.namespace_selector.is_some()
+            || term
+                .namespaces
+                .as_ref()
+                .is_some_and(|namespaces| !namespaces.is_empty())
+    })
+}
+
+// --- Evaluation ---
+
+/// Usage of a single quota resource after admitting a batch of objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaDelta {
+    /// Hard limit from `spec.hard`
+    pub hard: ParsedQuantity,
+    /// Current usage from `status.used`, zero if not reported
+    pub used: ParsedQuantity,
+    /// Usage added by the batch
+    pub requested: ParsedQuantity,
+    /// Usage after admitting the batch
+    pub projected: ParsedQuantity,
+    /// Capacity left after admitting the batch, negative if exceeded
+    pub remaining: ParsedQuantity,
+}
+
+impl QuotaDelta {
+    /// Returns whether the projected usage exceeds the hard limit.
+    pub fn is_exceeded(&self) -> bool {
+        self.remaining.to_base_decimal() < Decimal::ZERO
+    }
+}
+
+/// Result of evaluating a batch of objects against a ResourceQuota.
+#[derive(Debug, Clone)]
+pub struct QuotaEvaluation {
+    /// Name of the quota
+    pub name: String,
+    /// Usage of all objects matching the scopes of the quota
+    pub usage: ParsedResourceList,
+    /// Usage per resource limited by the quota
+    pub deltas: BTreeMap<String, QuotaDelta>,
+}
+
+impl QuotaEvaluation {
+    /// Returns whether any hard limit would be exceeded.
+    pub fn is_exceeded(&self) -> bool {
+        self.deltas.values().any(QuotaDelta::is_exceeded)
+    }
+
+    /// Returns the resources whose hard limits would be exceeded.
+    pub fn exceeded(&self) -> impl Iterator<Item = (&String, &QuotaDelta)> {
+        self.deltas.iter().filter(|(_, delta)| delta.is_exceeded())
+    }
+
+    /// Returns the message the quota admission plugin rejects the batch with,
+    /// if any limit would be exceeded.
+    pub fn message(&self) -> Option<String> {
+        if !self.is_exceeded() {
+            return None;
+        }
+
+        let print = |value: fn(&QuotaDelta) -> &ParsedQuantity| {
+            self.exceeded()
+                .map(|(resource, delta)| {
+                    format!("{resource}={}", value(delta).to_canonical_string())
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        Some(format!(
+            "exceeded quota: {}, requested: {}, used: {}, limited: {}",
+            self.name,
+            print(|delta| &delta.requested),
+            print(|delta| &delta.used),
+            print(|delta| &delta.hard),
+        ))
+    }
+}
+
+/// Computes the usage of a batch of objects that match the scopes of a quota
+/// and compares it to `status.used` and `spec.hard`.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{ResourceQuota, ResourceQuotaSpec, ResourceQuotaStatus};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::quota::{evaluate_quota, QuotaObject};
+///
+/// let list = |value: &str| {
+///     Some(BTreeMap::from([("count/deployments.apps".to_string(), Quantity(value.to_string()))]))
+/// };
+///
+/// let mut quota = ResourceQuota {
+///     spec: Some(ResourceQuotaSpec { hard: list("2"), ..Default::default() }),
+///     status: Some(ResourceQuotaStatus { used: list("1"), ..Default::default() }),
+///     ..Default::default()
+/// };
+/// quota.metadata.name = Some("objects".to_string());
+///
+/// let objects = vec![QuotaObject::Object("deployments.apps".to_string()); 2];
+/// let evaluation = evaluate_quota(&quota, &objects).unwrap();
+///
+/// assert_eq!(evaluation.deltas["count/deployments.apps"].remaining.to_string(), "-1");
+/// assert_eq!(
+///     evaluation.message().unwrap(),
+///     "exceeded quota: objects, requested: count/deployments.apps=2, \
+///      used: count/deployments.apps=1, limited: count/deployments.apps=2",
+/// );
+/// ```
+pub fn evaluate_quota(
+    quota: &ResourceQuota,
+    objects: &[QuotaObject],
+) -> Result<QuotaEvaluation, ParseQuantityError> {
+    let mut usage = ParsedResourceList::new();
+    for object in objects {
+        if matches_scopes(quota, object)? {
+            add_lists(&mut usage, &object_usage(object)?);
+        }
+    }
+
+    let hard = parse_optional_list(quota.spec.as_ref().and_then(|spec| spec.hard.as_ref()))?;
+    let used = parse_optional_list(
+        quota
+            .status
+            .as_ref()
+            .and_then(|status| status.used.as_ref()),
+    )?;
+
+    let deltas = hard
+        .into_iter()
+        .map(|(resource, hard)| {
+            let value = |list: &ParsedResourceList| {
+                list.get(&resource)
+                    .map(ParsedQuantity::to_base_decimal)
+                    .unwrap_or_default()
+            };
+            let projected = value(&used) + value(&usage);
+            let quantity = |value| quantity_for(&resource, value);
+
+            let delta = QuotaDelta {
+                used: quantity(value(&used)),
+                requested: quantity(value(&usage)),
+                projected: quantity(projected),
+                remaining: quantity(hard.to_base_decimal() - projected),
+                hard,
+            };
+
+            (resource, delta)
+        })
+        .collect();
+
+    Ok(QuotaEvaluation {
+        name: quota.metadata.name.clone().unwrap_or_default(),
+        usage,
+        deltas,
+    })
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::{
+        Container, PersistentVolumeClaimSpec, ResourceQuotaSpec, ResourceQuotaStatus,
+        ResourceRequirements, ScopeSelector, ServicePort, ServiceSpec, VolumeResourceRequirements,
+    };
+
+    use super::*;
+    use crate::test_util::list;
+
+    fn pod(requests: &[(&str, &str)], limits: &[(&str, &str)]) -> Pod {
+        Pod {
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_owned(),
+                    resources: Some(ResourceRequirements {
+                        requests: Some(list(requests)),
+                        limits: Some(list(limits)),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn quota(hard: &[(&str, &str)], used: &[(&str, &str)]) -> ResourceQuota {
+        ResourceQuota {
+            spec: Some(ResourceQuotaSpec {
+                hard: Some(list(hard)),
+                ..Default::default()
+            }),
+            status: Some(ResourceQuotaStatus {
+                used: Some(list(used)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pod_usage() {
+        let pod = pod(
+            &[
+                ("cpu", "250m"),
+                ("memory", "512Mi"),
+                ("nvidia.com/gpu", "1"),
+            ],
+            &[("memory", "1Gi"), ("nvidia.com/gpu", "1")],
+        );
+        let usage = object_usage(&pod.into()).unwrap();
+
+        assert_eq!(
+            usage.keys().collect::<Vec<_>>(),
+            [
+                "count/pods",
+                "cpu",
+                "limits.memory",
+                "memory",
+                "pods",
+                "requests.cpu",
+                "requests.memory",
+                "requests.nvidia.com/gpu"
+            ]
+        );
+        assert_eq!(usage["requests.cpu"].to_string(), "250m");
+        assert_eq!(usage["limits.memory"].to_string(), "1Gi");
+    }
+
+    #[test]
+    fn test_terminated_pods_only_count_as_objects() {
+        let mut pod = pod(&[("cpu", "1")], &[]);
+        pod.status = Some(k8s_openapi::api::core::v1::PodStatus {
+            phase: Some("Succeeded".to_owned()),
+            ..Default::default()
+        });
+        let usage = object_usage(&pod.into()).unwrap();
+
+        assert_eq!(usage.keys().collect::<Vec<_>>(), ["count/pods"]);
+    }
+
+    #[test]
+    fn test_claim_storage_class_usage() {
+        let claim = |storage_class: &str, storage: &str| {
+            QuotaObject::from(PersistentVolumeClaim {
+                spec: Some(PersistentVolumeClaimSpec {
+                    storage_class_name: Some(storage_class.to_owned()),
+                    resources: Some(VolumeResourceRequirements {
+                        requests: Some(list(&[("storage", storage)])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let quota = quota(
+            &[
+                ("requests.storage", "100Gi"),
+                ("gold.storageclass.storage.k8s.io/requests.storage", "20Gi"),
+                ("persistentvolumeclaims", "5"),
+            ],
+            &[
+                ("requests.storage", "50Gi"),
+                ("gold.storageclass.storage.k8s.io/requests.storage", "10Gi"),
+                ("persistentvolumeclaims", "2"),
+            ],
+        );
+
+        let evaluation =
+            evaluate_quota(&quota, &[claim("gold", "15Gi"), claim("standard", "500Mi")]).unwrap();
+        let gold = &evaluation.deltas["gold.storageclass.storage.k8s.io/requests.storage"];
+
+        assert!(gold.is_exceeded());
+        assert_eq!(gold.requested.to_canonical_string(), "15Gi");
+        assert_eq!(gold.remaining.to_canonical_string(), "-5Gi");
+        assert_eq!(
+            evaluation.deltas["requests.storage"]
+                .projected
+                .to_canonical_string(),
+            "67060Mi"
+        );
+        assert_eq!(
+            evaluation.deltas["persistentvolumeclaims"]
+                .remaining
+                .to_string(),
+            "1"
+        );
+        assert_eq!(
+            evaluation.message().unwrap(),
+            "exceeded quota: , requested: gold.storageclass.storage.k8s.io/requests.storage=15Gi, \
+             used: gold.storageclass.storage.k8s.io/requests.storage=10Gi, \
+             limited: gold.storageclass.storage.k8s.io/requests.storage=20Gi"
+        );
+    }
+
+    #[test]
+    fn test_service_usage() {
+        let service = Service {
+            spec: Some(ServiceSpec {
+                type_: Some("LoadBalancer".to_owned()),
+                allocate_load_balancer_node_ports: Some(false),
+                ports: Some(vec![
+                    ServicePort {
+                        port: 80,
+                        node_port: Some(30080),
+                        ..Default::default()
+                    },
+                    ServicePort {
+                        port: 443,
+                        ..Default::default()
+                    },
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let usage = object_usage(&service.into()).unwrap();
+
+        assert_eq!(usage["services.loadbalancers"].to_string(), "1");
+        assert_eq!(usage["services.nodeports"].to_string(), "1");
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut terminating = pod(&[("cpu", "1")], &[("memory", "1Gi")]);
+        terminating.spec.as_mut().unwrap().active_deadline_seconds = Some(600);
+        let mut high_priority = pod(&[("cpu", "2")], &[("memory", "2Gi")]);
+        high_priority.spec.as_mut().unwrap().priority_class_name = Some("high".to_owned());
+        let best_effort = pod(&[], &[]);
+
+        let objects = [
+            terminating.into(),
+            high_priority.into(),
+            best_effort.into(),
+            QuotaObject::Object("configmaps".to_owned()),
+        ];
+
+        let mut quota = quota(&[("requests.cpu", "2"), ("pods", "10")], &[]);
+        quota.spec.as_mut().unwrap().scopes = Some(vec!["NotBestEffort".to_owned()]);
+        quota.spec.as_mut().unwrap().scope_selector = Some(ScopeSelector {
+            match_expressions: Some(vec![ScopedResourceSelectorRequirement {
+                scope_name: "PriorityClass".to_owned(),
+                operator: "NotIn".to_owned(),
+                values: Some(vec!["low".to_owned()]),
+            }]),
+        });
+
+        let evaluation = evaluate_quota(&quota, &objects).unwrap();
+
+        assert_eq!(evaluation.deltas["pods"].requested.to_string(), "2");
+        assert_eq!(
+            evaluation.deltas["requests.cpu"].remaining.to_string(),
+            "-1"
+        );
+        assert!(!evaluation.usage.contains_key("count/configmaps"));
+
+        quota.spec.as_mut().unwrap().scopes = Some(vec!["Terminating".to_owned()]);
+        let evaluation = evaluate_quota(&quota, &objects).unwrap();
+
+        assert_eq!(evaluation.deltas["pods"].requested.to_string(), "1");
+        assert_eq!(evaluation.usage["limits.memory"].to_string(), "1Gi");
+        assert!(!evaluation.is_exceeded());
+
+        let mut negative_deadline = pod(&[("cpu", "500m")], &[]);
+        negative_deadline
+            .spec
+            .as_mut()
+            .unwrap()
+            .active_deadline_seconds = Some(-1);
+        let mut objects = objects.to_vec();
+        objects.push(negative_deadline.into());
+
+        quota.spec.as_mut().unwrap().scopes = Some(vec!["NotTerminating".to_owned()]);
+        let evaluation = evaluate_quota(&quota, &objects).unwrap();
+
+        // A negative deadline does not make a pod terminating
+        assert_eq!(evaluation.deltas["pods"].requested.to_string(), "3");
+        assert_eq!(evaluation.usage["requests.cpu"].to_string(), "2.5");
+    }
+
+    #[test]
+    fn test_priority_class_scope() {
+        let priority_pod = |name: Option<&str>| {
+            let mut pod = pod(&[("cpu", "1")], &[]);
+            pod.spec.as_mut().unwrap().priority_class_name = name.map(str::to_owned);
+            pod.into()
+        };
+        let objects = [
+            priority_pod(None),
+            priority_pod(Some("")),
+            priority_pod(Some("high")),
+        ];
+
+        for (operator, values, expected) in [
+            ("Exists", None, "1"),
+            ("DoesNotExist", None, "2"),
+            ("In", Some("high"), "1"),
+            ("In", Some(""), "0"),
+            ("NotIn", Some("high"), "2"),
+            ("NotIn", Some(""), "3"),
+        ] {
+            let mut quota = quota(&[("pods", "10")], &[]);
+            quota.spec.as_mut().unwrap().scope_selector = Some(ScopeSelector {
+                match_expressions: Some(vec![ScopedResourceSelectorRequirement {
+                    scope_name: "PriorityClass".to_owned(),
+                    operator: operator.to_owned(),
+                    values: values.map(|value: &str| vec![value.to_owned()]),
+                }]),
+            });
+
+            let evaluation = evaluate_quota(&quota, &objects).unwrap();
+            assert_eq!(
+                evaluation.deltas["pods"].requested.to_string(),
+                expected,
+                "{operator} {values:?}"
+            );
+        }
+    }
+}
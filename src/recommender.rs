@@ -0,0 +1,937 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::{Container, LimitRange, PodSpec};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    format::Format,
+    limit_range::LIMIT_TYPE_CONTAINER,
+    resources::{
+        container_limits, container_requests, parse_optional_list, quantity_for, ParsedResourceList,
+    },
+    ParseQuantityError, ParsedQuantity, Rounding,
+};
+
+/// Default half-life of the weight of usage samples
+pub const DEFAULT_HALF_LIFE: Duration = Duration::from_secs(24 * 60 * 60);
+/// Default interval over which the memory usage peak is recorded as a single
+/// sample
+pub const DEFAULT_MEMORY_AGGREGATION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Policy that applies to all containers without a policy of their own
+pub const DEFAULT_CONTAINER_POLICY: &str = "*";
+
+/// Ratio between the sizes of consecutive histogram buckets
+const BUCKET_SIZE_GROWTH_RATIO: f64 = 1.05;
+/// Minimum weight of a CPU sample, in cores of the request
+const MIN_CPU_SAMPLE_WEIGHT: f64 = 0.1;
+/// Bucket weights below this are considered empty
+const EPSILON: f64 = 0.0001;
+/// Largest exponent of the decay factor before the reference time is shifted
+const MAX_DECAY_EXPONENT: f64 = 100.0;
+/// Assumed number of samples per day when computing the confidence
+const SAMPLES_PER_DAY: f64 = 60.0 * 24.0;
+
+/// Largest CPU usage the histogram distinguishes, in cores
+const MAX_CPU: f64 = 1000.0;
+/// Size of the first CPU bucket, in cores
+const FIRST_CPU_BUCKET_SIZE: f64 = 0.01;
+/// Largest memory usage the histogram distinguishes, in bytes
+const MAX_MEMORY: f64 = 1e12;
+/// Size of the first memory bucket, in bytes
+const FIRST_MEMORY_BUCKET_SIZE: f64 = 1e7;
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum RecommenderError {
+    /// The rounding granularity is not greater than zero
+    #[error("{resource} granularity must be greater than zero, got {granularity}")]
+    InvalidGranularity {
+        resource: String,
+        granularity: String,
+    },
+
+    /// A quantity of the pod or the LimitRange could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Options and policies ---
+
+/// Options of the recommender, defaulting to those of the Vertical Pod
+/// Autoscaler recommender.
+#[derive(Debug, Clone)]
+pub struct RecommenderOptions {
+    /// Time after which the weight of a sample is halved
+    pub half_life: Duration,
+    /// Interval over which the memory usage peak is recorded
+    pub memory_aggregation_interval: Duration,
+    /// Percentile of the usage recommended as target
+    pub target_percentile: f64,
+    /// Percentile of the usage the lower bound is based on
+    pub lower_bound_percentile: f64,
+    /// Percentile of the usage the upper bound is based on
+    pub upper_bound_percentile: f64,
+    /// Fraction added to the percentiles, e.g., `0.15` for 15%
+    pub safety_margin: f64,
+    /// Minimum CPU of a pod, split evenly among its containers
+    pub pod_min_cpu: ParsedQuantity,
+    /// Minimum memory of a pod, split evenly among its containers
+    pub pod_min_memory: ParsedQuantity,
+    /// Granularity CPU recommendations are rounded up to
+    pub cpu_granularity: ParsedQuantity,
+    /// Granularity memory recommendations are rounded up to
+    pub memory_granularity: ParsedQuantity,
+}
+
+impl Default for RecommenderOptions {
+    fn default() -> Self {
+        Self {
+            half_life: DEFAULT_HALF_LIFE,
+            memory_aggregation_interval: DEFAULT_MEMORY_AGGREGATION_INTERVAL,
+            target_percentile: 0.9,
+            lower_bound_percentile: 0.5,
+            upper_bound_percentile: 0.95,
+            safety_margin: 0.15,
+            pod_min_cpu: ParsedQuantity::from_milli_decimal(Decimal::from(25)),
+            pod_min_memory: ParsedQuantity::from_base_decimal(
+                Decimal::from(250 * 1024 * 1024),
+                Format::BinarySI,
+            ),
+            cpu_granularity: ParsedQuantity::from_milli_decimal(Decimal::ONE),
+            memory_granularity: ParsedQuantity::from_base_decimal(
+                Decimal::from(1024 * 1024),
+                Format::BinarySI,
+            ),
+        }
+    }
+}
+
+/// Resource values a policy allows the recommender to change, i.e.,
+/// `controlledValues` of a container policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ControlledValues {
+    /// Requests are recommended and limits are scaled proportionally
+    #[default]
+    RequestsAndLimits,
+    /// Only requests are recommended, limits are left unchanged
+    RequestsOnly,
+}
+
+/// Resource policy of a container, i.e., a `containerPolicies` entry of a
+/// VerticalPodAutoscaler.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerPolicy {
+    /// Lower bounds of the recommended requests
+    pub min_allowed: ParsedResourceList,
+    /// Upper bounds of the recommended requests
+    pub max_allowed: ParsedResourceList,
+    /// Resource values that are recommended
+    pub controlled_values: ControlledValues,
+}
+
+// --- Samples and recommendations ---
+
+/// Resource usage of a container at a point in time.
+#[derive(Debug, Clone)]
+pub struct UsageSample {
+    /// Time the usage was measured at
+    pub timestamp: DateTime<Utc>,
+    /// Usage of `cpu` and `memory`; other resources are ignored
+    pub usage: ParsedResourceList,
+    /// Requests of the container at the time, which weight CPU samples
+    pub requests: ParsedResourceList,
+}
+
+/// Recommended resources of a container. CPU and memory are rounded up to
+/// the granularity of the options and use the conventional suffixes.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerRecommendation {
+    /// Recommended requests
+    pub target: ParsedResourceList,
+    /// Smallest requests that are considered sufficient
+    pub lower_bound: ParsedResourceList,
+    /// Largest requests that are considered reasonable
+    pub upper_bound: ParsedResourceList,
+    /// Recommended requests before applying the policy and the LimitRange
+    pub uncapped_target: ParsedResourceList,
+    /// Limits scaled proportionally to the target requests, if the policy
+    /// controls limits and the container has any
+    pub limits: ParsedResourceList,
+}
+
+// --- Recommender ---
+
+/// Recommender of container requests based on the usage history of a pod,
+/// mirroring the Vertical Pod Autoscaler recommender.
+///
+/// CPU samples are weighted by the request of the container, memory samples
+/// are aggregated into one peak per interval. All samples decay with the
+/// half-life, so recent usage dominates the recommendation.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, PodSpec};
+/// use chrono::{DateTime, TimeDelta};
+/// use kube_quantity::recommender::{Recommender, RecommenderOptions, UsageSample};
+///
+/// let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+/// let mut recommender = Recommender::new(RecommenderOptions::default()).unwrap();
+/// for minute in 0..60 {
+///     recommender.add_sample("app", &UsageSample {
+///         timestamp: start + TimeDelta::minutes(minute),
+///         usage: BTreeMap::from([
+///             ("cpu".to_string(), "200m".try_into().unwrap()),
+///             ("memory".to_string(), "300Mi".try_into().unwrap()),
+///         ]),
+///         requests: BTreeMap::new(),
+///     });
+/// }
+///
+/// let spec = PodSpec {
+///     containers: vec![Container { name: "app".to_string(), ..Default::default() }],
+///     ..Default::default()
+/// };
+/// let recommendations = recommender.recommend(&spec, &BTreeMap::new(), None).unwrap();
+/// let target = &recommendations["app"].target;
+///
+/// // The upper edge of the bucket of the usage plus a 15% safety margin
+/// assert_eq!(target["cpu"].to_string(), "249m");
+/// assert_eq!(target["memory"].to_string(), "363Mi");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recommender {
+    options: RecommenderOptions,
+    containers: BTreeMap<String, ContainerState>,
+}
+
+impl Recommender {
+    /// Creates a recommender without samples.
+    pub fn new(options: RecommenderOptions) -> Result<Self, RecommenderError> {
+        for (resource, granularity) in [
+            ("cpu", &options.cpu_granularity),
+            ("memory", &options.memory_granularity),
+        ] {
+            if granularity.to_base_decimal() <= Decimal::ZERO {
+                return Err(RecommenderError::InvalidGranularity {
+                    resource: resource.to_owned(),
+                    granularity: granularity.to_canonical_string(),
+                });
+            }
+        }
+
+        Ok(Self {
+            options,
+            containers: BTreeMap::new(),
+        })
+    }
+
+    /// Adds a usage sample of a container.
+    pub fn add_sample(&mut self, container: &str, sample: &UsageSample) {
+        let options = &self.options;
+        self.containers
+            .entry(container.to_owned())
+            .or_insert_with(|| ContainerState::new(sample.timestamp, options))
+            .add_sample(sample, options);
+    }
+
+    /// Recommends the resources of all containers of the pod that have
+    /// samples.
+    ///
+    /// The policy of a container is looked up by name and falls back to
+    /// [`DEFAULT_CONTAINER_POLICY`]. The recommendations are capped to the
+    /// policy first and to the `Container` limits of the LimitRange second,
+    /// where the maximum limit caps the request so that the proportionally
+    /// scaled limit fits.
+    pub fn recommend(
+        &self,
+        spec: &PodSpec,
+        policies: &BTreeMap<String, ContainerPolicy>,
+        limit_range: Option<&LimitRange>,
+    ) -> Result<BTreeMap<String, ContainerRecommendation>, RecommenderError> {
+        let (limit_range_min, limit_range_max) = container_limit_range(limit_range)?;
+
+        // The minimum resources of the pod are split among its containers
+        let fraction = Decimal::ONE / Decimal::from(self.containers.len().max(1));
+        let min_cpu = self.options.pod_min_cpu.to_base_decimal() * fraction;
+        let min_memory = self.options.pod_min_memory.to_base_decimal() * fraction;
+
+        let mut recommendations = BTreeMap::new();
+        for (name, state) in &self.containers {
+            let Some(container) = spec.containers.iter().find(|c| c.name == *name) else {
+                continue;
+            };
+            let policy = policies
+                .get(name)
+                .or_else(|| policies.get(DEFAULT_CONTAINER_POLICY))
+                .cloned()
+                .unwrap_or_default();
+            let requests = container_requests(container)?;
+            let limits = container_limits(container)?;
+
+            let mut recommendation = ContainerRecommendation::default();
+            for (resource, estimates, min) in [
+                ("cpu", state.cpu_estimates(&self.options), min_cpu),
+                ("memory", state.memory_estimates(&self.options), min_memory),
+            ] {
+                let [target, lower_bound, upper_bound] =
+                    estimates.map(|estimate| self.round(resource, estimate.max(min)));
+
+                // The limit range constrains the scaled limit, not the request
+                let max = limit_range_max.get(resource).map(|max| {
+                    match (
+                        policy.controlled_values,
+                        requests.get(resource),
+                        limits.get(resource),
+                    ) {
+                        (ControlledValues::RequestsAndLimits, Some(request), Some(limit))
+                            if !limit.to_base_decimal().is_zero() =>
+                        {
+                            max.to_base_decimal() * request.to_base_decimal()
+                                / limit.to_base_decimal()
+                        }
+                        _ => max.to_base_decimal(),
+                    }
+                });
+                let cap = |value: Decimal| {
+                    let mut value = value;
+                    if let Some(min) = policy.min_allowed.get(resource) {
+                        value = value.max(min.to_base_decimal());
+                    }
+                    if let Some(max) = policy.max_allowed.get(resource) {
+                        value = value.min(max.to_base_decimal());
+                    }
+                    if let Some(max) = max {
+                        value = value.min(max);
+                    }
+                    if let Some(min) = limit_range_min.get(resource) {
+                        value = value.max(min.to_base_decimal());
+                    }
+                    value
+                };
+
+                recommendation
+                    .uncapped_target
+                    .insert(resource.to_owned(), friendly(resource, target));
+                recommendation
+                    .target
+                    .insert(resource.to_owned(), friendly(resource, cap(target)));
+                recommendation
+                    .lower_bound
+                    .insert(resource.to_owned(), friendly(resource, cap(lower_bound)));
+                recommendation
+                    .upper_bound
+                    .insert(resource.to_owned(), friendly(resource, cap(upper_bound)));
+
+                if policy.controlled_values == ControlledValues::RequestsAndLimits {
+                    if let Some(limit) =
+                        proportional_limit(resource, container, &requests, &limits, cap(target))
+                    {
+                        recommendation.limits.insert(resource.to_owned(), limit);
+                    }
+                }
+            }
+
+            recommendations.insert(name.clone(), recommendation);
+        }
+
+        Ok(recommendations)
+    }
+
+    /// Rounds the value up to the granularity of the resource.
+    fn round(&self, resource: &str, value: Decimal) -> Decimal {
+        let granularity = match resource {
+            "cpu" => &self.options.cpu_granularity,
+            _ => &self.options.memory_granularity,
+        }
+        .to_base_decimal();
+
+        Rounding::Up.round_dp(value / granularity, 0) * granularity
+    }
+}
+
+/// Usage history of a single container.
+#[derive(Debug, Clone)]
+struct ContainerState {
+    cpu: DecayingHistogram,
+    memory: DecayingHistogram,
+    /// End of the current memory aggregation interval and its peak
+    memory_peak: Option<(DateTime<Utc>, f64)>,
+    first_sample: DateTime<Utc>,
+    last_sample: DateTime<Utc>,
+    total_samples: u64,
+}
+
+impl ContainerState {
+    fn new(timestamp: DateTime<Utc>, options: &RecommenderOptions) -> Self {
+        Self {
+            cpu: DecayingHistogram::new(
+                HistogramOptions::exponential(MAX_CPU, FIRST_CPU_BUCKET_SIZE),
+                timestamp,
+                options.half_life,
+            ),
+            memory: DecayingHistogram::new(
+                HistogramOptions::exponential(MAX_MEMORY, FIRST_MEMORY_BUCKET_SIZE),
+                timestamp,
+                options.half_life,
+            ),
+            memory_peak: None,
+            first_sample: timestamp,
+            last_sample: timestamp,
+            total_samples: 0,
+        }
+    }
+
+    fn add_sample(&mut self, sample: &UsageSample, options: &RecommenderOptions) {
+        self.first_sample = self.first_sample.min(sample.timestamp);
+        self.last_sample = self.last_sample.max(sample.timestamp);
+        self.total_samples += 1;
+
+        if let Some(cpu) = sample.usage.get("cpu") {
+            let request = sample.requests.get("cpu").map_or(0.0, to_f64);
+            self.cpu.add(
+                to_f64(cpu),
+                request.max(MIN_CPU_SAMPLE_WEIGHT),
+                sample.timestamp,
+            );
+        }
+
+        if let Some(memory) = sample.usage.get("memory") {
+            let memory = to_f64(memory);
+            match self.memory_peak {
+                // Replace the peak of the current interval
+                Some((end, peak)) if sample.timestamp < end => {
+                    if memory > peak {
+                        self.memory.subtract(peak, 1.0, end);
+                        self.memory.add(memory, 1.0, end);
+                        self.memory_peak = Some((end, memory));
+                    }
+                }
+                _ => {
+                    let end = interval_end(sample.timestamp, options.memory_aggregation_interval);
+                    self.memory.add(memory, 1.0, end);
+                    self.memory_peak = Some((end, memory));
+                }
+            }
+        }
+    }
+
+    /// Returns the target, lower bound and upper bound of the CPU in cores.
+    fn cpu_estimates(&self, options: &RecommenderOptions) -> [Decimal; 3] {
+        self.estimates(&self.cpu, options)
+    }
+
+    /// Returns the target, lower bound and upper bound of the memory in
+    /// bytes.
+    fn memory_estimates(&self, options: &RecommenderOptions) -> [Decimal; 3] {
+        self.estimates(&self.memory, options)
+    }
+
+    fn estimates(
+        &self,
+        histogram: &DecayingHistogram,
+        options: &RecommenderOptions,
+    ) -> [Decimal; 3] {
+        let margin = 1.0 + options.safety_margin;
+        let confidence = self.confidence();
+
+        // Bounds widen while there is little history
+        let target = histogram.percentile(options.target_percentile) * margin;
+        let lower_bound = histogram.percentile(options.lower_bound_percentile)
+            * margin
+            * (1.0 + 0.001 / confidence).powf(-2.0);
+        let upper_bound = histogram.percentile(options.upper_bound_percentile)
+            * margin
+            * (1.0 + 1.0 / confidence);
+
+        [target, lower_bound, upper_bound].map(|value| {
+            let value = value.min(histogram.options.max_value());
+            Decimal::from_f64(value).unwrap_or_default()
+        })
+    }
+
+    /// Returns the days of history, limited by the days of samples at one
+    /// sample per minute.
+    fn confidence(&self) -> f64 {
+        let lifespan = (self.last_sample - self.first_sample).num_seconds() as f64 / 86400.0;
+        let samples = self.total_samples as f64 / SAMPLES_PER_DAY;
+
+        lifespan.min(samples)
+    }
+}
+
+// --- Histograms ---
+
+/// Buckets that grow exponentially, starting at `first_bucket_size`.
+#[derive(Debug, Clone, Copy)]
+struct HistogramOptions {
+    first_bucket_size: f64,
+    num_buckets: usize,
+}
+
+impl HistogramOptions {
+    fn exponential(max_value: f64, first_bucket_size: f64) -> Self {
+        let ratio = BUCKET_SIZE_GROWTH_RATIO;
+        let num_buckets = ((max_value * (ratio - 1.0) / first_bucket_size + 1.0).ln() / ratio.ln())
+            .ceil() as usize
+            + 1;
+
+        Self {
+            first_bucket_size,
+            num_buckets,
+        }
+    }
+
+    fn bucket(&self, value: f64) -> usize {
+        if value < self.first_bucket_size {
+            return 0;
+        }
+
+        let ratio = BUCKET_SIZE_GROWTH_RATIO;
+        let bucket = ((value * (ratio - 1.0) / self.first_bucket_size + 1.0).ln() / ratio.ln())
+            .floor() as usize;
+        bucket.min(self.num_buckets - 1)
+    }
+
+    fn bucket_start(&self, bucket: usize) -> f64 {
+        let ratio = BUCKET_SIZE_GROWTH_RATIO;
+        self.first_bucket_size * (ratio.powi(bucket as i32) - 1.0) / (ratio - 1.0)
+    }
+
+    fn max_value(&self) -> f64 {
+        self.bucket_start(self.num_buckets - 1)
+    }
+}
+
+/// Histogram whose sample weights grow exponentially with their time, which
+/// is equivalent to older samples decaying.
+#[derive(Debug, Clone)]
+struct DecayingHistogram {
+    options: HistogramOptions,
+    weights: Vec<f64>,
+    total_weight: f64,
+    reference: DateTime<Utc>,
+    half_life: f64,
+}
+
+impl DecayingHistogram {
+    fn new(options: HistogramOptions, reference: DateTime<Utc>, half_life: Duration) -> Self {
+        Self {
+            options,
+            weights: vec![0.0; options.num_buckets],
+            total_weight: 0.0,
+            reference,
+            half_life: half_life.as_secs_f64().max(1.0),
+        }
+    }
+
+    fn add(&mut self, value: f64, weight: f64, time: DateTime<Utc>) {
+        let weight = weight * self.decay_factor(time);
+        let bucket = self.options.bucket(value);
+        self.weights[bucket] += weight;
+        self.total_weight += weight;
+    }
+
+    fn subtract(&mut self, value: f64, weight: f64, time: DateTime<Utc>) {
+        let weight = weight * self.decay_factor(time);
+        let bucket = self.options.bucket(value);
+        self.weights[bucket] = (self.weights[bucket] - weight).max(0.0);
+        self.total_weight = (self.total_weight - weight).max(0.0);
+    }
+
+    /// Returns the factor of a sample at the given time, shifting the
+    /// reference time forward if the factor would grow too large.
+    fn decay_factor(&mut self, time: DateTime<Utc>) -> f64 {
+        let exponent = self.exponent(time);
+        if exponent > MAX_DECAY_EXPONENT {
+            let shift = exponent.floor();
+            let scale = 2f64.powf(-shift);
+            for weight in &mut self.weights {
+                *weight *= scale;
+            }
+            self.total_weight *= scale;
+            self.reference += Duration::from_secs_f64(shift * self.half_life);
+        }
+
+        2f64.powf(self.exponent(time))
+    }
+
+    fn exponent(&self, time: DateTime<Utc>) -> f64 {
+        (time - self.reference).num_milliseconds() as f64 / 1000.0 / self.half_life
+    }
+
+    /// Returns the end of the bucket the percentile falls into, or zero if
+    /// the histogram is empty.
+    fn percentile(&self, percentile: f64) -> f64 {
+        let Some(min_bucket) = self.weights.iter().position(|weight| *weight >= EPSILON) else {
+            return 0.0;
+        };
+        let max_bucket = self
+            .weights
+            .iter()
+            .rposition(|weight| *weight >= EPSILON)
+            .unwrap_or(min_bucket);
+
+        let threshold = percentile * self.total_weight;
+        let mut partial_sum = 0.0;
+        let mut bucket = min_bucket;
+        while bucket < max_bucket {
+            partial_sum += self.weights[bucket];
+            if partial_sum >= threshold {
+                break;
+            }
+            bucket += 1;
+        }
+
+        if bucket < self.options.num_buckets - 1 {
+            self.options.bucket_start(bucket + 1)
+        } else {
+            self.options.bucket_start(bucket)
+        }
+    }
+}
+
+// --- Helpers ---
+
+/// Returns the minimum and maximum of the `Container` limits of the
+/// LimitRange, where the most restrictive item wins.
+fn container_limit_range(
+    limit_range: Option<&LimitRange>,
+) -> Result<(ParsedResourceList, ParsedResourceList), ParseQuantityError> {
+    let mut min = ParsedResourceList::new();
+    let mut max = ParsedResourceList::new();
+
+    let items = limit_range
+        .and_then(|limit_range| limit_range.spec.as_ref())
+        .map(|spec| spec.limits.as_slice())
+        .unwrap_or_default();
+    for item in items
+        .iter()
+        .filter(|item| item.type_ == LIMIT_TYPE_CONTAINER)
+    {
+        for (resource, quantity) in parse_optional_list(item.min.as_ref())? {
+            if min
+                .get(&resource)
+                .is_none_or(|min| quantity.to_base_decimal() > min.to_base_decimal())
+            {
+                min.insert(resource, quantity);
+            }
+        }
+        for (resource, quantity) in parse_optional_list(item.max.as_ref())? {
+            if max
+                .get(&resource)
+                .is_none_or(|max| quantity.to_base_decimal() < max.to_base_decimal())
+            {
+                max.insert(resource, quantity);
+            }
+        }
+    }
+
+    Ok((min, max))
+}
+
+/// Returns the limit scaled by the same factor as the request, rounded up to
+/// millicores or bytes. Limits of containers without a request are kept.
+fn proportional_limit(
+    resource: &str,
+    container: &Container,
+    requests: &ParsedResourceList,
+    limits: &ParsedResourceList,
+    target: Decimal,
+) -> Option<ParsedQuantity> {
+    let limit = limits.get(resource)?.to_base_decimal();
+    if limit.is_zero() {
+        return None;
+    }
+
+    let request = requests
+        .get(resource)
+        .map(ParsedQuantity::to_base_decimal)
+        .filter(|request| !request.is_zero());
+    let Some(request) = request else {
+        let limit = container
+            .resources
+            .as_ref()?
+            .limits
+            .as_ref()?
+            .get(resource)?;
+        return ParsedQuantity::try_from(limit).ok();
+    };
+
+    let decimal_places = if resource == "cpu" { 3 } else { 0 };
+    let value = Rounding::Up.round_dp(limit * target / request, decimal_places);
+
+    Some(friendly(resource, value))
+}
+
+/// Returns the quantity with the largest suffix that represents the value
+/// exactly, e.g., `1536Mi` or `250m`.
+fn friendly(resource: &str, value: Decimal) -> ParsedQuantity {
+    let quantity = quantity_for(resource, value);
+    ParsedQuantity::from_base_decimal_suffixed(value, quantity.format().clone())
+}
+
+/// Returns the end of the aggregation interval the time falls into.
+fn interval_end(time: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+    let interval = interval.as_secs().max(1) as i64;
+    let start = time.timestamp().div_euclid(interval) * interval;
+
+    DateTime::from_timestamp(start + interval, 0).unwrap_or(time)
+}
+
+fn to_f64(quantity: &ParsedQuantity) -> f64 {
+    quantity.to_base_decimal().to_f64().unwrap_or_default()
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::{
+        api::core::v1::{LimitRangeItem, LimitRangeSpec},
+        apimachinery::pkg::api::resource::Quantity,
+    };
+
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::test_util::{container, parsed_list};
+
+    fn start() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn sample(minutes: i64, usage: &[(&str, &str)]) -> UsageSample {
+        UsageSample {
+            timestamp: start() + TimeDelta::minutes(minutes),
+            usage: parsed_list(usage),
+            requests: ParsedResourceList::new(),
+        }
+    }
+
+    fn strings(list: &ParsedResourceList) -> BTreeMap<&str, String> {
+        list.iter()
+            .map(|(name, quantity)| (name.as_str(), quantity.to_string()))
+            .collect()
+    }
+
+    /// Records a day of samples, one per minute.
+    fn recommender(usage: &[(&str, &[(&str, &str)])]) -> Recommender {
+        let mut recommender = Recommender::new(RecommenderOptions::default()).unwrap();
+        for minute in 0..24 * 60 {
+            for (container, usage) in usage {
+                recommender.add_sample(container, &sample(minute, usage));
+            }
+        }
+
+        recommender
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let options = HistogramOptions::exponential(MAX_CPU, FIRST_CPU_BUCKET_SIZE);
+
+        assert_eq!(options.bucket(0.0), 0);
+        assert_eq!(options.bucket(0.005), 0);
+        assert_eq!(options.bucket(0.01), 1);
+        assert_eq!(options.bucket(1e9), options.num_buckets - 1);
+        assert!(options.max_value() >= MAX_CPU);
+        for value in [0.02, 0.25, 1.0, 7.5, 512.0] {
+            let bucket = options.bucket(value);
+            assert!(options.bucket_start(bucket) <= value + 1e-9);
+            assert!(options.bucket_start(bucket + 1) > value);
+        }
+    }
+
+    #[test]
+    fn test_decaying_histogram() {
+        let options = HistogramOptions::exponential(MAX_CPU, FIRST_CPU_BUCKET_SIZE);
+        let mut histogram = DecayingHistogram::new(options, start(), DEFAULT_HALF_LIFE);
+        assert_eq!(histogram.percentile(0.5), 0.0);
+
+        // The newer sample weighs twice as much after one half-life
+        histogram.add(0.1, 1.0, start());
+        histogram.add(1.0, 1.0, start() + TimeDelta::days(1));
+        assert_eq!(
+            histogram.percentile(0.3),
+            options.bucket_start(options.bucket(0.1) + 1)
+        );
+        assert_eq!(
+            histogram.percentile(0.5),
+            options.bucket_start(options.bucket(1.0) + 1)
+        );
+
+        // The reference time is shifted instead of overflowing
+        histogram.add(2.0, 1.0, start() + TimeDelta::days(1000));
+        assert!(histogram.total_weight.is_finite());
+        assert_eq!(histogram.reference, start() + TimeDelta::days(1000));
+        assert_eq!(
+            histogram.percentile(0.01),
+            options.bucket_start(options.bucket(2.0) + 1)
+        );
+    }
+
+    #[test]
+    fn test_memory_peaks() {
+        let options = RecommenderOptions::default();
+        let mut state = ContainerState::new(start(), &options);
+        let memory = state.memory.options;
+        let bucket_end = move |value: &str| {
+            let value = to_f64(&value.try_into().unwrap());
+            memory.bucket_start(memory.bucket(value) + 1)
+        };
+
+        // Only the peak of the interval is recorded
+        for (minute, memory) in [(0, "100Mi"), (10, "500Mi"), (20, "200Mi")] {
+            state.add_sample(&sample(minute, &[("memory", memory)]), &options);
+        }
+        assert_eq!(state.memory.percentile(0.0), bucket_end("500Mi"));
+        let (end, _) = state.memory_peak.unwrap();
+        let weight = 2f64.powf(state.memory.exponent(end));
+        assert!((state.memory.total_weight - weight).abs() < 1e-9);
+
+        // A later interval records its own peak
+        state.add_sample(&sample(24 * 60, &[("memory", "100Mi")]), &options);
+        assert_eq!(state.memory.percentile(0.0), bucket_end("100Mi"));
+        assert_eq!(state.memory.percentile(1.0), bucket_end("500Mi"));
+        assert_eq!(state.total_samples, 4);
+    }
+
+    #[test]
+    fn test_bounds_and_floors() {
+        let recommender = recommender(&[
+            ("app", &[("cpu", "1"), ("memory", "1Gi")]),
+            ("sidecar", &[("cpu", "1m"), ("memory", "1Mi")]),
+        ]);
+        let spec = PodSpec {
+            containers: vec![container("app", &[], &[]), container("sidecar", &[], &[])],
+            ..Default::default()
+        };
+        let recommendations = recommender
+            .recommend(&spec, &BTreeMap::new(), None)
+            .unwrap();
+
+        let app = &recommendations["app"];
+        assert_eq!(
+            strings(&app.target),
+            BTreeMap::from([("cpu", "1169m".to_owned()), ("memory", "1182Mi".to_owned())])
+        );
+        assert_eq!(strings(&app.target), strings(&app.uncapped_target));
+        // A day of history doubles the upper bound
+        assert_eq!(
+            strings(&app.upper_bound),
+            BTreeMap::from([("cpu", "2339m".to_owned()), ("memory", "2364Mi".to_owned())])
+        );
+        assert_eq!(
+            strings(&app.lower_bound),
+            BTreeMap::from([("cpu", "1167m".to_owned()), ("memory", "1179Mi".to_owned())])
+        );
+        assert!(app.limits.is_empty());
+
+        // The minimum of the pod is split among the containers
+        assert_eq!(
+            strings(&recommendations["sidecar"].target),
+            BTreeMap::from([("cpu", "13m".to_owned()), ("memory", "125Mi".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_policy_and_limit_range() {
+        let recommender = recommender(&[("app", &[("cpu", "1"), ("memory", "1Gi")])]);
+        let spec = PodSpec {
+            containers: vec![container(
+                "app",
+                &[("cpu", "100m"), ("memory", "256Mi")],
+                &[("cpu", "200m"), ("memory", "512Mi")],
+            )],
+            ..Default::default()
+        };
+        let limit_range = LimitRange {
+            spec: Some(LimitRangeSpec {
+                limits: vec![LimitRangeItem {
+                    type_: LIMIT_TYPE_CONTAINER.to_owned(),
+                    max: Some(BTreeMap::from([(
+                        "cpu".to_owned(),
+                        Quantity("1".to_owned()),
+                    )])),
+                    ..Default::default()
+                }],
+            }),
+            ..Default::default()
+        };
+        let mut policies = BTreeMap::from([(
+            DEFAULT_CONTAINER_POLICY.to_owned(),
+            ContainerPolicy {
+                max_allowed: parsed_list(&[("memory", "200Mi")]),
+                ..Default::default()
+            },
+        )]);
+
+        // The scaled CPU limit must not exceed the maximum of the LimitRange
+        let recommendation = &recommender
+            .recommend(&spec, &policies, Some(&limit_range))
+            .unwrap()["app"];
+        assert_eq!(
+            strings(&recommendation.target),
+            BTreeMap::from([("cpu", "500m".to_owned()), ("memory", "200Mi".to_owned())])
+        );
+        assert_eq!(
+            strings(&recommendation.limits),
+            BTreeMap::from([("cpu", "1".to_owned()), ("memory", "400Mi".to_owned())])
+        );
+        assert_eq!(
+            recommendation.uncapped_target["memory"].to_string(),
+            "1182Mi"
+        );
+        assert_eq!(recommendation.upper_bound["memory"].to_string(), "200Mi");
+
+        // Limits are left unchanged for requests only
+        policies.insert(
+            "app".to_owned(),
+            ContainerPolicy {
+                min_allowed: parsed_list(&[("cpu", "2")]),
+                controlled_values: ControlledValues::RequestsOnly,
+                ..Default::default()
+            },
+        );
+        let recommendation = &recommender
+            .recommend(&spec, &policies, Some(&limit_range))
+            .unwrap()["app"];
+        assert_eq!(
+            strings(&recommendation.target),
+            BTreeMap::from([("cpu", "1".to_owned()), ("memory", "1182Mi".to_owned())])
+        );
+        assert!(recommendation.limits.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Recommender::new(RecommenderOptions {
+                memory_granularity: "0".try_into().unwrap(),
+                ..Default::default()
+            }),
+            Err(RecommenderError::InvalidGranularity { .. })
+        ));
+
+        let recommender = recommender(&[("app", &[("cpu", "1")])]);
+        let spec = PodSpec {
+            containers: vec![container("app", &[("cpu", "1.2.3")], &[])],
+            ..Default::default()
+        };
+        assert!(matches!(
+            recommender.recommend(&spec, &BTreeMap::new(), None),
+            Err(RecommenderError::InvalidQuantity(_))
+        ));
+
+        // Containers that are not part of the pod are skipped
+        let spec = PodSpec::default();
+        assert!(recommender
+            .recommend(&spec, &BTreeMap::new(), None)
+            .unwrap()
+            .is_empty());
+    }
+}
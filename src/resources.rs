@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::{
+    api::core::v1::{Container, PodSpec, ResourceRequirements},
+    apimachinery::pkg::api::resource::Quantity,
+};
+use rust_decimal::Decimal;
+
+use crate::{format::Format, ParseQuantityError, ParsedQuantity};
+
+// --- Resource lists ---
+
+/// A resource list with parsed quantities, keyed by resource name
+/// (e.g., `cpu`, `memory`, `nvidia.com/gpu`).
+pub type ParsedResourceList = BTreeMap<String, ParsedQuantity>;
+
+/// Parses every quantity of a Kubernetes resource list.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::resources::parse_resource_list;
+///
+/// let list = BTreeMap::from([
+///     ("cpu".to_string(), Quantity("500m".to_string())),
+///     ("memory".to_string(), Quantity("1Gi".to_string())),
+/// ]);
+///
+/// let parsed = parse_resource_list(&list).unwrap();
+///
+/// assert_eq!(parsed["cpu"].to_string(), "500m");
+/// assert_eq!(parsed["memory"].to_string(), "1Gi");
+/// ```
+pub fn parse_resource_list(
+    list: &BTreeMap<String, Quantity>,
+) -> Result<ParsedResourceList, ParseQuantityError> {
+    list.iter()
+        .map(|(name, quantity)| Ok((name.clone(), quantity.try_into()?)))
+        .collect()
+}
+
+/// Converts a parsed resource list back into a Kubernetes resource list.
+pub fn to_resource_list(list: &ParsedResourceList) -> BTreeMap<String, Quantity> {
+    list.iter()
+        .map(|(name, quantity)| (name.clone(), quantity.clone().into()))
+        .collect()
+}
+
+// --- Pod resources ---
+
+/// Returns the effective requests of a container.
+///
+/// Mirrors the API server defaulting, i.e., a resource with a limit but
+/// without a request is requested at its limit.
+pub fn container_requests(container: &Container) -> Result<ParsedResourceList, ParseQuantityError> {
+    let Some(resources) = &container.resources else {
+        return Ok(ParsedResourceList::new());
+    };
+
+    let mut requests = parse_optional_list(resources.limits.as_ref())?;
+    requests.extend(parse_optional_list(resources.requests.as_ref())?);
+
+    Ok(requests)
+}
+
+/// Returns the limits of a container.
+pub fn container_limits(container: &Container) -> Result<ParsedResourceList, ParseQuantityError> {
+    parse_optional_list(
+        container
+            .resources
+            .as_ref()
+            .and_then(|resources: &ResourceRequirements| resources.limits.as_ref()),
+    )
+}
+
+/// Returns the effective requests of a pod, the way the scheduler computes
+/// them.
+///
+/// This is the larger of the sum of all app containers (including sidecars,
+/// i.e., init containers with an `Always` restart policy) and the largest
+/// init container, plus the pod overhead.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::{Container, PodSpec, ResourceRequirements};
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::resources::pod_requests;
+///
+/// let container = |name: &str, cpu: &str| Container {
+///     name: name.to_string(),
+///     resources: Some(ResourceRequirements {
+///         requests: Some(BTreeMap::from([("cpu".to_string(), Quantity(cpu.to_string()))])),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+///
+/// let spec = PodSpec {
+///     containers: vec![container("app", "250m"), container("proxy", "250m")],
+///     init_containers: Some(vec![container("migrate", "1")]),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(pod_requests(&spec).unwrap()["cpu"].to_string(), "1");
+/// ```
+pub fn pod_requests(spec: &PodSpec) -> Result<ParsedResourceList, ParseQuantityError> {
+    let mut requests = pod_container_requests(spec)?;
+
+    for (name, quantity) in parse_optional_list(spec.overhead.as_ref())? {
+        add_to_list(&mut requests, &name, &quantity);
+    }
+
+    Ok(requests)
+}
+
+/// Returns the effective limits of a pod.
+///
+/// Limits are aggregated like requests, but the pod overhead is only added to
+/// resources that have a limit.
+pub fn pod_limits(spec: &PodSpec) -> Result<ParsedResourceList, ParseQuantityError> {
+    let mut limits = pod_container_limits(spec)?;
+
+    for (name, quantity) in parse_optional_list(spec.overhead.as_ref())? {
+        if limits.contains_key(&name) {
+            add_to_list(&mut limits, &name, &quantity);
+        }
+    }
+
+    Ok(limits)
+}
+
+/// Returns the requests of the containers of a pod, aggregated like
+/// [`pod_requests`] but without the pod overhead.
+pub(crate) fn pod_container_requests(
+    spec: &PodSpec,
+) -> Result<ParsedResourceList, ParseQuantityError> {
+    aggregate_pod(spec, container_requests)
+}
+
+/// Returns the limits of the containers of a pod, aggregated like
+/// [`pod_limits`] but without the pod overhead.
+pub(crate) fn pod_container_limits(
+    spec: &PodSpec,
+) -> Result<ParsedResourceList, ParseQuantityError> {
+    aggregate_pod(spec, container_limits)
+}
+
+/// Quality of service class of a pod.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QosClass {
+    /// No container has a cpu or memory request or limit
+    BestEffort,
+    /// Neither best effort nor guaranteed
+    Burstable,
+    /// Every container has equal cpu and memory requests and limits
+    Guaranteed,
+}
+
+impl QosClass {
+    /// Returns the name of the class as used in `status.qosClass`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QosClass::BestEffort => "BestEffort",
+            QosClass::Burstable => "Burstable",
+            QosClass::Guaranteed => "Guaranteed",
+        }
+    }
+}
+
+/// Returns the quality of service class of a pod, the way kubelet computes it
+/// from the cpu and memory requests and limits of all containers.
+pub fn pod_qos_class(spec: &PodSpec) -> Result<QosClass, ParseQuantityError> {
+    let mut requests = ParsedResourceList::new();
+    let mut limits = ParsedResourceList::new();
+    let mut is_guaranteed = true;
+
+    for container in spec
+        .containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+    {
+        let container_limits = container_limits(container)?;
+
+        for (list, resources) in [
+            (&mut requests, container_requests(container)?),
+            (&mut limits, container_limits.clone()),
+        ] {
+            for (name, quantity) in resources {
+                let is_positive = quantity.to_base_decimal() > Decimal::ZERO;
+                if (name == "cpu" || name == "memory") && is_positive {
+                    add_to_list(list, &name, &quantity);
+                }
+            }
+        }
+
+        let has_all_limits = ["cpu", "memory"].iter().all(|name| {
+            container_limits
+                .get(*name)
+                .is_some_and(|quantity| quantity.to_base_decimal() > Decimal::ZERO)
+        });
+        if !has_all_limits {
+            is_guaranteed = false;
+        }
+    }
+
+    if requests.is_empty() && limits.is_empty() {
+        return Ok(QosClass::BestEffort);
+    }
+
+    let limits_match_requests = requests.iter().all(|(name, request)| {
+        limits
+            .get(name)
+            .is_some_and(|limit| limit.to_base_decimal() == request.to_base_decimal())
+    });
+
+    if is_guaranteed && limits_match_requests && requests.len() == limits.len() {
+        Ok(QosClass::Guaranteed)
+    } else {
+        Ok(QosClass::Burstable)
+    }
+}
+
+fn aggregate_pod(
+    spec: &PodSpec,
+    resources_of: fn(&Container) -> Result<ParsedResourceList, ParseQuantityError>,
+) -> Result<ParsedResourceList, ParseQuantityError> {
+    let mut total = ParsedResourceList::new();
+    for container in &spec.containers {
+        add_lists(&mut total, &resources_of(container)?);
+    }
+
+    let mut sidecars = ParsedResourceList::new();
+    let mut init_peak = ParsedResourceList::new();
+
+    for container in spec.init_containers.iter().flatten() {
+        let resources = resources_of(container)?;
+
+        let running = if container.restart_policy.as_deref() == Some("Always") {
+            // Sidecars keep running next to the app containers
+            add_lists(&mut total, &resources);
+            add_lists(&mut sidecars, &resources);
+            sidecars.clone()
+        } else {
+            let mut running = resources;
+            add_lists(&mut running, &sidecars);
+            running
+        };
+
+        max_lists(&mut init_peak, &running);
+    }
+
+    max_lists(&mut total, &init_peak);
+
+    Ok(total)
+}
+
+pub(crate) fn parse_optional_list(
+    list: Option<&BTreeMap<String, Quantity>>,
+) -> Result<ParsedResourceList, ParseQuantityError> {
+    list.map(parse_resource_list)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+// --- Exact list arithmetic ---
+
+/// Returns the exact sum of two quantities, keeping the format of the lhs.
+pub(crate) fn exact_add(lhs: &ParsedQuantity, rhs: &ParsedQuantity) -> ParsedQuantity {
+    ParsedQuantity::from_base_decimal(
+        lhs.to_base_decimal() + rhs.to_base_decimal(),
+        lhs.format().clone(),
+    )
+}
+
+/// Returns a quantity with the given value in base units and the format
+/// conventionally used for the resource.
+pub(crate) fn quantity_for(resource: &str, value: Decimal) -> ParsedQuantity {
+    let format = if is_binary_resource(resource) {
+        Format::BinarySI
+    } else {
+        Format::DecimalSI
+    };
+
+    ParsedQuantity::from_base_decimal(value, format)
+}
+
+/// Returns whether the resource is conventionally expressed with binary
+/// suffixes (e.g., `memory`, `ephemeral-storage`, `hugepages-2Mi`), including
+/// quota resources such as `limits.memory`.
+pub(crate) fn is_binary_resource(resource: &str) -> bool {
+    let resource = resource.rsplit('/').next().unwrap_or(resource);
+    let resource = resource
+        .strip_prefix("requests.")
+        .or_else(|| resource.strip_prefix("limits."))
+        .unwrap_or(resource);
+
+    resource == "memory"
+        || resource == "storage"
+        || resource == "ephemeral-storage"
+        || resource.starts_with("hugepages-")
+}
+
+/// Converts a resource list into exact values in base units.
+pub(crate) fn to_base_map(list: &ParsedResourceList) -> BTreeMap<String, Decimal> {
+    list.iter()
+        .map(|(resource, quantity)| (resource.clone(), quantity.to_base_decimal()))
+        .collect()
+}
+
+/// Converts exact values in base units back into a resource list.
+pub(crate) fn from_base_map(list: &BTreeMap<String, Decimal>) -> ParsedResourceList {
+    list.iter()
+        .map(|(resource, value)| (resource.clone(), quantity_for(resource, *value)))
+        .collect()
+}
+
+/// Returns whether every positive demand fits into the available capacity.
+/// Resources missing from the capacity have none available.
+pub(crate) fn fits_within(
+    demand: &BTreeMap<String, Decimal>,
+    capacity: &BTreeMap<String, Decimal>,
+) -> bool {
+    demand.iter().all(|(resource, value)| {
+        value.is_sign_negative()
+            || value.is_zero()
+            || capacity
+                .get(resource)
+                .is_some_and(|capacity| capacity >= value)
+    })
+}
+
+pub(crate) fn add_to_list(list: &mut ParsedResourceList, name: &str, quantity: &ParsedQuantity) {
+    match list.get_mut(name) {
+        Some(existing) => *existing = exact_add(existing, quantity),
+        None => {
+            list.insert(name.to_owned(), quantity.clone());
+        }
+    }
+}
+
+pub(crate) fn add_lists(lhs: &mut ParsedResourceList, rhs: &ParsedResourceList) {
+    for (name, quantity) in rhs {
+        add_to_list(lhs, name, quantity);
+    }
+}
+
+pub(crate) fn max_lists(lhs: &mut ParsedResourceList, rhs: &ParsedResourceList) {
+    for (name, quantity) in rhs {
+        match lhs.get_mut(name) {
+            Some(existing) => {
+                if quantity.to_base_decimal() > existing.to_base_decimal() {
+                    *existing = quantity.clone();
+                }
+            }
+            None => {
+                lhs.insert(name.clone(), quantity.clone());
+            }
+        }
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::container;
+
+    #[test]
+    fn test_container_requests_default_to_limits() {
+        let container = container(
+            "app",
+            &[("cpu", "100m")],
+            &[("cpu", "1"), ("memory", "1Gi")],
+        );
+        let requests = container_requests(&container).unwrap();
+
+        assert_eq!(requests["cpu"].to_string(), "100m");
+        assert_eq!(requests["memory"].to_string(), "1Gi");
+    }
+
+    #[test]
+    fn test_pod_requests_sum_containers() {
+        let spec = PodSpec {
+            containers: vec![
+                container("a", &[("cpu", "100m"), ("memory", "1Gi")], &[]),
+                container("b", &[("cpu", "0.5"), ("memory", "512Mi")], &[]),
+            ],
+            ..Default::default()
+        };
+        let requests = pod_requests(&spec).unwrap();
+
+        assert_eq!(requests["cpu"].to_base_decimal(), Decimal::new(6, 1));
+        assert_eq!(
+            requests["memory"].to_base_decimal(),
+            Decimal::from(1610612736)
+        );
+    }
+
+    #[test]
+    fn test_pod_requests_with_sidecars_and_overhead() {
+        let mut sidecar = container("sidecar", &[("cpu", "200m")], &[]);
+        sidecar.restart_policy = Some("Always".to_owned());
+
+        let spec = PodSpec {
+            containers: vec![container("app", &[("cpu", "1")], &[])],
+            init_containers: Some(vec![sidecar, container("init", &[("cpu", "1500m")], &[])]),
+            overhead: Some(BTreeMap::from([(
+                "cpu".to_owned(),
+                Quantity("50m".to_owned()),
+            )])),
+            ..Default::default()
+        };
+        let requests = pod_requests(&spec).unwrap();
+
+        // max(1 + 0.2, 1.5 + 0.2) + 0.05
+        assert_eq!(requests["cpu"].to_base_decimal(), Decimal::new(175, 2));
+    }
+
+    #[test]
+    fn test_pod_qos_class() {
+        let spec = |containers: Vec<Container>| PodSpec {
+            containers,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            pod_qos_class(&spec(vec![container("a", &[], &[])])).unwrap(),
+            QosClass::BestEffort
+        );
+        assert_eq!(
+            pod_qos_class(&spec(vec![container(
+                "a",
+                &[],
+                &[("cpu", "1"), ("memory", "1Gi")]
+            )]))
+            .unwrap(),
+            QosClass::Guaranteed
+        );
+        assert_eq!(
+            pod_qos_class(&spec(vec![
+                container("a", &[], &[("cpu", "1"), ("memory", "1Gi")]),
+                container("b", &[("cpu", "100m")], &[]),
+            ]))
+            .unwrap(),
+            QosClass::Burstable
+        );
+        assert_eq!(
+            pod_qos_class(&spec(vec![container(
+                "a",
+                &[("cpu", "500m"), ("memory", "1Gi")],
+                &[("cpu", "1"), ("memory", "1Gi")]
+            )]))
+            .unwrap(),
+            QosClass::Burstable
+        );
+    }
+
+    #[test]
+    fn test_pod_limits_overhead_only_on_limited_resources() {
+        let spec = PodSpec {
+            containers: vec![container("app", &[], &[("memory", "1Gi")])],
+            overhead: Some(BTreeMap::from([
+                ("cpu".to_owned(), Quantity("50m".to_owned())),
+                ("memory".to_owned(), Quantity("64Mi".to_owned())),
+            ])),
+            ..Default::default()
+        };
+        let limits = pod_limits(&spec).unwrap();
+
+        assert!(!limits.contains_key("cpu"));
+        assert_eq!(
+            limits["memory"].to_base_decimal(),
+            Decimal::from(1140850688)
+        );
+    }
+}
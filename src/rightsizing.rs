@@ -0,0 +1,617 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::Container;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+use crate::{
+    format::Format,
+    resources::{container_limits, container_requests, quantity_for, ParsedResourceList},
+    ParseQuantityError, ParsedQuantity, Rounding,
+};
+
+/// Columns of the CSV report, in order
+const CSV_HEADER: [&str; 12] = [
+    "pod",
+    "container",
+    "resource",
+    "samples",
+    "p50",
+    "p95",
+    "max",
+    "request",
+    "limit",
+    "status",
+    "suggestedRequest",
+    "suggestedLimit",
+];
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum RightSizingError {
+    /// The increment suggestions are rounded to is not greater than zero
+    #[error("{resource} increment must be greater than zero, got {increment}")]
+    InvalidIncrement { resource: String, increment: String },
+
+    /// A quantity of the container could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+
+    /// The report could not be serialized
+    #[cfg(feature = "serde")]
+    #[error("failed to serialize report: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+// --- Options and input ---
+
+/// Thresholds and rounding of the right-sizing report. Thresholds and
+/// headroom are given in percent.
+#[derive(Debug, Clone)]
+pub struct RightSizingOptions {
+    /// A resource is over-provisioned if its p95 usage is below this share
+    /// of the request
+    pub over_provisioned_threshold: Decimal,
+    /// A resource is under-provisioned if its max usage reaches this share of
+    /// the limit, i.e., it risks throttling or OOM kills
+    pub under_provisioned_threshold: Decimal,
+    /// Headroom added to the p95 usage for the suggested request
+    pub request_headroom: Decimal,
+    /// Headroom added to the max usage for the suggested limit
+    pub limit_headroom: Decimal,
+    /// Increments suggestions are rounded up to, by resource. Suggestions for
+    /// other resources are not rounded.
+    pub increments: ParsedResourceList,
+}
+
+impl Default for RightSizingOptions {
+    fn default() -> Self {
+        Self {
+            over_provisioned_threshold: Decimal::from(50),
+            under_provisioned_threshold: Decimal::from(90),
+            request_headroom: Decimal::from(15),
+            limit_headroom: Decimal::from(20),
+            increments: BTreeMap::from([
+                (
+                    "cpu".to_owned(),
+                    ParsedQuantity::from_milli_decimal(Decimal::TEN),
+                ),
+                (
+                    "memory".to_owned(),
+                    ParsedQuantity::from_base_decimal(
+                        Decimal::from(16 * 1024 * 1024),
+                        Format::BinarySI,
+                    ),
+                ),
+            ]),
+        }
+    }
+}
+
+/// Usage samples of a container together with its requests and limits.
+#[derive(Debug, Clone, Default)]
+pub struct ContainerUsage {
+    /// Name of the pod or workload the container belongs to
+    pub pod: String,
+    /// Name of the container
+    pub container: String,
+    /// Requests of the container
+    pub requests: ParsedResourceList,
+    /// Limits of the container
+    pub limits: ParsedResourceList,
+    /// Usage samples by resource
+    pub samples: BTreeMap<String, Vec<ParsedQuantity>>,
+}
+
+impl ContainerUsage {
+    /// Creates the usage of a container from its spec, where requests
+    /// default to limits.
+    pub fn from_container(
+        pod: impl Into<String>,
+        container: &Container,
+        samples: BTreeMap<String, Vec<ParsedQuantity>>,
+    ) -> Result<Self, ParseQuantityError> {
+        Ok(Self {
+            pod: pod.into(),
+            container: container.name.clone(),
+            requests: container_requests(container)?,
+            limits: container_limits(container)?,
+            samples,
+        })
+    }
+}
+
+// --- Report ---
+
+/// Provisioning status of a resource of a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProvisioningStatus {
+    /// The request matches the usage
+    Balanced,
+    /// The usage is far below the request
+    OverProvisioned,
+    /// The usage exceeds the request or gets close to the limit
+    UnderProvisioned,
+}
+
+impl ProvisioningStatus {
+    /// Returns the name of the status as used in the report.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProvisioningStatus::Balanced => "balanced",
+            ProvisioningStatus::OverProvisioned => "over-provisioned",
+            ProvisioningStatus::UnderProvisioned => "under-provisioned",
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ProvisioningStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Usage statistics and suggestions for one resource of a container.
+/// Quantities are serialized in their canonical form.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ResourceReport {
+    /// Name of the pod or workload
+    pub pod: String,
+    /// Name of the container
+    pub container: String,
+    /// Name of the resource
+    pub resource: String,
+    /// Number of usage samples
+    pub samples: usize,
+    /// Median usage
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_quantity"))]
+    pub p50: ParsedQuantity,
+    /// 95th percentile of the usage
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_quantity"))]
+    pub p95: ParsedQuantity,
+    /// Maximum usage
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_quantity"))]
+    pub max: ParsedQuantity,
+    /// Current request, if any
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_optional_quantity")
+    )]
+    pub request: Option<ParsedQuantity>,
+    /// Current limit, if any
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_optional_quantity")
+    )]
+    pub limit: Option<ParsedQuantity>,
+    /// Provisioning status of the resource
+    pub status: ProvisioningStatus,
+    /// Suggested request, i.e., the p95 usage plus headroom
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_quantity"))]
+    pub suggested_request: ParsedQuantity,
+    /// Suggested limit, i.e., the max usage plus headroom, if the container
+    /// has a limit
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_optional_quantity")
+    )]
+    pub suggested_limit: Option<ParsedQuantity>,
+}
+
+/// Right-sizing report with one row per resource of each container.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RightSizingReport {
+    /// Rows in the order of the containers, by resource name
+    pub resources: Vec<ResourceReport>,
+}
+
+impl RightSizingReport {
+    /// Returns the rows with the given status.
+    pub fn with_status(
+        &self,
+        status: ProvisioningStatus,
+    ) -> impl Iterator<Item = &ResourceReport> + '_ {
+        self.resources
+            .iter()
+            .filter(move |report| report.status == status)
+    }
+
+    /// Returns the report as CSV with a header row. Missing values are empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_HEADER.join(",");
+        csv.push('\n');
+
+        for report in &self.resources {
+            let optional = |quantity: &Option<ParsedQuantity>| {
+                quantity
+                    .as_ref()
+                    .map(ParsedQuantity::to_canonical_string)
+                    .unwrap_or_default()
+            };
+            let fields = [
+                csv_field(&report.pod),
+                csv_field(&report.container),
+                csv_field(&report.resource),
+                report.samples.to_string(),
+                report.p50.to_canonical_string(),
+                report.p95.to_canonical_string(),
+                report.max.to_canonical_string(),
+                optional(&report.request),
+                optional(&report.limit),
+                report.status.as_str().to_owned(),
+                report.suggested_request.to_canonical_string(),
+                optional(&report.suggested_limit),
+            ];
+
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Returns the report as pretty-printed JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, RightSizingError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Compares the usage of containers with their requests and limits and
+/// suggests new values.
+///
+/// A resource is under-provisioned if its p95 usage exceeds the request
+/// (a missing request counts as zero) or its max usage reaches the
+/// threshold of the limit, and over-provisioned if its p95 usage is below
+/// the threshold of the request. Resources without samples are skipped.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use kube_quantity::rightsizing::{
+///     right_sizing_report, ContainerUsage, ProvisioningStatus, RightSizingOptions,
+/// };
+///
+/// let usage = ContainerUsage {
+///     pod: "web".to_string(),
+///     container: "app".to_string(),
+///     requests: BTreeMap::from([("cpu".to_string(), "2".try_into().unwrap())]),
+///     samples: BTreeMap::from([(
+///         "cpu".to_string(),
+///         ["120m", "180m", "250m", "210m"].map(|value| value.try_into().unwrap()).to_vec(),
+///     )]),
+///     ..Default::default()
+/// };
+///
+/// let report = right_sizing_report(&[usage], &RightSizingOptions::default()).unwrap();
+/// let cpu = &report.resources[0];
+///
+/// assert_eq!(cpu.status, ProvisioningStatus::OverProvisioned);
+/// assert_eq!(cpu.p95.to_string(), "250m");
+/// // 250m plus 15% headroom, rounded up to 10m
+/// assert_eq!(cpu.suggested_request.to_canonical_string(), "290m");
+/// ```
+pub fn right_sizing_report(
+    containers: &[ContainerUsage],
+    options: &RightSizingOptions,
+) -> Result<RightSizingReport, RightSizingError> {
+    for (resource, increment) in &options.increments {
+        if increment.to_base_decimal() <= Decimal::ZERO {
+            return Err(RightSizingError::InvalidIncrement {
+                resource: resource.clone(),
+                increment: increment.to_canonical_string(),
+            });
+        }
+    }
+
+    let hundred = Decimal::ONE_HUNDRED;
+    let mut report = RightSizingReport::default();
+    for usage in containers {
+        for (resource, samples) in &usage.samples {
+            let (Some(p50), Some(p95), Some(max)) = (
+                percentile(samples, Decimal::from(50)),
+                percentile(samples, Decimal::from(95)),
+                percentile(samples, hundred),
+            ) else {
+                continue;
+            };
+            let request = usage.requests.get(resource).cloned();
+            let limit = usage.limits.get(resource).cloned();
+
+            let request_value = request
+                .as_ref()
+                .map_or(Decimal::ZERO, ParsedQuantity::to_base_decimal);
+            let limit_value = limit.as_ref().map(ParsedQuantity::to_base_decimal);
+            let p95_value = p95.to_base_decimal();
+            let max_value = max.to_base_decimal();
+
+            let status = if p95_value > request_value
+                || limit_value.is_some_and(|limit| {
+                    max_value * hundred >= limit * options.under_provisioned_threshold
+                }) {
+                ProvisioningStatus::UnderProvisioned
+            } else if p95_value * hundred < request_value * options.over_provisioned_threshold {
+                ProvisioningStatus::OverProvisioned
+            } else {
+                ProvisioningStatus::Balanced
+            };
+
+            let suggest = |value: Decimal, headroom: Decimal| {
+                let value = value * (hundred + headroom) / hundred;
+                let value = match options.increments.get(resource) {
+                    Some(increment) => {
+                        let increment = increment.to_base_decimal();
+                        (Rounding::Up.round_dp(value / increment, 0) * increment).max(increment)
+                    }
+                    None => value,
+                };
+                quantity_for(resource, value)
+            };
+
+            report.resources.push(ResourceReport {
+                pod: usage.pod.clone(),
+                container: usage.container.clone(),
+                resource: resource.clone(),
+                samples: samples.len(),
+                suggested_request: suggest(p95_value, options.request_headroom),
+                suggested_limit: limit
+                    .as_ref()
+                    .map(|_| suggest(max_value, options.limit_headroom)),
+                p50,
+                p95,
+                max,
+                request,
+                limit,
+                status,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns the sample at the percentile by the nearest-rank method, i.e.,
+/// the smallest sample that at least the given percent of all samples do
+/// not exceed. The result is always one of the samples.
+///
+/// ```rust
+/// use kube_quantity::{rightsizing::percentile, ParsedQuantity};
+/// use rust_decimal::{prelude::ToPrimitive, Decimal};
+///
+/// let samples: Vec<ParsedQuantity> = ["15Mi", "20Mi", "35Mi", "40Mi", "50Mi"]
+///     .iter()
+///     .map(|value| (*value).try_into().unwrap())
+///     .collect();
+///
+/// assert_eq!(percentile(&samples, Decimal::from(30)).unwrap().to_string(), "20Mi");
+/// assert_eq!(percentile(&samples, Decimal::from(50)).unwrap().to_string(), "35Mi");
+/// assert_eq!(percentile(&samples, Decimal::from(100)).unwrap().to_string(), "50Mi");
+/// assert!(percentile(&[], Decimal::from(50)).is_none());
+/// ```
+pub fn percentile(samples: &[ParsedQuantity], percentile: Decimal) -> Option<ParsedQuantity> {
+    let mut sorted: Vec<&ParsedQuantity> = samples.iter().collect();
+    sorted.sort_by_cached_key(|quantity| quantity.to_base_decimal());
+
+    let count = Decimal::from(sorted.len());
+    let percentile = percentile.clamp(Decimal::ZERO, Decimal::ONE_HUNDRED);
+    let rank = Rounding::Up.round_dp(percentile * count / Decimal::ONE_HUNDRED, 0);
+    let index = rank.to_usize().unwrap_or_default().saturating_sub(1);
+
+    sorted.get(index).map(|quantity| (*quantity).clone())
+}
+
+#[cfg(feature = "serde")]
+fn serialize_quantity<S: Serializer>(
+    quantity: &ParsedQuantity,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&quantity.to_canonical_string())
+}
+
+#[cfg(feature = "serde")]
+fn serialize_optional_quantity<S: Serializer>(
+    quantity: &Option<ParsedQuantity>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match quantity {
+        Some(quantity) => serialize_quantity(quantity, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixture, list};
+    use k8s_openapi::api::core::v1::ResourceRequirements;
+
+    fn samples(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<ParsedQuantity>> {
+        entries
+            .iter()
+            .map(|(resource, values)| {
+                let values = values
+                    .iter()
+                    .map(|value| (*value).try_into().unwrap())
+                    .collect();
+                (resource.to_string(), values)
+            })
+            .collect()
+    }
+
+    fn report() -> RightSizingReport {
+        let app = Container {
+            name: "app".to_owned(),
+            resources: Some(ResourceRequirements {
+                requests: Some(list(&[("cpu", "500m"), ("memory", "512Mi")])),
+                limits: Some(list(&[("cpu", "1"), ("memory", "1Gi")])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let sidecar = Container {
+            name: "sidecar".to_owned(),
+            resources: Some(ResourceRequirements {
+                requests: Some(list(&[("cpu", "50m")])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let containers = [
+            ContainerUsage::from_container(
+                "web",
+                &app,
+                samples(&[
+                    (
+                        "cpu",
+                        &[
+                            "100m", "120m", "150m", "130m", "110m", "140m", "160m", "90m", "105m",
+                            "115m",
+                        ],
+                    ),
+                    ("memory", &["900Mi", "950Mi", "980Mi", "920Mi"]),
+                ]),
+            )
+            .unwrap(),
+            ContainerUsage::from_container(
+                "web",
+                &sidecar,
+                samples(&[
+                    ("cpu", &["40m", "45m", "30m"]),
+                    ("memory", &["20Mi"]),
+                    ("ephemeral-storage", &[]),
+                ]),
+            )
+            .unwrap(),
+        ];
+
+        right_sizing_report(&containers, &RightSizingOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_golden_reports() {
+        let report = report();
+
+        assert_eq!(report.to_csv(), fixture("rightsizing/report.csv"));
+        assert_eq!(
+            report
+                .with_status(ProvisioningStatus::UnderProvisioned)
+                .map(|row| (row.container.as_str(), row.resource.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("app", "memory"), ("sidecar", "memory")]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_golden_json_report() {
+        use serde_json::Value;
+
+        assert_eq!(
+            serde_json::from_str::<Value>(&report().to_json().unwrap()).unwrap(),
+            serde_json::from_str::<Value>(&fixture("rightsizing/report.json")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_limit_thresholds() {
+        let usage = |request: &str, limit: &str| ContainerUsage {
+            pod: "batch".to_owned(),
+            container: "job".to_owned(),
+            requests: BTreeMap::from([("cpu".to_owned(), request.try_into().unwrap())]),
+            limits: BTreeMap::from([("cpu".to_owned(), limit.try_into().unwrap())]),
+            samples: samples(&[("cpu", &["500m", "600m", "1800m"])]),
+        };
+        let status = |request: &str, limit: &str| {
+            right_sizing_report(&[usage(request, limit)], &RightSizingOptions::default())
+                .unwrap()
+                .resources[0]
+                .status
+        };
+
+        // The max usage risks throttling at 90% of the limit
+        assert_eq!(status("2", "2"), ProvisioningStatus::UnderProvisioned);
+        assert_eq!(status("2", "2001m"), ProvisioningStatus::Balanced);
+        // ... as well as a p95 usage above the request
+        assert_eq!(status("1", "4"), ProvisioningStatus::UnderProvisioned);
+
+        let options = RightSizingOptions {
+            over_provisioned_threshold: Decimal::from(70),
+            increments: ParsedResourceList::new(),
+            ..Default::default()
+        };
+        let report = right_sizing_report(&[usage("3", "4")], &options).unwrap();
+        let row = &report.resources[0];
+        assert_eq!(row.status, ProvisioningStatus::OverProvisioned);
+        // Without an increment the suggestions are exact
+        assert_eq!(row.suggested_request.to_canonical_string(), "2070m");
+        assert_eq!(
+            row.suggested_limit.as_ref().unwrap().to_canonical_string(),
+            "2160m"
+        );
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<ParsedQuantity> = ["3", "1Ki", "1k", "-1", "500m"]
+            .iter()
+            .map(|value| (*value).try_into().unwrap())
+            .collect();
+        let at = |percent: i64| {
+            percentile(&values, Decimal::from(percent))
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(at(0), "-1");
+        assert_eq!(at(20), "-1");
+        assert_eq!(at(21), "500m");
+        assert_eq!(at(60), "3");
+        assert_eq!(at(80), "1k");
+        assert_eq!(at(100), "1Ki");
+        assert_eq!(at(200), "1Ki");
+    }
+
+    #[test]
+    fn test_errors_and_quoting() {
+        let options = RightSizingOptions {
+            increments: BTreeMap::from([("cpu".to_owned(), "0".try_into().unwrap())]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            right_sizing_report(&[], &options),
+            Err(RightSizingError::InvalidIncrement { .. })
+        ));
+
+        let container = Container {
+            resources: Some(ResourceRequirements {
+                limits: Some(list(&[("cpu", "1.2.3")])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(ContainerUsage::from_container("web", &container, BTreeMap::new()).is_err());
+
+        assert_eq!(csv_field("web"), "web");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
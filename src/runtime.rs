@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core::v1::ResourceRequirements;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use thiserror::Error;
+
+use crate::{
+    format::Format, resources::ParsedResourceList, Limit, ParseQuantityError, ParsedQuantity,
+    QuantityOrPercent, Rounding,
+};
+
+/// Environment variable limiting the number of OS threads running Go code
+pub const GOMAXPROCS: &str = "GOMAXPROCS";
+/// Environment variable setting the soft memory limit of the Go runtime
+pub const GOMEMLIMIT: &str = "GOMEMLIMIT";
+
+const KIBI: i128 = 1024;
+
+// --- Errors ---
+
+#[derive(Debug, Error)]
+pub enum RuntimeError {
+    /// The memory headroom leaves no memory for the runtime
+    #[error("memory headroom {headroom} leaves no memory within {limit}")]
+    InvalidHeadroom { headroom: String, limit: String },
+
+    /// The memory granularity is not greater than zero
+    #[error("memory granularity must be greater than zero, got {0}")]
+    InvalidGranularity(String),
+
+    /// A quantity of the container could not be parsed
+    #[error("invalid quantity: {0}")]
+    InvalidQuantity(#[from] ParseQuantityError),
+}
+
+// --- Runtime settings ---
+
+/// Options for deriving runtime settings from container resources.
+#[derive(Debug, Clone)]
+pub struct RuntimeOptions {
+    /// Rounding of the CPU limit to whole cores for `GOMAXPROCS`, where
+    /// [`Rounding::Exact`] rounds up
+    pub cpu_rounding: Rounding,
+    /// Memory kept free of the Go heap, relative to the memory limit
+    pub go_memory_headroom: QuantityOrPercent,
+    /// Memory kept free of the JVM heap, relative to the memory limit
+    pub jvm_memory_headroom: QuantityOrPercent,
+    /// Granularity the memory settings are rounded to
+    pub memory_granularity: ParsedQuantity,
+    /// Rounding of the memory settings to the granularity
+    pub memory_rounding: Rounding,
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            cpu_rounding: Rounding::Up,
+            go_memory_headroom: QuantityOrPercent::Percent(Decimal::TEN),
+            jvm_memory_headroom: QuantityOrPercent::Percent(Decimal::from(25)),
+            memory_granularity: ParsedQuantity::from_base_decimal(
+                Decimal::from(KIBI * KIBI),
+                Format::BinarySI,
+            ),
+            memory_rounding: Rounding::Down,
+        }
+    }
+}
+
+/// Runtime settings derived from the resources of a container. Settings are
+/// missing if neither the container nor the node limits the resource.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeSettings {
+    /// `GOMAXPROCS`, i.e., the CPU limit in whole cores, at least one
+    pub gomaxprocs: Option<u64>,
+    /// `GOMEMLIMIT` in bytes
+    pub gomemlimit: Option<ParsedQuantity>,
+    /// Maximum JVM heap size in bytes, i.e., `-Xmx`
+    pub jvm_max_heap: Option<ParsedQuantity>,
+    /// Maximum JVM heap size relative to the memory limit, i.e.,
+    /// `-XX:MaxRAMPercentage`
+    pub jvm_max_ram_percentage: Option<Decimal>,
+}
+
+impl RuntimeSettings {
+    /// Returns the `GOMAXPROCS` and `GOMEMLIMIT` environment variables, e.g.,
+    /// `GOMEMLIMIT=921MiB`.
+    pub fn go_env(&self) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::new();
+        if let Some(gomaxprocs) = self.gomaxprocs {
+            env.insert(GOMAXPROCS.to_owned(), gomaxprocs.to_string());
+        }
+        if let Some(gomemlimit) = &self.gomemlimit {
+            env.insert(
+                GOMEMLIMIT.to_owned(),
+                format_bytes(gomemlimit, &["B", "KiB", "MiB", "GiB", "TiB"]),
+            );
+        }
+
+        env
+    }
+
+    /// Returns the `-Xmx` option of the JVM, e.g., `-Xmx768m`.
+    pub fn jvm_xmx_option(&self) -> Option<String> {
+        self.jvm_max_heap
+            .as_ref()
+            .map(|heap| format!("-Xmx{}", format_bytes(heap, &["", "k", "m", "g"])))
+    }
+
+    /// Returns the `-XX:MaxRAMPercentage` option of the JVM, e.g.,
+    /// `-XX:MaxRAMPercentage=75.0`.
+    pub fn jvm_max_ram_percentage_option(&self) -> Option<String> {
+        self.jvm_max_ram_percentage.map(|percentage| {
+            let percentage = percentage.normalize();
+            if percentage.fract().is_zero() {
+                format!("-XX:MaxRAMPercentage={percentage}.0")
+            } else {
+                format!("-XX:MaxRAMPercentage={percentage}")
+            }
+        })
+    }
+}
+
+/// Derives the runtime settings of a container from its limits. Missing
+/// limits are replaced by the node allocatable, which is what the container
+/// can use at most.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use k8s_openapi::api::core::v1::ResourceRequirements;
+/// use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+/// use kube_quantity::runtime::{runtime_settings, RuntimeOptions};
+///
+/// let requirements = ResourceRequirements {
+///     limits: Some(BTreeMap::from([
+///         ("cpu".to_string(), Quantity("1500m".to_string())),
+///         ("memory".to_string(), Quantity("1Gi".to_string())),
+///     ])),
+///     ..Default::default()
+/// };
+///
+/// let settings = runtime_settings(&requirements, &BTreeMap::new(), &RuntimeOptions::default()).unwrap();
+///
+/// assert_eq!(settings.go_env()["GOMAXPROCS"], "2");
+/// assert_eq!(settings.go_env()["GOMEMLIMIT"], "921MiB");
+/// assert_eq!(settings.jvm_xmx_option().unwrap(), "-Xmx768m");
+/// assert_eq!(settings.jvm_max_ram_percentage_option().unwrap(), "-XX:MaxRAMPercentage=75.0");
+/// ```
+pub fn runtime_settings(
+    requirements: &ResourceRequirements,
+    node_allocatable: &ParsedResourceList,
+    options: &RuntimeOptions,
+) -> Result<RuntimeSettings, RuntimeError> {
+    let granularity = options.memory_granularity.to_base_decimal();
+    if granularity <= Decimal::ZERO {
+        return Err(RuntimeError::InvalidGranularity(
+            options.memory_granularity.to_canonical_string(),
+        ));
+    }
+
+    let mut settings = RuntimeSettings::default();
+
+    if let Some(cpu) = effective_limit(requirements, node_allocatable, "cpu")? {
+        let rounding = match options.cpu_rounding {
+            Rounding::Exact => Rounding::Up,
+            rounding => rounding,
+        };
+        let cores = rounding.round_dp(cpu.to_base_decimal(), 0);
+        settings.gomaxprocs = Some(cores.to_u64().unwrap_or(u64::MAX).max(1));
+    }
+
+    if let Some(memory) = effective_limit(requirements, node_allocatable, "memory")? {
+        let limit = memory.to_base_decimal();
+        let budget = |headroom: &QuantityOrPercent| {
+            let value = limit - headroom.resolve(&memory, Rounding::Exact).to_base_decimal();
+            let value = options.memory_rounding.round_dp(value / granularity, 0) * granularity;
+            if value <= Decimal::ZERO {
+                return Err(RuntimeError::InvalidHeadroom {
+                    headroom: headroom.to_string(),
+                    limit: memory.to_canonical_string(),
+                });
+            }
+
+            Ok(ParsedQuantity::from_base_decimal(value, Format::BinarySI))
+        };
+
+        settings.gomemlimit = Some(budget(&options.go_memory_headroom)?);
+        settings.jvm_max_heap = Some(budget(&options.jvm_memory_headroom)?);
+
+        let headroom = options
+            .jvm_memory_headroom
+            .resolve(&memory, Rounding::Exact)
+            .to_base_decimal();
+        let percentage = (limit - headroom) / limit * Decimal::ONE_HUNDRED;
+        settings.jvm_max_ram_percentage = Some(options.memory_rounding.round_dp(percentage, 1));
+    }
+
+    Ok(settings)
+}
+
+/// Returns the limit of the resource, falling back to the node allocatable.
+fn effective_limit(
+    requirements: &ResourceRequirements,
+    node_allocatable: &ParsedResourceList,
+    resource: &str,
+) -> Result<Option<ParsedQuantity>, ParseQuantityError> {
+    Ok(match Limit::from_requirements(requirements, resource)? {
+        Limit::Limited(limit) => Some(limit),
+        Limit::Unlimited => node_allocatable.get(resource).cloned(),
+    })
+}
+
+/// Formats whole bytes with the largest binary unit that divides them
+/// exactly, where the units are powers of 1024 starting at bytes.
+fn format_bytes(quantity: &ParsedQuantity, units: &[&str]) -> String {
+    let Some(mut value) = quantity.to_base_decimal().ceil().to_i128() else {
+        return quantity.to_base_decimal().ceil().to_string();
+    };
+
+    let mut unit = 0;
+    while unit + 1 < units.len() && value != 0 && value % KIBI == 0 {
+        value /= KIBI;
+        unit += 1;
+    }
+
+    format!("{value}{}", units[unit])
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::list;
+
+    fn requirements(limits: &[(&str, &str)]) -> ResourceRequirements {
+        ResourceRequirements {
+            limits: Some(list(limits)),
+            ..Default::default()
+        }
+    }
+
+    fn settings(limits: &[(&str, &str)], options: &RuntimeOptions) -> RuntimeSettings {
+        let allocatable = BTreeMap::from([
+            ("cpu".to_owned(), "3920m".try_into().unwrap()),
+            ("memory".to_owned(), "15Gi".try_into().unwrap()),
+        ]);
+
+        runtime_settings(&requirements(limits), &allocatable, options).unwrap()
+    }
+
+    #[test]
+    fn test_gomaxprocs() {
+        let gomaxprocs = |cpu: &str, cpu_rounding| {
+            let options = RuntimeOptions {
+                cpu_rounding,
+                ..Default::default()
+            };
+            settings(&[("cpu", cpu)], &options).gomaxprocs.unwrap()
+        };
+
+        assert_eq!(gomaxprocs("100m", Rounding::Up), 1);
+        assert_eq!(gomaxprocs("100m", Rounding::Down), 1);
+        assert_eq!(gomaxprocs("2", Rounding::Up), 2);
+        assert_eq!(gomaxprocs("2001m", Rounding::Exact), 3);
+        assert_eq!(gomaxprocs("2500m", Rounding::Nearest), 3);
+        assert_eq!(gomaxprocs("2499m", Rounding::Down), 2);
+    }
+
+    #[test]
+    fn test_memory_settings() {
+        let defaults = settings(&[("memory", "1000Mi")], &RuntimeOptions::default());
+        // 900Mi and 750Mi
+        assert_eq!(defaults.go_env()[GOMEMLIMIT], "900MiB");
+        assert_eq!(defaults.jvm_xmx_option().unwrap(), "-Xmx750m");
+
+        let options = RuntimeOptions {
+            go_memory_headroom: "100Mi".parse().unwrap(),
+            jvm_memory_headroom: "33%".parse().unwrap(),
+            memory_granularity: "1".try_into().unwrap(),
+            ..Default::default()
+        };
+        let custom = settings(&[("memory", "1G")], &options);
+        assert_eq!(custom.go_env()[GOMEMLIMIT], "895142400B");
+        assert_eq!(custom.jvm_xmx_option().unwrap(), "-Xmx670000000");
+        assert_eq!(
+            custom.jvm_max_ram_percentage_option().unwrap(),
+            "-XX:MaxRAMPercentage=67.0"
+        );
+
+        let options = RuntimeOptions {
+            jvm_memory_headroom: "300Mi".parse().unwrap(),
+            ..Default::default()
+        };
+        let absolute = settings(&[("memory", "1Gi")], &options);
+        assert_eq!(absolute.jvm_xmx_option().unwrap(), "-Xmx724m");
+        // 724 / 1024 = 70.703125%
+        assert_eq!(
+            absolute.jvm_max_ram_percentage_option().unwrap(),
+            "-XX:MaxRAMPercentage=70.7"
+        );
+    }
+
+    #[test]
+    fn test_node_allocatable_fallback() {
+        let settings = settings(&[], &RuntimeOptions::default());
+
+        assert_eq!(
+            settings.go_env(),
+            BTreeMap::from([
+                (GOMAXPROCS.to_owned(), "4".to_owned()),
+                (GOMEMLIMIT.to_owned(), "13824MiB".to_owned()),
+            ])
+        );
+        assert_eq!(settings.jvm_xmx_option().unwrap(), "-Xmx11520m");
+
+        let none = runtime_settings(
+            &ResourceRequirements::default(),
+            &BTreeMap::new(),
+            &RuntimeOptions::default(),
+        )
+        .unwrap();
+        assert!(none.go_env().is_empty());
+        assert!(none.jvm_xmx_option().is_none());
+        assert!(none.jvm_max_ram_percentage_option().is_none());
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let options = RuntimeOptions {
+            go_memory_headroom: "1Gi".parse().unwrap(),
+            ..Default::default()
+        };
+        let result = runtime_settings(
+            &requirements(&[("memory", "1Gi")]),
+            &BTreeMap::new(),
+            &options,
+        );
+        assert!(matches!(result, Err(RuntimeError::InvalidHeadroom { .. })));
+
+        let options = RuntimeOptions {
+            memory_granularity: "0".try_into().unwrap(),
+            ..Default::default()
+        };
+        let result = runtime_settings(&requirements(&[]), &BTreeMap::new(), &options);
+        assert!(matches!(result, Err(RuntimeError::InvalidGranularity(_))));
+    }
+}
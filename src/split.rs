@@ -0,0 +1,264 @@
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::ParsedQuantity;
+
+// - Errors -
+
+#[derive(Debug, Error)]
+pub enum SplitError {
+    /// No weights were given
+    #[error("no weights to split by")]
+    NoWeights,
+
+    /// A weight is negative
+    #[error("weight {0} is negative")]
+    NegativeWeight(Decimal),
+
+    /// All weights are zero
+    #[error("weights sum to zero")]
+    ZeroWeights,
+
+    /// The granularity is not greater than zero
+    #[error("granularity must be greater than zero, got {0}")]
+    InvalidGranularity(String),
+
+    /// The quantity to split is negative
+    #[error("cannot split negative quantity {0}")]
+    NegativeQuantity(String),
+
+    /// The quantity is not a whole multiple of the granularity
+    #[error("{quantity} is not a multiple of {granularity}")]
+    NotMultiple {
+        quantity: String,
+        granularity: String,
+    },
+
+    /// The split exceeds the range of a decimal
+    #[error("splitting {0} overflows")]
+    Overflow(String),
+}
+
+// - Split -
+
+impl ParsedQuantity {
+    /// Splits the quantity into parts proportional to the weights, each a
+    /// whole multiple of the granularity, e.g., `1m` for CPU or `1Mi` for
+    /// memory. Units left over after rounding the shares down go to the parts
+    /// with the largest remainders, earlier parts first on ties, so that the
+    /// parts always sum exactly to the quantity.
+    ///
+    /// ```rust
+    /// use kube_quantity::ParsedQuantity;
+    ///
+    /// let cpu: ParsedQuantity = "10".try_into().unwrap();
+    /// let parts = cpu.split(&[1, 1, 1], &"1m".try_into().unwrap()).unwrap();
+    /// assert_eq!(
+    ///     parts.iter().map(|part| part.to_string()).collect::<Vec<_>>(),
+    ///     ["3334m", "3333m", "3333m"]
+    /// );
+    ///
+    /// let memory: ParsedQuantity = "37Gi".try_into().unwrap();
+    /// let parts = memory.split(&[2, 1, 1], &"1Mi".try_into().unwrap()).unwrap();
+    /// assert_eq!(
+    ///     parts.iter().map(|part| part.to_string()).collect::<Vec<_>>(),
+    ///     ["18944Mi", "9472Mi", "9472Mi"]
+    /// );
+    /// ```
+    pub fn split<W: Into<Decimal> + Copy>(
+        &self,
+        weights: &[W],
+        granularity: &ParsedQuantity,
+    ) -> Result<Vec<ParsedQuantity>, SplitError> {
+        let overflow = || SplitError::Overflow(self.to_string());
+
+        let weights: Vec<Decimal> = weights.iter().map(|weight| (*weight).into()).collect();
+        if weights.is_empty() {
+            return Err(SplitError::NoWeights);
+        }
+        if let Some(weight) = weights.iter().find(|weight| weight.is_sign_negative()) {
+            return Err(SplitError::NegativeWeight(*weight));
+        }
+        let total_weight = weights
+            .iter()
+            .try_fold(Decimal::ZERO, |total, weight| total.checked_add(*weight))
+            .ok_or_else(overflow)?;
+        if total_weight.is_zero() {
+            return Err(SplitError::ZeroWeights);
+        }
+
+        let unit = granularity.checked_base_decimal().ok_or_else(overflow)?;
+        if unit <= Decimal::ZERO {
+            return Err(SplitError::InvalidGranularity(granularity.to_string()));
+        }
+        let value = self.checked_base_decimal().ok_or_else(overflow)?;
+        if value < Decimal::ZERO {
+            return Err(SplitError::NegativeQuantity(self.to_string()));
+        }
+        if !(value % unit).is_zero() {
+            return Err(SplitError::NotMultiple {
+                quantity: self.to_string(),
+                granularity: granularity.to_string(),
+            });
+        }
+        let units = value / unit;
+
+        // Shares are kept as numerators over the total weight, so that the
+        // quotients and remainders are exact
+        let mut counts = Vec::with_capacity(weights.len());
+        let mut remainders = Vec::with_capacity(weights.len());
+        for weight in &weights {
+            let numerator = units.checked_mul(*weight).ok_or_else(overflow)?;
+            let remainder = numerator % total_weight;
+            counts.push((numerator - remainder) / total_weight);
+            remainders.push(remainder);
+        }
+
+        let mut leftover = units - counts.iter().sum::<Decimal>();
+        let mut order: Vec<usize> = (0..weights.len()).collect();
+        order.sort_by(|lhs, rhs| remainders[*rhs].cmp(&remainders[*lhs]));
+        for index in order {
+            if leftover <= Decimal::ZERO {
+                break;
+            }
+            counts[index] += Decimal::ONE;
+            leftover -= Decimal::ONE;
+        }
+
+        counts
+            .into_iter()
+            .map(|count| {
+                let part = count.checked_mul(unit).ok_or_else(overflow)?;
+                Ok(ParsedQuantity::from_base_decimal_suffixed(
+                    part,
+                    self.format().clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+// - Tests -
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantity(value: &str) -> ParsedQuantity {
+        value.try_into().unwrap()
+    }
+
+    fn split(value: &str, weights: &[u32], granularity: &str) -> Vec<String> {
+        quantity(value)
+            .split(weights, &quantity(granularity))
+            .unwrap()
+            .iter()
+            .map(|part| part.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("1", &[1, 1, 1], "1m"), ["334m", "333m", "333m"]);
+        assert_eq!(split("2", &[1, 1, 1], "1m"), ["667m", "667m", "666m"]);
+        assert_eq!(split("1Gi", &[1, 1, 1], "1Mi"), ["342Mi", "341Mi", "341Mi"]);
+        assert_eq!(split("10", &[5, 3, 2], "1"), ["5", "3", "2"]);
+        assert_eq!(split("4", &[1], "1m"), ["4"]);
+    }
+
+    #[test]
+    fn test_split_largest_remainder() {
+        // Exact shares are 2.86, 4.29, 0 and 2.86, so the two leftover units
+        // skip the second part
+        assert_eq!(split("10", &[2, 3, 0, 2], "1"), ["3", "4", "0", "3"]);
+
+        // Ties go to earlier parts
+        assert_eq!(
+            split("10", &[1, 1, 1, 1, 1, 1], "1"),
+            ["2", "2", "2", "2", "1", "1"]
+        );
+
+        // Fewer units than parts
+        assert_eq!(split("2m", &[1, 1, 1], "1m"), ["1m", "1m", "0"]);
+        assert_eq!(split("2m", &[1, 1, 2], "1m"), ["1m", "0", "1m"]);
+    }
+
+    #[test]
+    fn test_split_decimal_weights() {
+        let parts = quantity("1500m")
+            .split(
+                &[Decimal::new(5, 1), Decimal::new(25, 2), Decimal::new(25, 2)],
+                &quantity("100m"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            parts
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>(),
+            ["700m", "400m", "400m"]
+        );
+    }
+
+    #[test]
+    fn test_split_sums_exactly() {
+        for (value, granularity) in [
+            ("10", "1m"),
+            ("37Gi", "1Mi"),
+            ("1234567k", "1"),
+            // Parts above 2^53 are not exactly representable as f64
+            ("900Pi", "1"),
+            ("0", "1m"),
+        ] {
+            let whole = quantity(value);
+            for count in 1..=13 {
+                let weights: Vec<u32> = (1..=count).map(|index| index * 7 % 5).collect();
+                let parts = whole.split(&weights, &quantity(granularity)).unwrap();
+
+                let unit = quantity(granularity).to_base_decimal();
+                let total: Decimal = weights.iter().map(|weight| Decimal::from(*weight)).sum();
+                let sum: Decimal = parts.iter().map(|part| part.to_base_decimal()).sum();
+                assert_eq!(sum, whole.to_base_decimal(), "{value} by {weights:?}");
+
+                for (part, weight) in parts.iter().zip(&weights) {
+                    let share = whole.to_base_decimal() * Decimal::from(*weight) / total;
+                    assert!((part.to_base_decimal() - share).abs() < unit);
+                    assert!((part.to_base_decimal() % unit).is_zero());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_keeps_format() {
+        assert_eq!(split("3Gi", &[1, 2], "1Gi"), ["1Gi", "2Gi"]);
+        assert_eq!(split("3G", &[1, 2], "1G"), ["1G", "2G"]);
+    }
+
+    #[test]
+    fn test_split_errors() {
+        let granularity = quantity("1m");
+        let errors = [
+            quantity("1").split::<u32>(&[], &granularity),
+            quantity("1").split(&[Decimal::ONE, Decimal::NEGATIVE_ONE], &granularity),
+            quantity("1").split(&[0, 0], &granularity),
+            quantity("1").split(&[1], &quantity("0")),
+            quantity("-1").split(&[1], &granularity),
+            quantity("1500m").split(&[1], &quantity("1")),
+        ]
+        .map(|result| result.unwrap_err().to_string());
+
+        assert_eq!(
+            errors,
+            [
+                "no weights to split by",
+                "weight -1 is negative",
+                "weights sum to zero",
+                "granularity must be greater than zero, got 0",
+                "cannot split negative quantity -1",
+                "1500m is not a multiple of 1",
+            ]
+        );
+    }
+}
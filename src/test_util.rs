@@ -0,0 +1,56 @@
+// Factories shared by the unit tests of all modules.
+
+use std::collections::BTreeMap;
+
+use k8s_openapi::{
+    api::core::v1::{Container, ResourceRequirements},
+    apimachinery::pkg::api::resource::Quantity,
+};
+
+use crate::{resources::ParsedResourceList, ParsedQuantity};
+
+/// Returns a resource list, e.g., `list(&[("cpu", "500m")])`.
+pub(crate) fn list(entries: &[(&str, &str)]) -> BTreeMap<String, Quantity> {
+    entries
+        .iter()
+        .map(|(name, value)| (name.to_string(), Quantity(value.to_string())))
+        .collect()
+}
+
+/// Returns a resource list of parsed quantities.
+pub(crate) fn parsed_list(entries: &[(&str, &str)]) -> ParsedResourceList {
+    entries
+        .iter()
+        .map(|(name, value)| (name.to_string(), ParsedQuantity::try_from(*value).unwrap()))
+        .collect()
+}
+
+/// Returns a container with the given requests and limits. Empty lists are
+/// left unset.
+pub(crate) fn container(
+    name: &str,
+    requests: &[(&str, &str)],
+    limits: &[(&str, &str)],
+) -> Container {
+    let optional = |entries: &[(&str, &str)]| (!entries.is_empty()).then(|| list(entries));
+
+    Container {
+        name: name.to_owned(),
+        resources: Some(ResourceRequirements {
+            requests: optional(requests),
+            limits: optional(limits),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Returns the path of a file in `tests/fixtures`, e.g., `cgroup/v2/cpu.max`.
+pub(crate) fn fixture_path(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Returns the content of a file in `tests/fixtures`.
+pub(crate) fn fixture(name: &str) -> String {
+    std::fs::read_to_string(fixture_path(name)).unwrap()
+}